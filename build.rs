@@ -21,9 +21,26 @@ use protobuf_codegen::Customize;
 const UPROTOCOL_BASE_URI: &str =
     "https://raw.githubusercontent.com/eclipse-uprotocol/up-spec/main/up-core-api/";
 const UPROTOCOL_REL_PATH: &str = "uprotocol/";
+#[cfg(feature = "grpc")]
+const GOOGLEAPIS_BASE_URI: &str = "https://raw.githubusercontent.com/googleapis/googleapis/master/";
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "grpc")]
     get_and_build_protos(
+        GOOGLEAPIS_BASE_URI,
+        &[format!("{}google/rpc/status.proto", GOOGLEAPIS_BASE_URI).as_str()],
+        "google",
+    )
+    .map_err(|e| {
+        println!(
+            "failed to generate types from google.rpc proto3 definitions: {}",
+            e
+        );
+        e
+    })?;
+
+    get_and_build_protos(
+        UPROTOCOL_BASE_URI,
         &[
             // uProtocol-project proto definitions
             format!("{}{}uoptions.proto", UPROTOCOL_BASE_URI, UPROTOCOL_REL_PATH).as_str(),
@@ -80,6 +97,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 // Fetch protobuf definitions from `url`, and build them with prost_build
 fn get_and_build_protos(
+    base_uri: &str,
     urls: &[&str],
     output_folder: &str,
 ) -> core::result::Result<(), Box<dyn std::error::Error>> {
@@ -89,7 +107,7 @@ fn get_and_build_protos(
 
     for url in urls {
         // Extract relative filename from the URL
-        let filename = url.strip_prefix(UPROTOCOL_BASE_URI).unwrap_or_default();
+        let filename = url.strip_prefix(base_uri).unwrap_or_default();
         let dest_path = proto_folder.join(filename);
 
         // Download the .proto file
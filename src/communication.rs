@@ -12,24 +12,57 @@
  ********************************************************************************/
 
 use bytes::Bytes;
+pub use channel_listener::{ChannelListener, MessageReceiver, MessageStream, OverflowPolicy};
+pub use chunking::{ChunkedPayloadAssembler, ChunkedPayloadSplitter};
+pub use composite_listener::{CompositeListener, DispatchMode};
+pub use compression::{CompressionAlgorithm, DecompressionError, DEFAULT_MAX_DECOMPRESSED_SIZE};
+#[cfg(feature = "dynamic")]
+pub use dynamic_payload::{DescriptorRegistry, DynamicPayload};
+pub use in_memory_rpc_client::InMemoryRpcClient;
+pub use in_memory_rpc_server::{
+    DropCallback, DropReason, EndpointGuard, EndpointSpec, InMemoryRpcServer, MethodStats,
+    RateLimit, RequestRouter, ResponseInterceptor, SendErrorCallback, ValidationMode,
+};
 pub use notification::{NotificationError, NotificationListener, Notifier};
-use protobuf::Message;
+use protobuf::{well_known_types::any::Any, Message};
 pub use pubsub::{PubSubError, Publisher, Subscriber};
-pub use rpc::{RpcClient, RpcServer, ServiceInvocationError};
-use std::{error::Error, fmt::Display};
+pub use retrying_rpc_client::RetryingRpcClient;
+pub use rpc::{
+    preferred_response_format, stream_index, EchoHandler, MethodId, RequestContext, RequestHandler,
+    RpcClient, RpcServer, ServiceInvocationError, StreamingRequestHandler,
+};
+#[cfg(feature = "json")]
+pub use serialization::JsonSerializer;
+pub use serialization::{PayloadDeserializer, PayloadSerializer, ProtobufSerializer};
+use std::{error::Error, fmt::Display, time::Duration};
+use tokio_util::sync::CancellationToken;
+pub use typed_listener::TypedListener;
 
 use crate::{
     umessage::{self, UMessageError},
-    UPayloadFormat, UPriority, UUID,
+    UAttributes, UCode, UPayloadFormat, UPriority, UStatus, UUID,
 };
 
+mod channel_listener;
+mod chunking;
+mod composite_listener;
+mod compression;
+#[cfg(feature = "dynamic")]
+mod dynamic_payload;
+mod in_memory_rpc_client;
+mod in_memory_rpc_server;
 mod notification;
 mod pubsub;
+mod retrying_rpc_client;
 mod rpc;
+mod serialization;
+mod typed_listener;
 
 /// An error indicating a problem with registering or unregistering a message listener.
 #[derive(Debug)]
 pub enum RegistrationError {
+    /// Indicates that the given filter criteria are invalid.
+    InvalidFilter(String),
     /// Indicates that the maximum number of listeners supported by the Transport Layer implementation
     /// has already been registered.
     MaxListenersExceeded,
@@ -38,11 +71,41 @@ pub enum RegistrationError {
     /// Indicates that the underlying Transport Layer implementation does not support registration and
     /// notification of message handlers.
     PushDeliveryMethodNotSupported,
+    /// Indicates an unspecific error that occurred at the Transport Layer while trying to register a listener.
+    Unknown(UStatus),
+    /// Indicates that the operation could not be performed without waiting for another operation to complete.
+    WouldBlock,
+    /// Indicates that the Transport Layer implementation has failed to register a listener.
+    ///
+    /// The status is the one reported by the transport, so that callers can decide based on its code
+    /// whether to retry the registration.
+    TransportError(UStatus),
+    /// Indicates that an endpoint has already been registered for the same origin filter and resource ID.
+    DuplicateEndpoint,
+    /// Indicates that the maximum number of endpoints supported by the service provider has already
+    /// been registered.
+    CapacityExceeded,
+    /// Indicates that an argument other than a filter is invalid.
+    InvalidArgument(String),
+}
+
+impl From<UStatus> for RegistrationError {
+    fn from(value: UStatus) -> Self {
+        match value.code.enum_value() {
+            Ok(UCode::NOT_FOUND) => RegistrationError::NoSuchListener,
+            Ok(UCode::RESOURCE_EXHAUSTED) => RegistrationError::MaxListenersExceeded,
+            Ok(UCode::UNIMPLEMENTED) => RegistrationError::PushDeliveryMethodNotSupported,
+            _ => RegistrationError::Unknown(value),
+        }
+    }
 }
 
 impl Display for RegistrationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            RegistrationError::InvalidFilter(msg) => {
+                f.write_fmt(format_args!("invalid filter(s): {}", msg))
+            }
             RegistrationError::MaxListenersExceeded => {
                 f.write_str("maximum number of listeners has been reached")
             }
@@ -52,6 +115,26 @@ impl Display for RegistrationError {
             RegistrationError::PushDeliveryMethodNotSupported => f.write_str(
                 "the underlying transport implementation does not support the push delivery method",
             ),
+            RegistrationError::Unknown(status) => f.write_fmt(format_args!(
+                "error registering listener: {}",
+                status.get_message()
+            )),
+            RegistrationError::WouldBlock => {
+                f.write_str("operation would block, another registration is in progress")
+            }
+            RegistrationError::TransportError(status) => f.write_fmt(format_args!(
+                "transport failed to register listener: {}",
+                status.get_message()
+            )),
+            RegistrationError::DuplicateEndpoint => {
+                f.write_str("endpoint has already been registered")
+            }
+            RegistrationError::CapacityExceeded => {
+                f.write_str("maximum number of endpoints has been reached")
+            }
+            RegistrationError::InvalidArgument(msg) => {
+                f.write_fmt(format_args!("invalid argument: {}", msg))
+            }
         }
     }
 }
@@ -61,13 +144,16 @@ impl Error for RegistrationError {}
 const DEFAULT_TTL: u32 = 10_000; // 10 seconds
 
 /// General options that clients might want to specify when sending a uProtocol message.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CallOptions {
     ttl: u32,
     message_id: Option<UUID>,
     token: Option<String>,
     priority: Option<UPriority>,
     traceparent: Option<String>,
+    cancellation_token: Option<CancellationToken>,
+    idempotent: bool,
+    max_wait: Option<Duration>,
 }
 
 impl Default for CallOptions {
@@ -79,6 +165,9 @@ impl Default for CallOptions {
             token: None,
             priority: None,
             traceparent: None,
+            cancellation_token: None,
+            idempotent: false,
+            max_wait: None,
         }
     }
 }
@@ -108,7 +197,58 @@ impl CallOptions {
             token,
             priority,
             traceparent: None,
+            cancellation_token: None,
+            idempotent: false,
+            max_wait: None,
+        }
+    }
+
+    /// Creates options for invoking a method while processing an (inbound) RPC request.
+    ///
+    /// The TTL of the outgoing request is set to the time that is left until the inbound request
+    /// expires, as determined from the creation time contained in its ID and its TTL. Handlers that
+    /// use these options for invoking other services thereby propagate a deadline that shrinks
+    /// with every hop of a call chain. The inbound request's
+    /// [traceparent](Self::with_traceparent) is propagated as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `attributes` - The attributes of the request that is being processed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ServiceInvocationError::DeadlineExceeded`] if the inbound request has expired already,
+    /// i.e. if there is no time left for invoking another service.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UAttributes, UUID, UUri};
+    /// use up_rust::communication::CallOptions;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let attributes = UAttributes::request(
+    ///     UUID::build(),
+    ///     UUri::try_from("//my-vehicle/4210/1/1A50")?,
+    ///     UUri::try_from("//my-cloud/BA4C/1/0")?,
+    ///     None,
+    ///     None,
+    ///     Some(5_000),
+    /// );
+    /// let call_options = CallOptions::from_inbound_request(&attributes)?;
+    /// assert!(call_options.ttl() <= 5_000);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_inbound_request(attributes: &UAttributes) -> Result<Self, ServiceInvocationError> {
+        let remaining = rpc::remaining_time(attributes);
+        let ttl = u32::try_from(remaining.as_millis()).unwrap_or(u32::MAX);
+        if ttl == 0 {
+            return Err(ServiceInvocationError::DeadlineExceeded);
         }
+        let mut call_options = CallOptions::new(ttl, None, None, None);
+        call_options.traceparent = attributes.traceparent.clone();
+        Ok(call_options)
     }
 
     /// Sets the message's time-to-live.
@@ -196,10 +336,95 @@ impl CallOptions {
     pub fn traceparent(&self) -> Option<String> {
         self.traceparent.clone()
     }
+
+    /// Sets a token for cancelling the invocation of an RPC method.
+    ///
+    /// Once the token has been cancelled, the client stops waiting for the response message
+    /// and returns [`ServiceInvocationError::Cancelled`].
+    ///
+    /// # Returns
+    ///
+    /// `CallOption` with specified cancellation token.
+    pub fn with_cancellation_token(&mut self, token: CancellationToken) -> &mut Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Gets the token for cancelling the invocation of an RPC method.
+    pub fn cancellation_token(&self) -> Option<CancellationToken> {
+        self.cancellation_token.clone()
+    }
+
+    /// Marks the invoked method as being idempotent.
+    ///
+    /// Invoking an idempotent method multiple times with the same arguments has the same effect
+    /// as invoking it once. Only invocations of idempotent methods are retried by a
+    /// [`RetryingRpcClient`]. By default, methods are not considered idempotent.
+    ///
+    /// Each retry is sent with a new message ID. The [duplicate request detection](InMemoryRpcServer#duplicate-requests)
+    /// of service providers therefore does not prevent a retried invocation from being processed again,
+    /// which is why methods that are not idempotent must not be retried.
+    ///
+    /// # Returns
+    ///
+    /// `CallOption` with specified idempotency.
+    pub fn with_idempotent(&mut self, idempotent: bool) -> &mut Self {
+        self.idempotent = idempotent;
+        self
+    }
+
+    /// Checks if the invoked method is idempotent.
+    pub fn is_idempotent(&self) -> bool {
+        self.idempotent
+    }
+
+    /// Sets the maximum amount of time to wait for a response message.
+    ///
+    /// Clients stop waiting for the response once the request's TTL has expired. This option can be
+    /// used for waiting a shorter amount of time, regardless of the TTL, e.g. in order to not keep
+    /// resources allocated for a long time if responses are likely to get lost. By default, clients
+    /// wait for the full TTL.
+    ///
+    /// # Returns
+    ///
+    /// `CallOption` with specified maximum waiting time.
+    pub fn with_max_wait(&mut self, max_wait: Duration) -> &mut Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    /// Gets the maximum amount of time to wait for a response message.
+    pub fn max_wait(&self) -> Option<Duration> {
+        self.max_wait
+    }
+
+    /// Gets the amount of time to wait for a response message.
+    ///
+    /// # Returns
+    ///
+    /// The minimum of the TTL and the [maximum waiting time](Self::with_max_wait).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use up_rust::communication::CallOptions;
+    ///
+    /// let mut call_options = CallOptions::new(60_000, None, None, None);
+    /// assert_eq!(call_options.response_timeout(), Duration::from_secs(60));
+    /// call_options.with_max_wait(Duration::from_secs(5));
+    /// assert_eq!(call_options.response_timeout(), Duration::from_secs(5));
+    /// ```
+    pub fn response_timeout(&self) -> Duration {
+        let ttl = Duration::from_millis(u64::from(self.ttl));
+        self.max_wait.map_or(ttl, |max_wait| max_wait.min(ttl))
+    }
 }
 
 /// A wrapper around (raw) message payload data and the corresponding payload format.
-#[derive(Clone)]
+///
+/// Two payloads are equal if they have the same format and contain the same bytes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct UPayload {
     payload_format: UPayloadFormat,
     payload: Bytes,
@@ -233,6 +458,26 @@ impl UPayload {
         }
     }
 
+    /// Creates a new UPayload from a protobuf message that has been wrapped in an `Any`.
+    ///
+    /// The resulting payload will have `UPayloadType::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY`.
+    /// This allows consumers to [inspect the type](Self::extract_any) of the wrapped message before
+    /// unpacking it, which is useful for methods that may return different types of messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the given message cannot be serialized to bytes.
+    pub fn try_from_any(any: &Any) -> Result<Self, UMessageError> {
+        any.write_to_bytes()
+            .map(|bytes| {
+                UPayload::new(
+                    bytes.into(),
+                    UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY,
+                )
+            })
+            .map_err(UMessageError::DataSerializationError)
+    }
+
     /// Gets the payload format.
     ///
     /// # Returns
@@ -249,6 +494,27 @@ impl UPayload {
         self.payload
     }
 
+    /// Gets a reference to the payload data.
+    ///
+    /// The data is not copied. The returned slice is valid for as long as this payload is.
+    /// Transports can use this to write the payload data directly, without cloning it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use bytes::Bytes;
+    /// use up_rust::UPayloadFormat;
+    /// use up_rust::communication::UPayload;
+    ///
+    /// let data = Bytes::from_static(b"hello");
+    /// let payload = UPayload::new(data.clone(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+    /// assert_eq!(payload.as_bytes(), b"hello");
+    /// assert_eq!(payload.as_bytes().as_ptr(), data.as_ptr());
+    /// ```
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+
     /// Extracts the protobuf `Message` contained in payload.
     ///
     /// This function is used to extract strongly-typed data from a `UPayload` object,
@@ -270,4 +536,368 @@ impl UPayload {
     pub fn extract_protobuf<T: Message + Default>(&self) -> Result<T, UMessageError> {
         umessage::deserialize_protobuf_bytes(&self.payload, &self.payload_format)
     }
+
+    /// Creates a new UPayload from a value, using a given encoding.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `S`: The encoding to use, e.g. [`ProtobufSerializer`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UCode, UPayloadFormat, UStatus};
+    /// use up_rust::communication::{ProtobufSerializer, UPayload};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let status = UStatus::fail_with_code(UCode::NOT_FOUND, "no such entity");
+    /// let payload = UPayload::serialize::<ProtobufSerializer, _>(&status)?;
+    /// assert_eq!(payload.payload_format(), UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF);
+    /// assert_eq!(payload.deserialize::<ProtobufSerializer, UStatus>()?, status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn serialize<S, T>(value: &T) -> Result<Self, UMessageError>
+    where
+        S: PayloadSerializer<T>,
+        T: ?Sized,
+    {
+        S::serialize(value)
+    }
+
+    /// Extracts a value from this payload, using a given encoding.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `D`: The encoding to use, e.g. [`ProtobufSerializer`].
+    /// * `T`: The type of value to extract.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this payload's format is not supported by the encoding or if the
+    /// payload data cannot be decoded.
+    pub fn deserialize<D, T>(&self) -> Result<T, UMessageError>
+    where
+        D: PayloadDeserializer<T>,
+    {
+        D::deserialize(self)
+    }
+
+    /// Extracts the `Any` that wraps the protobuf message contained in this payload.
+    ///
+    /// In contrast to [`Self::extract_protobuf`], the type of the wrapped message does not need to be known
+    /// in advance. The caller can inspect the `Any`'s `type_url` in order to decide how to unpack the message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this payload's format is not `UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY`
+    /// or if the payload data cannot be deserialized into an `Any`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use protobuf::well_known_types::{any::Any, wrappers::StringValue};
+    /// use up_rust::communication::UPayload;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let value = StringValue { value: "hello".to_string(), ..Default::default() };
+    /// let payload = UPayload::try_from_any(&Any::pack(&value)?)?;
+    ///
+    /// let any = payload.extract_any()?;
+    /// assert!(any.is::<StringValue>());
+    /// assert_eq!(any.unpack::<StringValue>()?, Some(value));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn extract_any(&self) -> Result<Any, UMessageError> {
+        if self.payload_format != UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY {
+            return Err(UMessageError::from(
+                "Payload does not contain a protobuf message wrapped in Any",
+            ));
+        }
+        Any::parse_from_tokio_bytes(&self.payload).map_err(UMessageError::DataSerializationError)
+    }
+
+    /// Converts this payload to another payload format.
+    ///
+    /// The payload data is parsed according to this payload's format and is then encoded in the
+    /// target format. Conversion is supported between any of the following formats:
+    ///
+    /// * [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF`]
+    /// * [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY`]
+    /// * [`UPayloadFormat::UPAYLOAD_FORMAT_JSON`], using the canonical
+    ///   [proto3 JSON mapping](https://protobuf.dev/programming-guides/proto3/#json) as implemented by
+    ///   [protobuf-json-mapping](https://docs.rs/protobuf-json-mapping)
+    ///
+    /// Neither the binary nor the JSON encoding of a protobuf message contain enough information for
+    /// interpreting the data without knowing the message's type. The caller therefore needs to provide
+    /// the descriptor of the message type that the payload contains.
+    ///
+    /// Well-known types like `google.protobuf.Timestamp` are encoded using their special JSON representation.
+    /// Note that messages containing a `google.protobuf.Any` cannot be converted to JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The format to convert the payload to.
+    /// * `descriptor` - The descriptor of the protobuf message type contained in the payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either this payload's format or the target format is not supported, or
+    /// if the payload data cannot be parsed as a message of the given type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use protobuf::MessageFull;
+    /// use up_rust::{UCode, UPayloadFormat, UStatus};
+    /// use up_rust::communication::UPayload;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let payload = UPayload::try_from_protobuf(UStatus::fail_with_code(UCode::NOT_FOUND, "no such entity"))?;
+    /// let json = payload.transcode_to(UPayloadFormat::UPAYLOAD_FORMAT_JSON, &UStatus::descriptor())?;
+    /// assert_eq!(json.payload_format(), UPayloadFormat::UPAYLOAD_FORMAT_JSON);
+    /// assert_eq!(
+    ///     serde_json::from_slice::<serde_json::Value>(json.as_bytes())?,
+    ///     serde_json::json!({"code": "NOT_FOUND", "message": "no such entity"})
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn transcode_to(
+        &self,
+        target: UPayloadFormat,
+        descriptor: &protobuf::reflect::MessageDescriptor,
+    ) -> Result<UPayload, UMessageError> {
+        let message = match self.payload_format {
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF => {
+                descriptor.parse_from_bytes(&self.payload)?
+            }
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY => {
+                let any = Any::parse_from_tokio_bytes(&self.payload)?;
+                descriptor.parse_from_bytes(&any.value)?
+            }
+            UPayloadFormat::UPAYLOAD_FORMAT_JSON => {
+                let json = std::str::from_utf8(&self.payload)
+                    .map_err(|e| UMessageError::PayloadError(e.to_string()))?;
+                protobuf_json_mapping::parse_dyn_from_str(descriptor, json)
+                    .map_err(|e| UMessageError::PayloadError(e.to_string()))?
+            }
+            _ => return Err(UMessageError::from("Unsupported source payload format")),
+        };
+
+        let data = match target {
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF => message.write_to_bytes_dyn()?,
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY => Any {
+                type_url: format!("type.googleapis.com/{}", descriptor.full_name()),
+                value: message.write_to_bytes_dyn()?,
+                ..Default::default()
+            }
+            .write_to_bytes()?,
+            UPayloadFormat::UPAYLOAD_FORMAT_JSON => {
+                protobuf_json_mapping::print_to_string(&*message)
+                    .map_err(|e| UMessageError::PayloadError(e.to_string()))?
+                    .into_bytes()
+            }
+            _ => return Err(UMessageError::from("Unsupported target payload format")),
+        };
+        Ok(UPayload::new(data.into(), target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::time::SystemTime;
+
+    use protobuf::well_known_types::wrappers::StringValue;
+
+    use crate::UUri;
+
+    use super::*;
+
+    fn request_attributes(age: Duration, ttl: u32) -> UAttributes {
+        let created_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            - age;
+        let mut attributes = UAttributes::request(
+            UUID::build_for_timestamp(created_at),
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            None,
+            Some("my-token".to_string()),
+            Some(ttl),
+        );
+        attributes.traceparent = Some("my-traceparent".to_string());
+        attributes
+    }
+
+    #[test]
+    fn test_call_options_from_inbound_request_use_remaining_ttl() {
+        let attributes = request_attributes(Duration::from_millis(2_000), 5_000);
+        let call_options = CallOptions::from_inbound_request(&attributes).unwrap();
+        // allow for some time to pass while running the test
+        assert!((2_900..=3_000).contains(&call_options.ttl()));
+        assert_eq!(
+            call_options.traceparent(),
+            Some("my-traceparent".to_string())
+        );
+        assert!(call_options.token().is_none());
+        assert!(call_options.message_id().is_none());
+    }
+
+    #[test]
+    fn test_call_options_from_expired_inbound_request_fail() {
+        let attributes = request_attributes(Duration::from_millis(6_000), 5_000);
+        assert!(matches!(
+            CallOptions::from_inbound_request(&attributes),
+            Err(ServiceInvocationError::DeadlineExceeded)
+        ));
+    }
+
+    #[test]
+    fn test_payloads_with_same_data_and_format_are_equal() {
+        let payload = UPayload::new("hello".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+        let same = UPayload::new(
+            Bytes::copy_from_slice(b"hello"),
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+        );
+        assert_eq!(payload, same);
+        let distinct: HashSet<UPayload> = [payload, same].into_iter().collect();
+        assert_eq!(distinct.len(), 1);
+    }
+
+    #[test]
+    fn test_payloads_with_different_data_or_format_are_not_equal() {
+        let payload = UPayload::new("hello".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+        assert_ne!(
+            payload,
+            UPayload::new("hello".into(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+        );
+        assert_ne!(
+            payload,
+            UPayload::new("world".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        );
+    }
+
+    #[test]
+    fn test_byte_access_does_not_copy_data() {
+        let data = Bytes::from(vec![0x01, 0x02, 0x03]);
+        let payload = UPayload::new(data.clone(), UPayloadFormat::UPAYLOAD_FORMAT_RAW);
+
+        assert_eq!(payload.as_bytes().as_ptr(), data.as_ptr());
+        let bytes = payload.payload();
+        assert_eq!(bytes.as_ptr(), data.as_ptr());
+        assert_eq!(bytes, data);
+    }
+
+    #[test]
+    fn test_extract_any_provides_type_url() {
+        let value = StringValue {
+            value: "hello".to_string(),
+            ..Default::default()
+        };
+        let payload = UPayload::try_from_any(&Any::pack(&value).unwrap()).unwrap();
+        assert_eq!(
+            payload.payload_format(),
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY
+        );
+
+        let any = payload.extract_any().unwrap();
+        assert_eq!(
+            any.type_url,
+            "type.googleapis.com/google.protobuf.StringValue"
+        );
+        assert_eq!(payload.extract_protobuf::<StringValue>().unwrap(), value);
+    }
+
+    #[test]
+    fn test_extract_any_fails_for_plain_protobuf_payload() {
+        let payload = UPayload::try_from_protobuf(StringValue::default()).unwrap();
+        assert!(payload.extract_any().is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_transcoding_payload_preserves_message() {
+        use protobuf::MessageFull;
+
+        let uri = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+        let payload = UPayload::try_from_protobuf(uri.clone()).unwrap();
+
+        let json = payload
+            .transcode_to(UPayloadFormat::UPAYLOAD_FORMAT_JSON, &UUri::descriptor())
+            .unwrap();
+        let wrapped = json
+            .transcode_to(
+                UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY,
+                &UUri::descriptor(),
+            )
+            .unwrap();
+        assert_eq!(wrapped.extract_protobuf::<UUri>().unwrap(), uri);
+        let plain = wrapped
+            .transcode_to(
+                UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF,
+                &UUri::descriptor(),
+            )
+            .unwrap();
+        assert_eq!(plain.extract_protobuf::<UUri>().unwrap(), uri);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_transcoding_payload_fails_for_unsupported_format() {
+        use protobuf::MessageFull;
+
+        let payload = UPayload::new("hello".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+        assert!(payload
+            .transcode_to(UPayloadFormat::UPAYLOAD_FORMAT_JSON, &UUri::descriptor())
+            .is_err());
+        let payload = UPayload::try_from_protobuf(UUri::default()).unwrap();
+        assert!(payload
+            .transcode_to(UPayloadFormat::UPAYLOAD_FORMAT_RAW, &UUri::descriptor())
+            .is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_transcoding_payload_uses_canonical_encoding_of_well_known_types() {
+        use protobuf::well_known_types::timestamp::Timestamp;
+        use protobuf::MessageFull;
+
+        let timestamp = Timestamp {
+            seconds: 1_700_000_000,
+            nanos: 500_000_000,
+            ..Default::default()
+        };
+        let payload = UPayload::try_from_protobuf(timestamp.clone()).unwrap();
+
+        let json = payload
+            .transcode_to(
+                UPayloadFormat::UPAYLOAD_FORMAT_JSON,
+                &Timestamp::descriptor(),
+            )
+            .unwrap();
+        // timestamps are encoded as RFC 3339 strings instead of objects
+        let serde_json::Value::String(text) =
+            serde_json::from_slice::<serde_json::Value>(json.as_bytes()).unwrap()
+        else {
+            panic!("timestamp is not encoded as JSON string");
+        };
+        assert!(text.starts_with("2023-11-14T22:13:20.5"));
+        assert!(text.ends_with('Z'));
+        let decoded = json
+            .transcode_to(
+                UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF,
+                &Timestamp::descriptor(),
+            )
+            .unwrap();
+        assert_eq!(decoded.extract_protobuf::<Timestamp>().unwrap(), timestamp);
+    }
 }
@@ -0,0 +1,416 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt};
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+use crate::{UListener, UMessage, UStatus, UTransport, UUri};
+
+/// The strategy for handling a message that arrives while a [`ChannelListener`]'s buffer is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discard the oldest buffered message in order to make room for the new message.
+    DropOldest,
+    /// Discard the new message.
+    DropNewest,
+}
+
+struct Channel {
+    buffer: Mutex<VecDeque<UMessage>>,
+    capacity: usize,
+    message_available: Notify,
+    closed: AtomicBool,
+}
+
+/// A [`UListener`] that buffers all received messages, so that they can be pulled from a [`MessageReceiver`].
+///
+/// This bridges the push based [`UTransport`](crate::UTransport) API to consumers that prefer to
+/// process messages in a loop.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use up_rust::{UListener, UMessageBuilder, UUri};
+/// use up_rust::communication::{ChannelListener, OverflowPolicy};
+///
+/// # async_std::task::block_on(async {
+/// let (listener, mut receiver) = ChannelListener::new(10, OverflowPolicy::DropOldest);
+/// let listener = Arc::new(listener);
+/// // register the listener with a transport ...
+/// # let topic = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+/// # listener.on_receive(UMessageBuilder::publish(topic).build().unwrap()).await;
+///
+/// // ... and process the messages received by the listener
+/// let msg = receiver.recv().await;
+/// assert!(msg.is_some());
+/// # });
+/// ```
+pub struct ChannelListener {
+    channel: Arc<Channel>,
+    overflow_policy: OverflowPolicy,
+}
+
+impl ChannelListener {
+    /// Creates a new listener along with the receiver for the messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of messages to buffer.
+    /// * `overflow_policy` - The strategy to apply when a message arrives while the buffer is full.
+    ///
+    /// # Panics
+    ///
+    /// if the capacity is 0.
+    pub fn new(capacity: usize, overflow_policy: OverflowPolicy) -> (Self, MessageReceiver) {
+        assert!(capacity > 0, "capacity must be greater than 0");
+        let channel = Arc::new(Channel {
+            buffer: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            message_available: Notify::new(),
+            closed: AtomicBool::new(false),
+        });
+        (
+            ChannelListener {
+                channel: channel.clone(),
+                overflow_policy,
+            },
+            MessageReceiver { channel },
+        )
+    }
+}
+
+impl Drop for ChannelListener {
+    fn drop(&mut self) {
+        self.channel.closed.store(true, Ordering::Release);
+        self.channel.message_available.notify_one();
+    }
+}
+
+#[async_trait]
+impl UListener for ChannelListener {
+    async fn on_receive(&self, msg: UMessage) {
+        {
+            let mut buffer = self.channel.buffer.lock().unwrap();
+            if buffer.len() >= self.channel.capacity {
+                match self.overflow_policy {
+                    OverflowPolicy::DropOldest => {
+                        debug!("buffer is full, discarding oldest message");
+                        buffer.pop_front();
+                    }
+                    OverflowPolicy::DropNewest => {
+                        debug!("buffer is full, discarding received message");
+                        return;
+                    }
+                }
+            }
+            buffer.push_back(msg);
+        }
+        self.channel.message_available.notify_one();
+    }
+}
+
+/// The receiving end of a [`ChannelListener`].
+pub struct MessageReceiver {
+    channel: Arc<Channel>,
+}
+
+impl MessageReceiver {
+    /// Gets the next message, waiting for one to arrive if the buffer is empty.
+    ///
+    /// # Returns
+    ///
+    /// The oldest buffered message or `None`, if the buffer is empty and the
+    /// listener has been dropped.
+    pub async fn recv(&mut self) -> Option<UMessage> {
+        loop {
+            if let Some(msg) = self.try_recv() {
+                return Some(msg);
+            }
+            if self.channel.closed.load(Ordering::Acquire) {
+                // a message might have been added before the listener has been dropped
+                return self.try_recv();
+            }
+            self.channel.message_available.notified().await;
+        }
+    }
+
+    /// Gets the next message, if available.
+    ///
+    /// # Returns
+    ///
+    /// The oldest buffered message or `None`, if the buffer is empty.
+    pub fn try_recv(&mut self) -> Option<UMessage> {
+        self.channel.buffer.lock().unwrap().pop_front()
+    }
+}
+
+/// A [`Stream`] of the messages that a transport dispatches to a listener.
+///
+/// This allows consuming messages using the combinators provided by [`StreamExt`], e.g. in
+/// reactive applications. The stream is backed by a [`ChannelListener`] which gets registered with
+/// the transport when the stream is created. The listener is unregistered again when the stream is
+/// dropped, so that the transport does not keep on dispatching messages to it.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use futures::StreamExt;
+/// use up_rust::{InMemoryTransport, UMessageBuilder, UTransport, UUri};
+/// use up_rust::communication::{MessageStream, OverflowPolicy};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let transport = Arc::new(InMemoryTransport::new());
+/// let topic = UUri::try_from("//my-vehicle/4210/1/B24D")?;
+/// let mut messages = MessageStream::register(
+///     transport.clone(),
+///     &topic,
+///     None,
+///     10,
+///     OverflowPolicy::DropOldest,
+/// )
+/// .await?;
+///
+/// transport.send(UMessageBuilder::publish(topic).build()?).await?;
+/// assert!(messages.next().await.is_some());
+/// # Ok(())
+/// # }
+/// ```
+pub struct MessageStream {
+    messages: BoxStream<'static, UMessage>,
+    transport: Arc<dyn UTransport>,
+    source_filter: UUri,
+    sink_filter: Option<UUri>,
+    listener: Arc<dyn UListener>,
+}
+
+impl MessageStream {
+    /// Registers a listener with a transport and creates a stream of the messages dispatched to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport to register the listener with.
+    /// * `source_filter` - The source address pattern that messages need to match.
+    /// * `sink_filter` - The sink address pattern that messages need to match.
+    /// * `capacity` - The maximum number of messages to buffer until they are consumed from the stream.
+    /// * `overflow_policy` - The strategy to apply when a message arrives while the buffer is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot be registered with the transport.
+    ///
+    /// # Panics
+    ///
+    /// if the capacity is 0.
+    pub async fn register(
+        transport: Arc<dyn UTransport>,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        capacity: usize,
+        overflow_policy: OverflowPolicy,
+    ) -> Result<Self, UStatus> {
+        let (listener, receiver) = ChannelListener::new(capacity, overflow_policy);
+        let listener: Arc<dyn UListener> = Arc::new(listener);
+        transport
+            .register_listener(source_filter, sink_filter, listener.clone())
+            .await?;
+        let messages = stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|msg| (msg, receiver))
+        })
+        .boxed();
+        Ok(MessageStream {
+            messages,
+            transport,
+            source_filter: source_filter.to_owned(),
+            sink_filter: sink_filter.cloned(),
+            listener,
+        })
+    }
+}
+
+impl Stream for MessageStream {
+    type Item = UMessage;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.messages.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for MessageStream {
+    fn drop(&mut self) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("cannot unregister listener of dropped message stream, no runtime available");
+            return;
+        };
+        let transport = self.transport.clone();
+        let source_filter = self.source_filter.clone();
+        let sink_filter = self.sink_filter.take();
+        let listener = self.listener.clone();
+        // unregistering is asynchronous, so it cannot be done while dropping the stream
+        runtime.spawn(async move {
+            if let Err(e) = transport
+                .unregister_listener(&source_filter, sink_filter.as_ref(), listener)
+                .await
+            {
+                debug!(
+                    "failed to unregister listener of dropped message stream: {}",
+                    e
+                );
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{UMessageBuilder, UPayloadFormat, UTransport, UUri};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct LoopbackTransport {
+        listeners: Mutex<Vec<Arc<dyn UListener>>>,
+    }
+
+    #[async_trait]
+    impl UTransport for LoopbackTransport {
+        async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+            let listeners = self.listeners.lock().unwrap().clone();
+            for listener in listeners {
+                listener.on_receive(message.clone()).await;
+            }
+            Ok(())
+        }
+
+        async fn register_listener(
+            &self,
+            _source_filter: &UUri,
+            _sink_filter: Option<&UUri>,
+            listener: Arc<dyn UListener>,
+        ) -> Result<(), UStatus> {
+            self.listeners.lock().unwrap().push(listener);
+            Ok(())
+        }
+
+        async fn unregister_listener(
+            &self,
+            _source_filter: &UUri,
+            _sink_filter: Option<&UUri>,
+            _listener: Arc<dyn UListener>,
+        ) -> Result<(), UStatus> {
+            self.listeners.lock().unwrap().clear();
+            Ok(())
+        }
+    }
+
+    fn message(text: &'static str) -> UMessage {
+        UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D").unwrap())
+            .build_with_payload(text, UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_receiver_yields_messages_received_via_transport() {
+        let transport = LoopbackTransport::default();
+        let (listener, mut receiver) = ChannelListener::new(5, OverflowPolicy::DropNewest);
+        let listener: Arc<dyn UListener> = Arc::new(listener);
+        transport
+            .register_listener(&UUri::any(), None, listener.clone())
+            .await
+            .unwrap();
+
+        transport.send(message("one")).await.unwrap();
+        transport.send(message("two")).await.unwrap();
+
+        assert_eq!(receiver.recv().await.unwrap().payload, Some("one".into()));
+        assert_eq!(receiver.recv().await.unwrap().payload, Some("two".into()));
+        assert!(receiver.try_recv().is_none());
+
+        transport
+            .unregister_listener(&UUri::any(), None, listener.clone())
+            .await
+            .unwrap();
+        drop(listener);
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_message_stream_yields_messages_and_unregisters_on_drop() {
+        let transport = Arc::new(LoopbackTransport::default());
+        let mut messages = MessageStream::register(
+            transport.clone(),
+            &UUri::any(),
+            None,
+            5,
+            OverflowPolicy::DropNewest,
+        )
+        .await
+        .unwrap();
+
+        transport.send(message("one")).await.unwrap();
+        transport.send(message("two")).await.unwrap();
+
+        assert_eq!(messages.next().await.unwrap().payload, Some("one".into()));
+        assert_eq!(messages.next().await.unwrap().payload, Some("two".into()));
+
+        drop(messages);
+        // give the spawned task a chance to unregister the listener
+        tokio::task::yield_now().await;
+        assert!(transport.listeners.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recv_waits_for_message() {
+        let (listener, mut receiver) = ChannelListener::new(5, OverflowPolicy::DropNewest);
+        let (msg, _) = tokio::join!(receiver.recv(), async {
+            tokio::task::yield_now().await;
+            listener.on_receive(message("one")).await;
+        });
+        assert_eq!(msg.unwrap().payload, Some("one".into()));
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_policy() {
+        let (listener, mut receiver) = ChannelListener::new(2, OverflowPolicy::DropOldest);
+        listener.on_receive(message("one")).await;
+        listener.on_receive(message("two")).await;
+        listener.on_receive(message("three")).await;
+
+        assert_eq!(receiver.try_recv().unwrap().payload, Some("two".into()));
+        assert_eq!(receiver.try_recv().unwrap().payload, Some("three".into()));
+        assert!(receiver.try_recv().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest_policy() {
+        let (listener, mut receiver) = ChannelListener::new(2, OverflowPolicy::DropNewest);
+        listener.on_receive(message("one")).await;
+        listener.on_receive(message("two")).await;
+        listener.on_receive(message("three")).await;
+
+        assert_eq!(receiver.try_recv().unwrap().payload, Some("one".into()));
+        assert_eq!(receiver.try_recv().unwrap().payload, Some("two".into()));
+        assert!(receiver.try_recv().is_none());
+    }
+}
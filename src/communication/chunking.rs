@@ -0,0 +1,408 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use protobuf::Enum;
+use tokio::time::Instant;
+
+use crate::{UMessageError, UPayloadFormat, UUID};
+
+use super::UPayload;
+
+// The bytes that chunk data starts with.
+const CHUNK_MAGIC: &[u8] = b"uPCH";
+// magic + group ID + chunk index + chunk count + original payload format
+const CHUNK_HEADER_LEN: usize = CHUNK_MAGIC.len() + 16 + 4 + 4 + 1;
+
+struct ChunkHeader {
+    group_id: UUID,
+    index: u32,
+    count: u32,
+    payload_format: UPayloadFormat,
+}
+
+impl ChunkHeader {
+    fn write_to(&self, data: &mut BytesMut) {
+        data.extend_from_slice(CHUNK_MAGIC);
+        data.extend_from_slice(&self.group_id.msb.to_be_bytes());
+        data.extend_from_slice(&self.group_id.lsb.to_be_bytes());
+        data.extend_from_slice(&self.index.to_be_bytes());
+        data.extend_from_slice(&self.count.to_be_bytes());
+        data.extend_from_slice(&[self.payload_format.value() as u8]);
+    }
+
+    fn read_from(chunk: &UPayload) -> Result<Self, UMessageError> {
+        if !chunk.is_chunk() {
+            return Err(UMessageError::from("Payload is not a chunk"));
+        }
+        let header = &chunk.payload[CHUNK_MAGIC.len()..CHUNK_HEADER_LEN];
+        let (group_id, header) = header.split_at(16);
+        let (index, header) = header.split_at(4);
+        let (count, payload_format) = header.split_at(4);
+        let group_id = UUID::from_bytes_unchecked(
+            group_id[..8].try_into().unwrap_or_default(),
+            group_id[8..].try_into().unwrap_or_default(),
+        );
+        let index = u32::from_be_bytes(index.try_into().unwrap_or_default());
+        let count = u32::from_be_bytes(count.try_into().unwrap_or_default());
+        let payload_format = UPayloadFormat::from_i32(i32::from(payload_format[0]))
+            .ok_or_else(|| UMessageError::from("Unknown payload format"))?;
+        if index >= count {
+            return Err(UMessageError::PayloadError(format!(
+                "Chunk index {} is out of range for chunk count {}",
+                index, count
+            )));
+        }
+        Ok(ChunkHeader {
+            group_id,
+            index,
+            count,
+            payload_format,
+        })
+    }
+}
+
+impl UPayload {
+    /// Checks if this payload is a chunk created by a [`ChunkedPayloadSplitter`].
+    ///
+    /// The check is based on the payload format and the magic bytes `uPCH` at the start of the data only.
+    /// Consequently, (regular) payloads of format [`UPayloadFormat::UPAYLOAD_FORMAT_RAW`] whose data
+    /// happens to start with these bytes are treated as chunks as well.
+    pub fn is_chunk(&self) -> bool {
+        self.payload_format == UPayloadFormat::UPAYLOAD_FORMAT_RAW
+            && self.payload.len() >= CHUNK_HEADER_LEN
+            && self.payload.starts_with(CHUNK_MAGIC)
+    }
+}
+
+/// Splits payloads that exceed a transport's maximum transmission unit into multiple chunks.
+///
+/// Each chunk has format [`UPayloadFormat::UPAYLOAD_FORMAT_RAW`]. Its data consists of a small header
+/// followed by a slice of the original payload's data. [`UAttributes`](crate::UAttributes) do not provide
+/// fields for correlating multiple messages, so the header contains
+///
+/// * the ID of the group of chunks that the original payload has been split into,
+/// * the chunk's (zero based) index within the group,
+/// * the number of chunks in the group and
+/// * the original payload's format.
+///
+/// The chunks can be sent in separate messages of any type and are put back together by a
+/// [`ChunkedPayloadAssembler`] on the receiving side, regardless of the order in which they arrive.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use up_rust::UPayloadFormat;
+/// use up_rust::communication::{ChunkedPayloadAssembler, ChunkedPayloadSplitter, UPayload};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let payload = UPayload::new("Hello, chunked world!".repeat(10).into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+/// let chunks = ChunkedPayloadSplitter::new(64)?.split(&payload)?;
+/// assert!(chunks.len() > 1);
+///
+/// let mut assembler = ChunkedPayloadAssembler::new(Duration::from_secs(5));
+/// let mut reassembled = None;
+/// for chunk in chunks.iter().rev() {
+///     reassembled = assembler.add_chunk(chunk)?;
+/// }
+/// assert_eq!(reassembled, Some(payload));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChunkedPayloadSplitter {
+    max_chunk_size: usize,
+}
+
+impl ChunkedPayloadSplitter {
+    /// Creates a new splitter.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_chunk_size` - The maximum number of bytes of a chunk's data, including the chunk header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the given size does not leave any room for payload data besides the header.
+    pub fn new(max_chunk_size: usize) -> Result<Self, UMessageError> {
+        if max_chunk_size <= CHUNK_HEADER_LEN {
+            return Err(UMessageError::PayloadError(format!(
+                "Maximum chunk size must exceed chunk header size [{} bytes]",
+                CHUNK_HEADER_LEN
+            )));
+        }
+        Ok(ChunkedPayloadSplitter { max_chunk_size })
+    }
+
+    /// Splits a payload into chunks.
+    ///
+    /// A payload is split into a single chunk only, if it fits into one chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload would need to be split into more than `u32::MAX` chunks.
+    pub fn split(&self, payload: &UPayload) -> Result<Vec<UPayload>, UMessageError> {
+        let data_per_chunk = self.max_chunk_size - CHUNK_HEADER_LEN;
+        let count =
+            u32::try_from(((payload.payload.len() + data_per_chunk - 1) / data_per_chunk).max(1))
+                .map_err(|_e| UMessageError::from("Payload is too large for chunk size"))?;
+        let group_id = UUID::build();
+        let chunks = (0..count)
+            .map(|index| {
+                let start = index as usize * data_per_chunk;
+                let end = (start + data_per_chunk).min(payload.payload.len());
+                let mut data = BytesMut::with_capacity(CHUNK_HEADER_LEN + end - start);
+                ChunkHeader {
+                    group_id: group_id.clone(),
+                    index,
+                    count,
+                    payload_format: payload.payload_format,
+                }
+                .write_to(&mut data);
+                data.extend_from_slice(&payload.payload[start..end]);
+                UPayload::new(data.freeze(), UPayloadFormat::UPAYLOAD_FORMAT_RAW)
+            })
+            .collect();
+        Ok(chunks)
+    }
+}
+
+// the chunks of a group that have been received so far, by index
+struct PendingGroup {
+    payload_format: UPayloadFormat,
+    count: u32,
+    // not pre-allocated, because the count is provided by the (untrusted) sender
+    chunks: HashMap<u32, Bytes>,
+    expires_at: Instant,
+}
+
+/// Restores payloads from the chunks created by a [`ChunkedPayloadSplitter`].
+///
+/// The assembler collects the chunks of each group until all of them have arrived. Groups that are
+/// not complete within a given amount of time after their first chunk has been received are discarded,
+/// so that missing chunks do not cause memory to be held indefinitely. Expired groups are discarded
+/// whenever a chunk is added or [`Self::remove_expired`] is invoked.
+pub struct ChunkedPayloadAssembler {
+    timeout: Duration,
+    groups: HashMap<UUID, PendingGroup>,
+}
+
+impl ChunkedPayloadAssembler {
+    /// Creates a new assembler.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum amount of time to wait for all chunks of a group to arrive.
+    pub fn new(timeout: Duration) -> Self {
+        ChunkedPayloadAssembler {
+            timeout,
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Adds a chunk.
+    ///
+    /// Chunks that have been received before are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The original payload, if the given chunk is the last missing chunk of its group.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the given payload is not a valid chunk or if it does not belong
+    /// to the group that its group ID refers to.
+    pub fn add_chunk(&mut self, chunk: &UPayload) -> Result<Option<UPayload>, UMessageError> {
+        let header = ChunkHeader::read_from(chunk)?;
+        self.remove_expired();
+
+        let group = self
+            .groups
+            .entry(header.group_id.clone())
+            .or_insert_with(|| PendingGroup {
+                payload_format: header.payload_format,
+                count: header.count,
+                chunks: HashMap::new(),
+                expires_at: Instant::now() + self.timeout,
+            });
+        if group.count != header.count || group.payload_format != header.payload_format {
+            return Err(UMessageError::from(
+                "Chunk does not match other chunks of its group",
+            ));
+        }
+        group
+            .chunks
+            .entry(header.index)
+            .or_insert_with(|| chunk.payload.slice(CHUNK_HEADER_LEN..));
+        if group.chunks.len() < group.count as usize {
+            return Ok(None);
+        }
+
+        let Some(group) = self.groups.remove(&header.group_id) else {
+            return Ok(None);
+        };
+        let mut data = BytesMut::new();
+        (0..group.count)
+            .filter_map(|index| group.chunks.get(&index))
+            .for_each(|chunk_data| data.extend_from_slice(chunk_data));
+        Ok(Some(UPayload::new(data.freeze(), group.payload_format)))
+    }
+
+    /// Discards all groups that have not been completed in time.
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the discarded groups.
+    pub fn remove_expired(&mut self) -> Vec<UUID> {
+        let now = Instant::now();
+        let expired: Vec<UUID> = self
+            .groups
+            .iter()
+            .filter(|(_group_id, group)| group.expires_at <= now)
+            .map(|(group_id, _group)| group_id.clone())
+            .collect();
+        expired.iter().for_each(|group_id| {
+            self.groups.remove(group_id);
+        });
+        expired
+    }
+
+    /// Gets the number of groups that are still missing chunks.
+    pub fn pending_groups(&self) -> usize {
+        self.groups.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_payload() -> UPayload {
+        UPayload::new(
+            "Hello, chunked world! ".repeat(20).into(),
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+        )
+    }
+
+    fn chunks_of(payload: &UPayload) -> Vec<UPayload> {
+        ChunkedPayloadSplitter::new(CHUNK_HEADER_LEN + 100)
+            .unwrap()
+            .split(payload)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_splitter_fails_for_too_small_chunk_size() {
+        assert!(ChunkedPayloadSplitter::new(CHUNK_HEADER_LEN).is_err());
+    }
+
+    #[test]
+    fn test_split_creates_chunks_of_max_size() {
+        let chunks = chunks_of(&text_payload());
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks.iter().all(UPayload::is_chunk));
+        assert!(chunks[..4]
+            .iter()
+            .all(|chunk| chunk.payload.len() == CHUNK_HEADER_LEN + 100));
+        assert_eq!(chunks[4].payload.len(), CHUNK_HEADER_LEN + 40);
+    }
+
+    #[test]
+    fn test_chunks_in_order_are_reassembled() {
+        let payload = text_payload();
+        let chunks = chunks_of(&payload);
+        let mut assembler = ChunkedPayloadAssembler::new(Duration::from_secs(1));
+
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert_eq!(assembler.add_chunk(chunk).unwrap(), None);
+        }
+        let reassembled = assembler.add_chunk(&chunks[chunks.len() - 1]).unwrap();
+
+        assert_eq!(reassembled, Some(payload));
+        assert_eq!(assembler.pending_groups(), 0);
+    }
+
+    #[test]
+    fn test_chunks_out_of_order_are_reassembled() {
+        let payload = text_payload();
+        let other_payload = UPayload::new(
+            "other".repeat(50).into(),
+            UPayloadFormat::UPAYLOAD_FORMAT_RAW,
+        );
+        let chunks = chunks_of(&payload);
+        let other_chunks = chunks_of(&other_payload);
+        let mut assembler = ChunkedPayloadAssembler::new(Duration::from_secs(1));
+
+        // interleave chunks of different groups and deliver a duplicate
+        assert_eq!(assembler.add_chunk(&chunks[3]).unwrap(), None);
+        assert_eq!(assembler.add_chunk(&other_chunks[2]).unwrap(), None);
+        for index in [0, 4, 0, 1] {
+            assert_eq!(assembler.add_chunk(&chunks[index]).unwrap(), None);
+        }
+        assert_eq!(assembler.add_chunk(&other_chunks[1]).unwrap(), None);
+        assert_eq!(assembler.add_chunk(&chunks[2]).unwrap(), Some(payload));
+        assert_eq!(
+            assembler.add_chunk(&other_chunks[0]).unwrap(),
+            Some(other_payload)
+        );
+    }
+
+    #[test]
+    fn test_empty_payload_is_split_into_single_chunk() {
+        let payload = UPayload::new(Bytes::new(), UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF);
+        let chunks = chunks_of(&payload);
+        assert_eq!(chunks.len(), 1);
+
+        let mut assembler = ChunkedPayloadAssembler::new(Duration::from_secs(1));
+        assert_eq!(assembler.add_chunk(&chunks[0]).unwrap(), Some(payload));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_incomplete_group_expires() {
+        let chunks = chunks_of(&text_payload());
+        let mut assembler = ChunkedPayloadAssembler::new(Duration::from_secs(1));
+
+        // the last chunk is missing
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert_eq!(assembler.add_chunk(chunk).unwrap(), None);
+        }
+        assert!(assembler.remove_expired().is_empty());
+        assert_eq!(assembler.pending_groups(), 1);
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(assembler.remove_expired().len(), 1);
+        assert_eq!(assembler.pending_groups(), 0);
+    }
+
+    #[test]
+    fn test_add_chunk_fails_for_invalid_chunks() {
+        let mut assembler = ChunkedPayloadAssembler::new(Duration::from_secs(1));
+        assert!(assembler.add_chunk(&text_payload()).is_err());
+
+        let mut data = BytesMut::new();
+        ChunkHeader {
+            group_id: UUID::build(),
+            index: 2,
+            count: 2,
+            payload_format: UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+        }
+        .write_to(&mut data);
+        let out_of_range = UPayload::new(data.freeze(), UPayloadFormat::UPAYLOAD_FORMAT_RAW);
+        assert!(out_of_range.is_chunk());
+        assert!(assembler.add_chunk(&out_of_range).is_err());
+    }
+}
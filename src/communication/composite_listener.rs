@@ -0,0 +1,178 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::future::join_all;
+
+use crate::{UListener, UMessage, UStatus};
+
+/// The strategy for passing a message to the listeners contained in a [`CompositeListener`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DispatchMode {
+    /// Invoke the listeners one after the other, in the order in which they have been added.
+    #[default]
+    Sequential,
+    /// Invoke all listeners concurrently.
+    Concurrent,
+}
+
+/// A [`UListener`] that passes each received message to multiple other listeners.
+///
+/// A [`UTransport`](crate::UTransport) or [`RpcServer`](super::RpcServer) might support only a single
+/// listener per filter. This listener can be used to still have multiple handlers process the same
+/// messages, e.g. for tapping into the messages for observability purposes alongside the business logic.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use up_rust::{UListener, UMessageBuilder, UUri};
+/// use up_rust::communication::{ChannelListener, CompositeListener, DispatchMode, OverflowPolicy};
+///
+/// # async_std::task::block_on(async {
+/// let (business_logic, mut business_receiver) = ChannelListener::new(10, OverflowPolicy::DropOldest);
+/// let (tap, mut tap_receiver) = ChannelListener::new(10, OverflowPolicy::DropOldest);
+/// let listener = CompositeListener::new(DispatchMode::Concurrent)
+///     .with_listener(Arc::new(business_logic))
+///     .with_listener(Arc::new(tap));
+/// // register the listener with a transport ...
+/// # let topic = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+/// # listener.on_receive(UMessageBuilder::publish(topic).build().unwrap()).await;
+///
+/// // ... and have both listeners receive the messages
+/// assert!(business_receiver.recv().await.is_some());
+/// assert!(tap_receiver.recv().await.is_some());
+/// # });
+/// ```
+pub struct CompositeListener {
+    listeners: Vec<Arc<dyn UListener>>,
+    dispatch_mode: DispatchMode,
+}
+
+impl CompositeListener {
+    /// Creates a new listener that does not contain any other listeners yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `dispatch_mode` - The strategy for invoking the contained listeners.
+    pub fn new(dispatch_mode: DispatchMode) -> Self {
+        CompositeListener {
+            listeners: Vec::new(),
+            dispatch_mode,
+        }
+    }
+
+    /// Adds a listener that messages should be passed to.
+    pub fn with_listener(mut self, listener: Arc<dyn UListener>) -> Self {
+        self.listeners.push(listener);
+        self
+    }
+
+    /// Gets the listeners that messages are passed to.
+    pub fn listeners(&self) -> &[Arc<dyn UListener>] {
+        &self.listeners
+    }
+
+    /// Gets the strategy used for invoking the contained listeners.
+    pub fn dispatch_mode(&self) -> DispatchMode {
+        self.dispatch_mode
+    }
+}
+
+impl From<Vec<Arc<dyn UListener>>> for CompositeListener {
+    /// Creates a listener that sequentially passes messages to the given listeners.
+    fn from(listeners: Vec<Arc<dyn UListener>>) -> Self {
+        CompositeListener {
+            listeners,
+            dispatch_mode: DispatchMode::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl UListener for CompositeListener {
+    async fn on_receive(&self, msg: UMessage) {
+        match self.dispatch_mode {
+            DispatchMode::Sequential => {
+                for listener in &self.listeners {
+                    listener.on_receive(msg.clone()).await;
+                }
+            }
+            DispatchMode::Concurrent => {
+                join_all(
+                    self.listeners
+                        .iter()
+                        .map(|listener| listener.on_receive(msg.clone())),
+                )
+                .await;
+            }
+        }
+    }
+
+    async fn on_error(&self, err: UStatus) {
+        for listener in &self.listeners {
+            listener.on_error(err.clone()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use test_case::test_case;
+
+    use crate::{UMessageBuilder, UPayloadFormat, UUri};
+
+    use super::*;
+
+    struct RecordingListener {
+        id: usize,
+        received: Arc<Mutex<Vec<(usize, UMessage)>>>,
+    }
+
+    #[async_trait]
+    impl UListener for RecordingListener {
+        async fn on_receive(&self, msg: UMessage) {
+            self.received.lock().unwrap().push((self.id, msg));
+        }
+    }
+
+    #[test_case(DispatchMode::Sequential; "for sequential dispatch")]
+    #[test_case(DispatchMode::Concurrent; "for concurrent dispatch")]
+    #[tokio::test]
+    async fn test_all_listeners_receive_message(dispatch_mode: DispatchMode) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let listener = (0..3).fold(CompositeListener::new(dispatch_mode), |composite, id| {
+            composite.with_listener(Arc::new(RecordingListener {
+                id,
+                received: received.clone(),
+            }))
+        });
+        let msg = UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D").unwrap())
+            .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .unwrap();
+
+        listener.on_receive(msg.clone()).await;
+
+        let received = received.lock().unwrap();
+        let mut ids: Vec<usize> = received.iter().map(|(id, _)| *id).collect();
+        if dispatch_mode == DispatchMode::Concurrent {
+            ids.sort();
+        }
+        assert_eq!(ids, vec![0, 1, 2]);
+        assert!(received.iter().all(|(_, m)| *m == msg));
+    }
+}
@@ -0,0 +1,379 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::error::Error;
+use std::fmt::Display;
+
+use protobuf::Enum;
+
+use crate::{UMessageError, UPayloadFormat};
+
+use super::UPayload;
+
+// The bytes that compressed payload data starts with.
+const ENVELOPE_MAGIC: &[u8] = b"uPCZ";
+// magic + algorithm + original payload format
+const ENVELOPE_HEADER_LEN: usize = ENVELOPE_MAGIC.len() + 2;
+
+/// The maximum size of decompressed payload data that is accepted by [`UPayload::decompressed`] (4 MiB).
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 4 * 1024 * 1024;
+
+/// An error that occurs while restoring the original payload from a compressed payload.
+#[derive(Debug)]
+pub enum DecompressionError {
+    /// Indicates that the decompressed data would exceed the given maximum number of bytes.
+    SizeLimitExceeded(usize),
+    /// Indicates that the payload data cannot be decompressed.
+    InvalidData(UMessageError),
+}
+
+impl Display for DecompressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecompressionError::SizeLimitExceeded(max_size) => f.write_fmt(format_args!(
+                "decompressed payload exceeds maximum size of {} bytes",
+                max_size
+            )),
+            DecompressionError::InvalidData(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for DecompressionError {}
+
+impl From<UMessageError> for DecompressionError {
+    fn from(value: UMessageError) -> Self {
+        DecompressionError::InvalidData(value)
+    }
+}
+
+impl From<DecompressionError> for UMessageError {
+    fn from(value: DecompressionError) -> Self {
+        match value {
+            DecompressionError::InvalidData(e) => e,
+            e => UMessageError::PayloadError(e.to_string()),
+        }
+    }
+}
+
+/// An algorithm for compressing payload data.
+///
+/// The algorithms are implemented by optional backends which need to be enabled
+/// by means of the crate's `gzip` and `zstd` features respectively.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    /// [gzip](https://www.rfc-editor.org/rfc/rfc1952) compression, requires the `gzip` feature.
+    Gzip,
+    /// [Zstandard](https://www.rfc-editor.org/rfc/rfc8878) compression, requires the `zstd` feature.
+    Zstd,
+}
+
+impl CompressionAlgorithm {
+    fn id(&self) -> u8 {
+        match self {
+            CompressionAlgorithm::Gzip => 1,
+            CompressionAlgorithm::Zstd => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(CompressionAlgorithm::Gzip),
+            2 => Some(CompressionAlgorithm::Zstd),
+            _ => None,
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "gzip", feature = "zstd")), allow(unused_variables))]
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>, UMessageError> {
+        match self {
+            #[cfg(feature = "gzip")]
+            CompressionAlgorithm::Gzip => Ok(gzip::compress(data)),
+            #[cfg(feature = "zstd")]
+            CompressionAlgorithm::Zstd => zstd::bulk::compress(data, 0)
+                .map_err(|e| UMessageError::PayloadError(e.to_string())),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "gzip", feature = "zstd")), allow(unused_variables))]
+    fn decompress(&self, data: &[u8], max_size: usize) -> Result<Vec<u8>, DecompressionError> {
+        match self {
+            #[cfg(feature = "gzip")]
+            CompressionAlgorithm::Gzip => gzip::decompress(data, max_size),
+            #[cfg(feature = "zstd")]
+            CompressionAlgorithm::Zstd => {
+                use std::io::Read;
+
+                let decoder = zstd::stream::read::Decoder::new(data)
+                    .map_err(|e| UMessageError::PayloadError(e.to_string()))?;
+                let mut decompressed = Vec::new();
+                // read one more byte than allowed in order to detect data exceeding the limit
+                decoder
+                    .take(max_size as u64 + 1)
+                    .read_to_end(&mut decompressed)
+                    .map_err(|e| UMessageError::PayloadError(e.to_string()))?;
+                if decompressed.len() > max_size {
+                    return Err(DecompressionError::SizeLimitExceeded(max_size));
+                }
+                Ok(decompressed)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported().into()),
+        }
+    }
+
+    fn unsupported(&self) -> UMessageError {
+        UMessageError::PayloadError(format!(
+            "{:?} compression is not supported, the corresponding crate feature needs to be enabled",
+            self
+        ))
+    }
+}
+
+#[cfg(feature = "gzip")]
+mod gzip {
+    use miniz_oxide::inflate::TINFLStatus;
+
+    use crate::UMessageError;
+
+    use super::DecompressionError;
+
+    // magic, compression method (deflate), flags, mtime, extra flags, OS (unknown)
+    const HEADER: [u8; 10] = [0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff];
+    const TRAILER_LEN: usize = 8;
+    const COMPRESSION_LEVEL: u8 = 6;
+
+    pub(super) fn compress(data: &[u8]) -> Vec<u8> {
+        let mut compressed = HEADER.to_vec();
+        compressed.extend(miniz_oxide::deflate::compress_to_vec(
+            data,
+            COMPRESSION_LEVEL,
+        ));
+        compressed.extend(crc32fast::hash(data).to_le_bytes());
+        compressed.extend((data.len() as u32).to_le_bytes());
+        compressed
+    }
+
+    pub(super) fn decompress(data: &[u8], max_size: usize) -> Result<Vec<u8>, DecompressionError> {
+        // only the (minimal) header created by `compress` is supported
+        if data.len() < HEADER.len() + TRAILER_LEN || data[..4] != HEADER[..4] {
+            return Err(UMessageError::from("Invalid gzip data").into());
+        }
+        let (deflated, trailer) =
+            data[HEADER.len()..].split_at(data.len() - HEADER.len() - TRAILER_LEN);
+        let inflated = miniz_oxide::inflate::decompress_to_vec_with_limit(deflated, max_size)
+            .map_err(|e| match e.status {
+                TINFLStatus::HasMoreOutput => DecompressionError::SizeLimitExceeded(max_size),
+                _ => UMessageError::PayloadError(format!("Invalid gzip data: {}", e)).into(),
+            })?;
+        let (crc, size) = trailer.split_at(4);
+        if crc32fast::hash(&inflated).to_le_bytes() != crc
+            || (inflated.len() as u32).to_le_bytes() != size
+        {
+            return Err(UMessageError::from("gzip checksum mismatch").into());
+        }
+        Ok(inflated)
+    }
+}
+
+impl UPayload {
+    /// Creates a compressed copy of this payload.
+    ///
+    /// The compressed payload has format [`UPayloadFormat::UPAYLOAD_FORMAT_RAW`]. Its data consists
+    /// of a small header, which contains the compression algorithm and this payload's format, followed
+    /// by the compressed data. [`UAttributes`](crate::UAttributes) do not provide a field for indicating
+    /// the content encoding, so the header allows receivers to [detect](Self::is_compressed) and
+    /// [restore](Self::decompressed) the original payload.
+    ///
+    /// Note that [`InMemoryRpcServer`](super::InMemoryRpcServer) and
+    /// [`InMemoryRpcClient`](super::InMemoryRpcClient) transparently decompress the payloads
+    /// of incoming requests and responses respectively. Consequently, they also treat (uncompressed)
+    /// payloads of format [`UPayloadFormat::UPAYLOAD_FORMAT_RAW`] as compressed if their data happens
+    /// to start with the header's magic bytes `uPCZ`. Applications that send arbitrary binary data
+    /// to these components should therefore use a different payload format or compress the data explicitly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this payload is already compressed, or if the given algorithm is not supported
+    /// because the corresponding crate feature has not been enabled.
+    pub fn compressed(&self, algorithm: CompressionAlgorithm) -> Result<UPayload, UMessageError> {
+        if self.is_compressed() {
+            return Err(UMessageError::from("Payload is already compressed"));
+        }
+        let compressed = algorithm.compress(&self.payload)?;
+        let mut data = Vec::with_capacity(ENVELOPE_HEADER_LEN + compressed.len());
+        data.extend_from_slice(ENVELOPE_MAGIC);
+        data.push(algorithm.id());
+        data.push(self.payload_format.value() as u8);
+        data.extend(compressed);
+        Ok(UPayload::new(
+            data.into(),
+            UPayloadFormat::UPAYLOAD_FORMAT_RAW,
+        ))
+    }
+
+    /// Checks if this payload has been created using [`UPayload::compressed`].
+    ///
+    /// The check is based on the payload format and the magic bytes at the start of the data only,
+    /// see [`UPayload::compressed`].
+    pub fn is_compressed(&self) -> bool {
+        self.payload_format == UPayloadFormat::UPAYLOAD_FORMAT_RAW
+            && self.payload.len() >= ENVELOPE_HEADER_LEN
+            && self.payload.starts_with(ENVELOPE_MAGIC)
+    }
+
+    /// Restores the original payload from a [compressed](Self::compressed) payload.
+    ///
+    /// This is equivalent to invoking [`UPayload::decompressed_with_limit`] with
+    /// [`DEFAULT_MAX_DECOMPRESSED_SIZE`].
+    ///
+    /// # Returns
+    ///
+    /// the decompressed payload or a copy of this payload, if it is not compressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload data cannot be decompressed, if the decompressed data exceeds
+    /// the default maximum size, or if the compression algorithm is not supported because the
+    /// corresponding crate feature has not been enabled.
+    pub fn decompressed(&self) -> Result<UPayload, UMessageError> {
+        self.decompressed_with_limit(DEFAULT_MAX_DECOMPRESSED_SIZE)
+            .map_err(UMessageError::from)
+    }
+
+    /// Restores the original payload from a [compressed](Self::compressed) payload, limiting the size
+    /// of the decompressed data.
+    ///
+    /// The limit prevents a small compressed payload from exhausting the receiver's memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - The maximum number of bytes of the decompressed data.
+    ///
+    /// # Returns
+    ///
+    /// the decompressed payload or a copy of this payload, if it is not compressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecompressionError::SizeLimitExceeded`] if the decompressed data exceeds the given size.
+    /// Returns [`DecompressionError::InvalidData`] if the payload data cannot be decompressed, or if the
+    /// compression algorithm is not supported because the corresponding crate feature has not been enabled.
+    pub fn decompressed_with_limit(&self, max_size: usize) -> Result<UPayload, DecompressionError> {
+        if !self.is_compressed() {
+            return Ok(self.clone());
+        }
+        let header = &self.payload[ENVELOPE_MAGIC.len()..ENVELOPE_HEADER_LEN];
+        let algorithm = CompressionAlgorithm::from_id(header[0])
+            .ok_or_else(|| UMessageError::from("Unknown compression algorithm"))?;
+        let payload_format = UPayloadFormat::from_i32(i32::from(header[1]))
+            .ok_or_else(|| UMessageError::from("Unknown payload format"))?;
+        let data = algorithm.decompress(&self.payload[ENVELOPE_HEADER_LEN..], max_size)?;
+        Ok(UPayload::new(data.into(), payload_format))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    fn text_payload() -> UPayload {
+        UPayload::new(
+            "Hello, compressed world! ".repeat(20).into(),
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+        )
+    }
+
+    #[cfg_attr(feature = "gzip", test_case(CompressionAlgorithm::Gzip; "for gzip"))]
+    #[cfg_attr(feature = "zstd", test_case(CompressionAlgorithm::Zstd; "for zstd"))]
+    #[allow(dead_code)]
+    fn test_compression_round_trip(algorithm: CompressionAlgorithm) {
+        let payload = text_payload();
+        let compressed = payload.compressed(algorithm).unwrap();
+        assert!(compressed.is_compressed());
+        assert_eq!(
+            compressed.payload_format(),
+            UPayloadFormat::UPAYLOAD_FORMAT_RAW
+        );
+        assert!(compressed.payload.len() < payload.payload.len());
+        assert!(compressed.compressed(algorithm).is_err());
+
+        let decompressed = compressed.decompressed().unwrap();
+        assert_eq!(decompressed.payload_format(), payload.payload_format());
+        assert_eq!(decompressed.payload(), payload.payload());
+    }
+
+    #[cfg_attr(feature = "gzip", test_case(CompressionAlgorithm::Gzip; "for gzip"))]
+    #[cfg_attr(feature = "zstd", test_case(CompressionAlgorithm::Zstd; "for zstd"))]
+    #[allow(dead_code)]
+    fn test_decompression_fails_for_data_exceeding_limit(algorithm: CompressionAlgorithm) {
+        // 1 MiB of zeros compresses to a few hundred bytes at most
+        let payload = UPayload::new(
+            vec![0_u8; 1024 * 1024].into(),
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+        );
+        let compressed = payload.compressed(algorithm).unwrap();
+        assert!(compressed.payload.len() < 4 * 1024);
+
+        assert!(matches!(
+            compressed.decompressed_with_limit(1024 * 1024 - 1),
+            Err(DecompressionError::SizeLimitExceeded(_))
+        ));
+        let decompressed = compressed.decompressed_with_limit(1024 * 1024).unwrap();
+        assert_eq!(decompressed.payload(), payload.payload());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_data_can_be_decompressed_by_other_implementations() {
+        let compressed = text_payload()
+            .compressed(CompressionAlgorithm::Gzip)
+            .unwrap()
+            .payload();
+        let gzip_data = &compressed[ENVELOPE_HEADER_LEN..];
+        // a gzip member consists of header, deflate stream and trailer
+        assert_eq!(gzip_data[..3], [0x1f, 0x8b, 0x08]);
+        let deflated = &gzip_data[10..gzip_data.len() - 8];
+        assert!(miniz_oxide::inflate::decompress_to_vec(deflated).is_ok());
+    }
+
+    #[cfg_attr(not(feature = "gzip"), test_case(CompressionAlgorithm::Gzip; "for gzip"))]
+    #[cfg_attr(not(feature = "zstd"), test_case(CompressionAlgorithm::Zstd; "for zstd"))]
+    #[allow(dead_code)]
+    fn test_compression_fails_for_disabled_algorithm(algorithm: CompressionAlgorithm) {
+        assert!(text_payload().compressed(algorithm).is_err());
+    }
+
+    #[test]
+    fn test_decompressed_returns_uncompressed_payload_unchanged() {
+        let payload = text_payload();
+        assert!(!payload.is_compressed());
+        let decompressed = payload.decompressed().unwrap();
+        assert_eq!(decompressed.payload_format(), payload.payload_format());
+        assert_eq!(decompressed.payload(), payload.payload());
+    }
+
+    #[test]
+    fn test_decompressed_fails_for_unknown_algorithm() {
+        let payload = UPayload::new(
+            [ENVELOPE_MAGIC, &[0xAA, 0x07, 0x01, 0x02]].concat().into(),
+            UPayloadFormat::UPAYLOAD_FORMAT_RAW,
+        );
+        assert!(payload.is_compressed());
+        assert!(payload.decompressed().is_err());
+    }
+}
@@ -0,0 +1,260 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::fmt::Display;
+
+use protobuf::descriptor::FileDescriptorSet;
+use protobuf::reflect::{FileDescriptor, MessageDescriptor, ReflectFieldRef};
+use protobuf::well_known_types::any::Any;
+use protobuf::{Message, MessageDyn};
+
+use crate::umessage::UMessageError;
+use crate::UPayloadFormat;
+
+use super::UPayload;
+
+/// A set of protobuf message types that are known at runtime only.
+///
+/// The message types are created from a [`FileDescriptorSet`], e.g. as produced by
+/// `protoc --include_imports --descriptor_set_out`.
+#[derive(Clone)]
+pub struct DescriptorRegistry {
+    files: Vec<FileDescriptor>,
+}
+
+impl DescriptorRegistry {
+    /// Creates a registry from a set of proto file descriptors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the descriptors are invalid or if any of the files
+    /// imports a file that is not part of the set.
+    pub fn from_file_descriptor_set(set: &FileDescriptorSet) -> Result<Self, UMessageError> {
+        FileDescriptor::new_dynamic_fds(set.file.clone(), &[])
+            .map(|files| DescriptorRegistry { files })
+            .map_err(|e| UMessageError::PayloadError(format!("invalid file descriptors: {e}")))
+    }
+
+    /// Gets the descriptor of a message type.
+    ///
+    /// # Arguments
+    ///
+    /// * `full_name` - The fully qualified name of the message type, e.g. `uprotocol.v1.UStatus`.
+    pub fn message_descriptor(&self, full_name: &str) -> Option<MessageDescriptor> {
+        // protobuf expects fully qualified names to start with a dot
+        let full_name = format!(".{}", full_name.trim_start_matches('.'));
+        self.files
+            .iter()
+            .find_map(|file| file.message_by_full_name(&full_name))
+    }
+}
+
+/// A protobuf message that has been decoded from a [`UPayload`] without a compiled-in type.
+///
+/// This allows generic tooling, e.g. a message inspector, to render arbitrary protobuf payloads
+/// based on the message descriptors contained in a [`DescriptorRegistry`].
+pub struct DynamicPayload {
+    message: Box<dyn MessageDyn>,
+}
+
+impl DynamicPayload {
+    /// Decodes a payload into a dynamic message.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload to decode.
+    /// * `registry` - The registry to look up the message type in.
+    /// * `message_type` - The fully qualified name of the message type contained in the payload.
+    ///   If `None`, the type is determined from the `type_url` of payloads of format
+    ///   [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if
+    /// * the payload's format is neither [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF`] nor
+    ///   [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY`],
+    /// * no message type is given for a payload of format [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF`],
+    /// * the message type is not contained in the registry or
+    /// * the payload data cannot be parsed into a message of the type.
+    pub fn decode(
+        payload: &UPayload,
+        registry: &DescriptorRegistry,
+        message_type: Option<&str>,
+    ) -> Result<Self, UMessageError> {
+        let (type_name, data) = match payload.payload_format() {
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF => {
+                let Some(type_name) = message_type else {
+                    return Err(UMessageError::from(
+                        "Message type is required for decoding plain protobuf payloads",
+                    ));
+                };
+                (type_name.to_string(), payload.as_bytes().to_vec())
+            }
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY => {
+                let any = Any::parse_from_bytes(payload.as_bytes())?;
+                let type_name = match message_type {
+                    Some(type_name) => type_name.to_string(),
+                    None => any
+                        .type_url
+                        .rsplit_once('/')
+                        .map_or(any.type_url.as_str(), |(_, name)| name)
+                        .to_string(),
+                };
+                (type_name, any.value)
+            }
+            _ => {
+                return Err(UMessageError::from(
+                    "Payload does not contain a protobuf message",
+                ))
+            }
+        };
+
+        let descriptor = registry.message_descriptor(&type_name).ok_or_else(|| {
+            UMessageError::PayloadError(format!("Unknown message type: {type_name}"))
+        })?;
+        let message = descriptor.parse_from_bytes(&data)?;
+        Ok(DynamicPayload { message })
+    }
+
+    /// Gets the fully qualified name of the decoded message's type.
+    pub fn message_type(&self) -> String {
+        self.message.descriptor_dyn().full_name().to_string()
+    }
+
+    /// Gets the decoded message.
+    pub fn message(&self) -> &dyn MessageDyn {
+        &*self.message
+    }
+
+    /// Gets the names and rendered values of the message's fields.
+    ///
+    /// Fields are listed in declaration order. Fields that are not set
+    /// or that are empty repeated or map fields are omitted.
+    pub fn fields(&self) -> Vec<(String, String)> {
+        let message = &*self.message;
+        message
+            .descriptor_dyn()
+            .fields()
+            .filter_map(|field| {
+                let value = match field.get_reflect(message) {
+                    ReflectFieldRef::Optional(optional) => optional.value()?.to_string(),
+                    ReflectFieldRef::Repeated(repeated) => {
+                        if repeated.is_empty() {
+                            return None;
+                        }
+                        let values: Vec<String> =
+                            repeated.into_iter().map(|v| v.to_string()).collect();
+                        format!("[{}]", values.join(", "))
+                    }
+                    ReflectFieldRef::Map(map) => {
+                        if map.is_empty() {
+                            return None;
+                        }
+                        let entries: Vec<String> = (&map)
+                            .into_iter()
+                            .map(|(k, v)| format!("{k}: {v}"))
+                            .collect();
+                        format!("{{{}}}", entries.join(", "))
+                    }
+                };
+                Some((field.name().to_string(), value))
+            })
+            .collect()
+    }
+}
+
+impl Display for DynamicPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&protobuf::text_format::print_to_string(&*self.message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::MessageFull;
+
+    use crate::{UCode, UStatus};
+
+    use super::*;
+
+    fn registry_for(descriptor: &MessageDescriptor) -> DescriptorRegistry {
+        let mut set = FileDescriptorSet::new();
+        let mut pending = vec![descriptor.file_descriptor().clone()];
+        while let Some(file) = pending.pop() {
+            if set.file.iter().any(|f| f.name() == file.proto().name()) {
+                continue;
+            }
+            set.file.push(file.proto().clone());
+            pending.extend(file.deps().iter().cloned());
+        }
+        DescriptorRegistry::from_file_descriptor_set(&set).unwrap()
+    }
+
+    #[test]
+    fn test_decode_known_message_dynamically() {
+        let registry = registry_for(&UStatus::descriptor());
+        let status = UStatus::fail_with_code(UCode::NOT_FOUND, "no such entity");
+        let payload = UPayload::try_from_protobuf(status).unwrap();
+
+        let dynamic =
+            DynamicPayload::decode(&payload, &registry, Some("uprotocol.v1.UStatus")).unwrap();
+        assert_eq!(dynamic.message_type(), "uprotocol.v1.UStatus");
+        assert_eq!(
+            dynamic.fields(),
+            vec![
+                ("code".to_string(), "NOT_FOUND".to_string()),
+                ("message".to_string(), "no such entity".to_string()),
+            ]
+        );
+        assert_eq!(
+            dynamic.to_string(),
+            r#"code: NOT_FOUND message: "no such entity""#
+        );
+    }
+
+    #[test]
+    fn test_decode_infers_message_type_from_any() {
+        let registry = registry_for(&UStatus::descriptor());
+        let status = UStatus::fail_with_code(UCode::INTERNAL, "oops");
+        let payload = UPayload::try_from_any(&Any::pack(&status).unwrap()).unwrap();
+
+        let dynamic = DynamicPayload::decode(&payload, &registry, None).unwrap();
+        assert_eq!(dynamic.message_type(), "uprotocol.v1.UStatus");
+        assert_eq!(
+            dynamic.fields(),
+            vec![
+                ("code".to_string(), "INTERNAL".to_string()),
+                ("message".to_string(), "oops".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_fails_for_unsupported_input() {
+        let registry = registry_for(&UStatus::descriptor());
+        let payload = UPayload::try_from_protobuf(UStatus::ok()).unwrap();
+
+        assert!(DynamicPayload::decode(&payload, &registry, None).is_err());
+        assert!(DynamicPayload::decode(&payload, &registry, Some("uprotocol.v1.Unknown")).is_err());
+        let text = UPayload::new("hello".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+        assert!(DynamicPayload::decode(&text, &registry, Some("uprotocol.v1.UStatus")).is_err());
+    }
+
+    #[test]
+    fn test_registry_rejects_incomplete_descriptor_set() {
+        let mut set = FileDescriptorSet::new();
+        set.file
+            .push(UStatus::descriptor().file_descriptor().proto().clone());
+        assert!(DescriptorRegistry::from_file_descriptor_set(&set).is_err());
+    }
+}
@@ -0,0 +1,1162 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{self, BoxStream};
+use protobuf::{Enum, Message};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::{
+    LocalUriProvider, UAttributes, UCode, UListener, UMessage, UMessageBuilder, UMessageType,
+    UPriority, UStatus, UTransport, UUri, UUID,
+};
+
+use super::rpc::stream_index;
+use super::{CallOptions, RpcClient, ServiceInvocationError, UPayload};
+
+type InvocationResult = Result<Option<UPayload>, ServiceInvocationError>;
+
+// identifies invocations that can be coalesced: the method, the caller's token and the request payload
+type CallKey = (UUri, Option<String>, Option<UPayload>);
+
+type PendingCalls = Mutex<HashMap<CallKey, Vec<oneshot::Sender<InvocationResult>>>>;
+
+/// A listener for the response message to a single RPC request.
+struct ResponseListener {
+    request_id: UUID,
+    response_sender: Mutex<Option<oneshot::Sender<UMessage>>>,
+}
+
+// Checks if a message is a response to the request with the given ID.
+fn is_response_to(msg: &UMessage, request_id: &UUID) -> bool {
+    msg.attributes.as_ref().is_some_and(|attributes| {
+        attributes.type_.enum_value_or_default() == UMessageType::UMESSAGE_TYPE_RESPONSE
+            && attributes.reqid.as_ref() == Some(request_id)
+    })
+}
+
+#[async_trait]
+impl UListener for ResponseListener {
+    async fn on_receive(&self, msg: UMessage) {
+        if !is_response_to(&msg, &self.request_id) {
+            return;
+        }
+        if let Some(sender) = self.response_sender.lock().unwrap().take() {
+            // the receiver is gone if the client has already stopped waiting for the response
+            let _ = sender.send(msg);
+        }
+    }
+}
+
+/// A listener for the response messages to a single streaming RPC request.
+struct StreamResponseListener {
+    request_id: UUID,
+    response_sender: mpsc::UnboundedSender<UMessage>,
+}
+
+#[async_trait]
+impl UListener for StreamResponseListener {
+    async fn on_receive(&self, msg: UMessage) {
+        if is_response_to(&msg, &self.request_id) {
+            // the receiver is gone if the client has already stopped consuming the stream
+            let _ = self.response_sender.send(msg);
+        }
+    }
+}
+
+/// Unregisters the listener for the response messages to a streaming RPC request when being dropped.
+struct StreamRegistration {
+    transport: Arc<dyn UTransport>,
+    method: UUri,
+    reply_to_address: UUri,
+    listener: Arc<dyn UListener>,
+}
+
+impl Drop for StreamRegistration {
+    fn drop(&mut self) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("cannot unregister response listener of dropped stream, no runtime available");
+            return;
+        };
+        let transport = self.transport.clone();
+        let method = self.method.clone();
+        let reply_to_address = self.reply_to_address.clone();
+        let listener = self.listener.clone();
+        // unregistering is asynchronous, so it cannot be done while dropping the stream
+        runtime.spawn(async move {
+            let _ = transport
+                .unregister_listener(&method, Some(&reply_to_address), listener)
+                .await;
+        });
+    }
+}
+
+/// The state of a stream of response messages to a streaming RPC request.
+struct ResponseStream {
+    response_receiver: mpsc::UnboundedReceiver<UMessage>,
+    // the response messages that have been received before their predecessors, by stream index
+    pending_responses: BTreeMap<u32, UMessage>,
+    next_index: u32,
+    deadline: Instant,
+    cancellation_token: Option<CancellationToken>,
+    _registration: StreamRegistration,
+}
+
+impl ResponseStream {
+    // Gets the next response message in the order determined by the messages' stream index.
+    async fn next_response(&mut self) -> Result<UMessage, ServiceInvocationError> {
+        loop {
+            if let Some(response) = self.pending_responses.remove(&self.next_index) {
+                self.next_index = self.next_index.wrapping_add(1);
+                return Ok(response);
+            }
+            let received = tokio::time::timeout_at(self.deadline, self.response_receiver.recv());
+            let received = match self.cancellation_token.as_ref() {
+                Some(token) => tokio::select! {
+                    _ = token.cancelled() => return Err(ServiceInvocationError::Cancelled),
+                    received = received => received,
+                },
+                None => received.await,
+            };
+            let response = received
+                .ok()
+                .flatten()
+                .ok_or(ServiceInvocationError::DeadlineExceeded)?;
+            let Some(index) = stream_index(response.attributes.get_or_default()) else {
+                continue;
+            };
+            // ignore duplicates of response messages that have been processed already
+            if index.wrapping_sub(self.next_index) < u32::MAX / 2 {
+                self.pending_responses.entry(index).or_insert(response);
+            }
+        }
+    }
+
+    // Gets the next item of the stream or `None` if the final response message has been received.
+    async fn next_item(&mut self) -> Result<Option<UPayload>, ServiceInvocationError> {
+        let response = self.next_response().await?;
+        let attributes = response.attributes.get_or_default();
+        if attributes.commstatus.is_some() {
+            // the final response message, which indicates the outcome of the invocation
+            return InMemoryRpcClient::process_response(response).map(|_| None);
+        }
+        let payload_format = attributes.payload_format.enum_value_or_default();
+        InMemoryRpcClient::process_response(response).map(|payload| {
+            Some(payload.unwrap_or_else(|| UPayload::new(Bytes::new(), payload_format)))
+        })
+    }
+}
+
+/// An [`RpcClient`] which keeps all information about pending requests in memory.
+///
+/// The client requires an implementation of [`UTransport`] for sending RPC Request messages
+/// to the service provider and for receiving its RPC Response messages.
+///
+/// For each invocation, the client registers a listener for the response message with the transport.
+/// The listener is unregistered again once the response has been received, the request's TTL or the
+/// [maximum waiting time](CallOptions::with_max_wait) has expired or the invocation has been cancelled by means of the [cancellation token](CallOptions::with_cancellation_token).
+/// If an invocation is cancelled after its request has been sent, the client also sends a
+/// [cancellation message](UMessageBuilder::request_cancellation) to the service provider in the background.
+/// Failing to send the cancellation message does not affect the invocation's outcome.
+///
+/// Invocations fail immediately with [`UCode::UNAVAILABLE`](crate::UCode::UNAVAILABLE) if the transport
+/// is [not connected](UTransport::is_connected).
+///
+/// # Coalescing of identical invocations
+///
+/// If [enabled](Self::with_coalescing), concurrent invocations of the same [idempotent](CallOptions::with_idempotent)
+/// method with the same payload and token are coalesced into a single request. Only the first invocation
+/// actually sends a request message, while the others wait for its outcome. Each invocation still
+/// respects its own TTL and cancellation token while waiting. If the first invocation is cancelled
+/// or times out, the waiting invocations do not fail as well but send their own request messages
+/// instead, because the outcome may only result from the first invocation's options. Invocations that specify a
+/// [message ID](CallOptions::with_message_id) are never coalesced.
+pub struct InMemoryRpcClient {
+    transport: Arc<dyn UTransport>,
+    uri_provider: Arc<dyn LocalUriProvider>,
+    coalesce_calls: bool,
+    // the invocations that are in progress, along with the invocations waiting for their outcome
+    pending_calls: PendingCalls,
+}
+
+/// Removes an invocation from the pending calls when being dropped.
+///
+/// This makes sure that waiting invocations do not wait forever if the invocation that they wait for
+/// is dropped before it has completed.
+struct PendingCall<'a> {
+    pending_calls: &'a PendingCalls,
+    // None once the invocation has completed
+    key: Option<CallKey>,
+}
+
+impl PendingCall<'_> {
+    fn complete(mut self, result: &InvocationResult) {
+        let Some(key) = self.key.take() else {
+            return;
+        };
+        let waiters = self.pending_calls.lock().unwrap().remove(&key);
+        if matches!(
+            result,
+            Err(ServiceInvocationError::Cancelled | ServiceInvocationError::DeadlineExceeded)
+        ) {
+            // the outcome may be caused by the invocation's own cancellation token or timeout,
+            // so the waiting invocations send their own requests instead
+            return;
+        }
+        for waiter in waiters.into_iter().flatten() {
+            // the receiver is gone if the invocation has stopped waiting
+            let _ = waiter.send(result.clone());
+        }
+    }
+}
+
+impl Drop for PendingCall<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.pending_calls.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+impl InMemoryRpcClient {
+    /// Creates a new RPC client for a given transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport to use for sending requests and receiving responses.
+    /// * `uri_provider` - The helper for creating the reply-to address of requests.
+    pub fn new(transport: Arc<dyn UTransport>, uri_provider: Arc<dyn LocalUriProvider>) -> Self {
+        InMemoryRpcClient {
+            transport,
+            uri_provider,
+            coalesce_calls: false,
+            pending_calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets whether concurrent invocations of idempotent methods with identical parameters should be
+    /// coalesced into a single request.
+    ///
+    /// Please refer to the [type level documentation](Self#coalescing-of-identical-invocations) for details.
+    /// By default, invocations are not coalesced.
+    pub fn with_coalescing(&mut self, enabled: bool) -> &mut Self {
+        self.coalesce_calls = enabled;
+        self
+    }
+
+    fn create_request(
+        &self,
+        method: UUri,
+        call_options: &CallOptions,
+        payload: Option<UPayload>,
+    ) -> Result<UMessage, ServiceInvocationError> {
+        let mut builder = UMessageBuilder::request(
+            method,
+            self.uri_provider.get_source_uri(),
+            call_options.ttl(),
+        );
+        if let Some(message_id) = call_options.message_id() {
+            if !message_id.is_uprotocol_uuid() {
+                return Err(ServiceInvocationError::InvalidArgument(
+                    "message ID is not a valid uProtocol UUID".to_string(),
+                ));
+            }
+            builder.with_message_id(message_id);
+        }
+        if let Some(priority) = call_options.priority() {
+            if priority.value() < UPriority::UPRIORITY_CS4.value() {
+                return Err(ServiceInvocationError::InvalidArgument(
+                    "RPC requests require a priority of at least CS4".to_string(),
+                ));
+            }
+            builder.with_priority(priority);
+        }
+        if let Some(token) = call_options.token() {
+            builder.with_token(token);
+        }
+        if let Some(traceparent) = call_options.traceparent() {
+            builder.with_traceparent(traceparent);
+        }
+
+        let result = if let Some(payload) = payload {
+            let format = payload.payload_format();
+            builder.build_with_payload(payload.payload(), format)
+        } else {
+            builder.build()
+        };
+        result.map_err(|e| ServiceInvocationError::InvalidArgument(e.to_string()))
+    }
+
+    /// Extracts the outcome of the invocation from a response message.
+    ///
+    /// Response messages indicating a failure contain a non-OK `commstatus` and (optionally)
+    /// a [`UStatus`] providing details in the payload.
+    fn process_response(
+        response_message: UMessage,
+    ) -> Result<Option<UPayload>, ServiceInvocationError> {
+        let attributes = response_message.attributes.get_or_default();
+        let payload_format = attributes.payload_format.enum_value_or_default();
+        match attributes
+            .commstatus
+            .map(|code| code.enum_value_or_default())
+        {
+            Some(code) if code != UCode::OK => {
+                let mut status = response_message
+                    .payload
+                    .as_ref()
+                    .and_then(|data| UStatus::parse_from_tokio_bytes(data).ok())
+                    .unwrap_or_else(|| UStatus::fail_with_code(code, "service invocation failed"));
+                // the commstatus is authoritative
+                status.code = code.into();
+                Err(ServiceInvocationError::from(status))
+            }
+            _ => response_message
+                .payload
+                .map(|data| UPayload::new(data, payload_format).decompressed())
+                .transpose()
+                .map_err(|e| ServiceInvocationError::InvalidArgument(e.to_string())),
+        }
+    }
+
+    async fn await_response(
+        response_receiver: oneshot::Receiver<UMessage>,
+        call_options: &CallOptions,
+    ) -> Result<UMessage, ServiceInvocationError> {
+        let response = async {
+            tokio::time::timeout(call_options.response_timeout(), response_receiver)
+                .await
+                .map_err(|_elapsed| ServiceInvocationError::DeadlineExceeded)?
+                .map_err(|_closed| ServiceInvocationError::DeadlineExceeded)
+        };
+
+        match call_options.cancellation_token() {
+            Some(token) => tokio::select! {
+                _ = token.cancelled() => Err(ServiceInvocationError::Cancelled),
+                result = response => result,
+            },
+            None => response.await,
+        }
+    }
+
+    // Creates the request message for an invocation, unless the invocation cannot be performed at all.
+    async fn prepare_request(
+        &self,
+        method: &UUri,
+        call_options: &CallOptions,
+        payload: Option<UPayload>,
+    ) -> Result<(UMessage, UUID, UUri), ServiceInvocationError> {
+        if call_options
+            .cancellation_token()
+            .is_some_and(|token| token.is_cancelled())
+        {
+            return Err(ServiceInvocationError::Cancelled);
+        }
+
+        if !self.transport.is_connected().await {
+            return Err(ServiceInvocationError::RpcError(UStatus::unavailable(
+                "transport is not connected",
+            )));
+        }
+
+        let request_message = self.create_request(method.clone(), call_options, payload)?;
+        let request_attributes = request_message.attributes.get_or_default();
+        let request_id = request_attributes.id.get_or_default().to_owned();
+        let reply_to_address = request_attributes.source.get_or_default().to_owned();
+        Ok((request_message, request_id, reply_to_address))
+    }
+
+    async fn send_request(
+        &self,
+        method: UUri,
+        call_options: CallOptions,
+        payload: Option<UPayload>,
+    ) -> InvocationResult {
+        let (request_message, request_id, reply_to_address) = self
+            .prepare_request(&method, &call_options, payload)
+            .await?;
+        let request_attributes = request_message.attributes.get_or_default().to_owned();
+
+        let (response_sender, response_receiver) = oneshot::channel();
+        let response_listener: Arc<dyn UListener> = Arc::new(ResponseListener {
+            request_id,
+            response_sender: Mutex::new(Some(response_sender)),
+        });
+        self.transport
+            .register_listener(&method, Some(&reply_to_address), response_listener.clone())
+            .await
+            .map_err(ServiceInvocationError::RpcError)?;
+
+        let result = match self.transport.send(request_message).await {
+            Ok(()) => Self::await_response(response_receiver, &call_options).await,
+            Err(e) => Err(ServiceInvocationError::RpcError(e)),
+        };
+
+        // the listener is not needed anymore, regardless of the outcome
+        let _ = self
+            .transport
+            .unregister_listener(&method, Some(&reply_to_address), response_listener)
+            .await;
+
+        if matches!(result, Err(ServiceInvocationError::Cancelled)) {
+            self.send_cancellation(&request_attributes);
+        }
+        result.and_then(Self::process_response)
+    }
+
+    // Asks the service provider to stop processing a request, without waiting for the message to be sent.
+    fn send_cancellation(&self, request_attributes: &UAttributes) {
+        let cancellation = match UMessageBuilder::request_cancellation(request_attributes).build() {
+            Ok(cancellation) => cancellation,
+            Err(e) => {
+                warn!("failed to create request cancellation message: {}", e);
+                return;
+            }
+        };
+        let transport = self.transport.clone();
+        tokio::spawn(async move {
+            if let Err(e) = transport.send(cancellation).await {
+                warn!(
+                    "failed to send request cancellation message [code: {:?}]: {}",
+                    e.get_code(),
+                    e.get_message()
+                );
+            }
+        });
+    }
+
+    /// Invokes a method that produces a sequence of results.
+    ///
+    /// The client sends a single request message and yields the items contained in the response messages
+    /// that the service provider sends for the request, as described for
+    /// [`StreamingRequestHandler`](super::StreamingRequestHandler). The items are yielded in the order
+    /// determined by the response messages' [stream index](super::stream_index), regardless of the order
+    /// in which the transport delivers the messages. The stream ends once the final response message
+    /// (containing a `commstatus`) has been processed. If the final message indicates a failure,
+    /// the corresponding error is the stream's last item.
+    ///
+    /// The request's TTL or the [maximum waiting time](CallOptions::with_max_wait) applies to the stream
+    /// as a whole. The stream ends with [`ServiceInvocationError::DeadlineExceeded`] if the final response
+    /// message has not been received in time, and with [`ServiceInvocationError::Cancelled`] if the
+    /// invocation is cancelled by means of the [cancellation token](CallOptions::with_cancellation_token).
+    /// Dropping the stream stops listening for further response messages.
+    ///
+    /// Invocations of streaming methods are never coalesced.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The URI representing the method to invoke.
+    /// * `call_options` - Options to include in the request message.
+    /// * `payload` - The (optional) payload to include in the request message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request message cannot be sent.
+    pub async fn invoke_streaming_method(
+        &self,
+        method: UUri,
+        call_options: CallOptions,
+        payload: Option<UPayload>,
+    ) -> Result<BoxStream<'static, Result<UPayload, ServiceInvocationError>>, ServiceInvocationError>
+    {
+        let deadline = Instant::now() + call_options.response_timeout();
+        let (request_message, request_id, reply_to_address) = self
+            .prepare_request(&method, &call_options, payload)
+            .await?;
+
+        let (response_sender, response_receiver) = mpsc::unbounded_channel();
+        let response_listener: Arc<dyn UListener> = Arc::new(StreamResponseListener {
+            request_id,
+            response_sender,
+        });
+        self.transport
+            .register_listener(&method, Some(&reply_to_address), response_listener.clone())
+            .await
+            .map_err(ServiceInvocationError::RpcError)?;
+        let registration = StreamRegistration {
+            transport: self.transport.clone(),
+            method,
+            reply_to_address,
+            listener: response_listener,
+        };
+
+        // the listener is unregistered when the registration is dropped
+        self.transport
+            .send(request_message)
+            .await
+            .map_err(ServiceInvocationError::RpcError)?;
+
+        let response_stream = ResponseStream {
+            response_receiver,
+            pending_responses: BTreeMap::new(),
+            next_index: 0,
+            deadline,
+            cancellation_token: call_options.cancellation_token(),
+            _registration: registration,
+        };
+        let items = stream::unfold(Some(response_stream), |state| async move {
+            let mut response_stream = state?;
+            match response_stream.next_item().await {
+                Ok(Some(item)) => Some((Ok(item), Some(response_stream))),
+                Ok(None) => None,
+                // the stream ends after the first error
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+        Ok(Box::pin(items))
+    }
+
+    async fn await_coalesced_call(
+        &self,
+        outcome_receiver: oneshot::Receiver<InvocationResult>,
+        method: UUri,
+        call_options: CallOptions,
+        payload: Option<UPayload>,
+    ) -> InvocationResult {
+        let outcome = tokio::time::timeout(call_options.response_timeout(), outcome_receiver);
+        let received = match call_options.cancellation_token() {
+            Some(token) => tokio::select! {
+                _ = token.cancelled() => return Err(ServiceInvocationError::Cancelled),
+                received = outcome => received,
+            },
+            None => outcome.await,
+        };
+        match received {
+            Err(_elapsed) => Err(ServiceInvocationError::DeadlineExceeded),
+            Ok(Ok(result)) => result,
+            // the invocation that we have been waiting for has been dropped before completing
+            Ok(Err(_closed)) => self.send_request(method, call_options, payload).await,
+        }
+    }
+}
+
+#[async_trait]
+impl RpcClient for InMemoryRpcClient {
+    async fn invoke_method(
+        &self,
+        method: UUri,
+        call_options: CallOptions,
+        payload: Option<UPayload>,
+    ) -> Result<Option<UPayload>, ServiceInvocationError> {
+        if !self.coalesce_calls
+            || !call_options.is_idempotent()
+            || call_options.message_id().is_some()
+        {
+            return self.send_request(method, call_options, payload).await;
+        }
+
+        let key = (method.clone(), call_options.token(), payload.clone());
+        let outcome_receiver = {
+            let mut pending_calls = self.pending_calls.lock().unwrap();
+            match pending_calls.get_mut(&key) {
+                Some(waiters) => {
+                    let (outcome_sender, outcome_receiver) = oneshot::channel();
+                    waiters.push(outcome_sender);
+                    Some(outcome_receiver)
+                }
+                None => {
+                    pending_calls.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+        if let Some(outcome_receiver) = outcome_receiver {
+            return self
+                .await_coalesced_call(outcome_receiver, method, call_options, payload)
+                .await;
+        }
+
+        let pending_call = PendingCall {
+            pending_calls: &self.pending_calls,
+            key: Some(key),
+        };
+        let result = self.send_request(method, call_options, payload).await;
+        pending_call.complete(&result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use test_case::test_case;
+    use tokio_util::sync::CancellationToken;
+
+    use futures::StreamExt;
+
+    use crate::communication::rpc::stream_message_id;
+    use crate::{InMemoryTransport, UPayloadFormat};
+
+    use super::*;
+
+    struct TestUriProvider;
+
+    impl LocalUriProvider for TestUriProvider {
+        fn get_authority(&self) -> String {
+            "my-vehicle".to_string()
+        }
+        fn get_resource_uri(&self, resource_id: u16) -> UUri {
+            UUri {
+                authority_name: self.get_authority(),
+                ue_id: 0xa34b,
+                ue_version_major: 0x01,
+                resource_id: resource_id as u32,
+                ..Default::default()
+            }
+        }
+        fn get_source_uri(&self) -> UUri {
+            self.get_resource_uri(0x0000)
+        }
+    }
+
+    type Registration = (UUri, Option<UUri>, Arc<dyn UListener>);
+
+    /// A transport that (optionally) answers each request by echoing its payload.
+    #[derive(Default)]
+    struct TestTransport {
+        listeners: Mutex<Vec<Registration>>,
+        respond_to_requests: bool,
+        response_error: Option<UCode>,
+        send_error: Option<UCode>,
+        disconnected: bool,
+        response_delay: Option<Duration>,
+        sent_requests: AtomicUsize,
+        sent_notifications: Mutex<Vec<UMessage>>,
+    }
+
+    impl TestTransport {
+        fn responding() -> Self {
+            TestTransport {
+                respond_to_requests: true,
+                ..Default::default()
+            }
+        }
+
+        fn responding_with_error(code: UCode) -> Self {
+            TestTransport {
+                respond_to_requests: true,
+                response_error: Some(code),
+                ..Default::default()
+            }
+        }
+
+        fn responding_after(delay: Duration) -> Self {
+            TestTransport {
+                respond_to_requests: true,
+                response_delay: Some(delay),
+                ..Default::default()
+            }
+        }
+
+        fn failing(code: UCode) -> Self {
+            TestTransport {
+                send_error: Some(code),
+                ..Default::default()
+            }
+        }
+
+        fn listener_count(&self) -> usize {
+            self.listeners.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait]
+    impl UTransport for TestTransport {
+        async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+            if let Some(code) = self.send_error {
+                return Err(UStatus::fail_with_code(code, "cannot send message"));
+            }
+            if message.attributes.type_.enum_value_or_default()
+                == UMessageType::UMESSAGE_TYPE_NOTIFICATION
+            {
+                self.sent_notifications.lock().unwrap().push(message);
+                return Ok(());
+            }
+            self.sent_requests.fetch_add(1, Ordering::SeqCst);
+            if !self.respond_to_requests {
+                return Ok(());
+            }
+            let mut builder = UMessageBuilder::response_for_request(&message.attributes);
+            let response = match (self.response_error, message.payload) {
+                (Some(code), _) => builder
+                    .with_comm_status(code)
+                    .build_with_protobuf_payload(&UStatus::fail_with_code(code, "no such vehicle")),
+                (None, Some(data)) => builder.build_with_payload(
+                    data,
+                    message.attributes.payload_format.enum_value_or_default(),
+                ),
+                (None, None) => builder.build(),
+            }
+            .unwrap();
+            let attributes = response.attributes.get_or_default();
+            let listeners: Vec<Arc<dyn UListener>> = self
+                .listeners
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(source_filter, sink_filter, _listener)| {
+                    source_filter.matches(attributes.source.get_or_default())
+                        && sink_filter
+                            .as_ref()
+                            .is_some_and(|filter| filter.matches(attributes.sink.get_or_default()))
+                })
+                .map(|(_source, _sink, listener)| listener.clone())
+                .collect();
+            let deliver_response = async move {
+                for listener in listeners {
+                    listener.on_receive(response.clone()).await;
+                }
+            };
+            match self.response_delay {
+                // the response is delivered asynchronously, like by a real transport
+                Some(delay) => {
+                    tokio::spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        deliver_response.await;
+                    });
+                }
+                None => deliver_response.await,
+            }
+            Ok(())
+        }
+
+        async fn is_connected(&self) -> bool {
+            !self.disconnected
+        }
+
+        async fn register_listener(
+            &self,
+            source_filter: &UUri,
+            sink_filter: Option<&UUri>,
+            listener: Arc<dyn UListener>,
+        ) -> Result<(), UStatus> {
+            self.listeners.lock().unwrap().push((
+                source_filter.to_owned(),
+                sink_filter.cloned(),
+                listener,
+            ));
+            Ok(())
+        }
+
+        async fn unregister_listener(
+            &self,
+            source_filter: &UUri,
+            sink_filter: Option<&UUri>,
+            listener: Arc<dyn UListener>,
+        ) -> Result<(), UStatus> {
+            self.listeners.lock().unwrap().retain(|(source, sink, l)| {
+                !(source == source_filter
+                    && sink.as_ref() == sink_filter
+                    && Arc::ptr_eq(l, &listener))
+            });
+            Ok(())
+        }
+    }
+
+    fn method() -> UUri {
+        UUri::try_from("//other-vehicle/D1/1/1A").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_invoke_method_returns_response_payload() {
+        let transport = Arc::new(TestTransport::responding());
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+
+        let response = client
+            .invoke_method(
+                method(),
+                CallOptions::default(),
+                Some(UPayload::new(
+                    "ping".into(),
+                    UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+                )),
+            )
+            .await
+            .expect("invocation should have succeeded")
+            .expect("response should contain payload");
+        assert_eq!(
+            response.payload_format(),
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT
+        );
+        assert_eq!(response.payload(), "ping");
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_invoke_method_decompresses_response_payload() {
+        let transport = Arc::new(TestTransport::responding());
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+        // the transport echoes the (compressed) request payload
+        let request_payload = UPayload::new("ping".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .compressed(crate::communication::CompressionAlgorithm::Gzip)
+            .unwrap();
+
+        let response = client
+            .invoke_method(method(), CallOptions::default(), Some(request_payload))
+            .await
+            .expect("invocation should have succeeded")
+            .expect("response should contain payload");
+        assert_eq!(
+            response.payload_format(),
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT
+        );
+        assert_eq!(response.payload(), "ping");
+    }
+
+    #[tokio::test]
+    async fn test_invoke_method_returns_error_for_failed_response() {
+        let transport = Arc::new(TestTransport::responding_with_error(UCode::NOT_FOUND));
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+
+        let result = client
+            .invoke_method(method(), CallOptions::default(), None)
+            .await;
+        assert!(result.is_err_and(
+            |e| matches!(e, ServiceInvocationError::NotFound(msg) if msg == "no such vehicle")
+        ));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_method_fails_fast_if_transport_is_disconnected() {
+        let transport = Arc::new(TestTransport {
+            respond_to_requests: true,
+            disconnected: true,
+            ..Default::default()
+        });
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+
+        let result = client
+            .invoke_method(method(), CallOptions::default(), None)
+            .await;
+        assert!(result.is_err_and(|e| matches!(
+            e,
+            ServiceInvocationError::RpcError(status) if status.get_code() == UCode::UNAVAILABLE
+        )));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[test]
+    fn test_process_response_uses_commstatus_if_payload_is_missing() {
+        let request = UMessageBuilder::request(method(), TestUriProvider.get_source_uri(), 5_000)
+            .build()
+            .unwrap();
+        let response = UMessageBuilder::response_for_request(&request.attributes)
+            .with_comm_status(UCode::UNAVAILABLE)
+            .build()
+            .unwrap();
+
+        let result = InMemoryRpcClient::process_response(response);
+        assert!(result.is_err_and(|e| matches!(
+            e,
+            ServiceInvocationError::RpcError(status) if status.get_code() == UCode::UNAVAILABLE
+        )));
+    }
+
+    #[test]
+    fn test_process_response_accepts_ok_commstatus() {
+        let request = UMessageBuilder::request(method(), TestUriProvider.get_source_uri(), 5_000)
+            .build()
+            .unwrap();
+        let response = UMessageBuilder::response_for_request(&request.attributes)
+            .with_comm_status(UCode::OK)
+            .build_with_payload("pong", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .unwrap();
+
+        let result = InMemoryRpcClient::process_response(response);
+        assert!(result.is_ok_and(|payload| payload.is_some()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_method_times_out() {
+        let transport = Arc::new(TestTransport::default());
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+        let mut call_options = CallOptions::default();
+        call_options.with_ttl(500);
+
+        let result = client.invoke_method(method(), call_options, None).await;
+        assert!(result.is_err_and(|e| matches!(e, ServiceInvocationError::DeadlineExceeded)));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    fn idempotent_call() -> CallOptions {
+        let mut call_options = CallOptions::default();
+        call_options.with_idempotent(true);
+        call_options
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_identical_concurrent_calls_are_coalesced() {
+        let transport = Arc::new(TestTransport::responding_after(Duration::from_millis(100)));
+        let mut client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+        client.with_coalescing(true);
+        let payload = UPayload::new("ping".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+
+        let (first, second) = tokio::join!(
+            client.invoke_method(method(), idempotent_call(), Some(payload.clone())),
+            client.invoke_method(method(), idempotent_call(), Some(payload.clone())),
+        );
+
+        assert_eq!(first.unwrap(), Some(payload.clone()));
+        assert_eq!(second.unwrap(), Some(payload));
+        assert_eq!(transport.sent_requests.load(Ordering::SeqCst), 1);
+        assert!(client.pending_calls.lock().unwrap().is_empty());
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_coalesced_call_survives_cancellation_of_first_call() {
+        let transport = Arc::new(TestTransport::responding_after(Duration::from_millis(100)));
+        let mut client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+        client.with_coalescing(true);
+        let payload = UPayload::new("ping".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+        let token = CancellationToken::new();
+        let mut cancellable_call = idempotent_call();
+        cancellable_call.with_cancellation_token(token.clone());
+
+        let (first, second, _) = tokio::join!(
+            client.invoke_method(method(), cancellable_call, Some(payload.clone())),
+            client.invoke_method(method(), idempotent_call(), Some(payload.clone())),
+            async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                token.cancel();
+            }
+        );
+
+        assert!(first.is_err_and(|e| matches!(e, ServiceInvocationError::Cancelled)));
+        assert_eq!(second.unwrap(), Some(payload));
+        // the second invocation has sent its own request after the first one had been cancelled
+        assert_eq!(transport.sent_requests.load(Ordering::SeqCst), 2);
+        assert!(client.pending_calls.lock().unwrap().is_empty());
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[test_case(true, true, "pong"; "for different payloads")]
+    #[test_case(true, false, "ping"; "for non-idempotent methods")]
+    #[test_case(false, true, "ping"; "if coalescing is disabled")]
+    #[tokio::test(start_paused = true)]
+    async fn test_calls_are_not_coalesced(
+        coalescing: bool,
+        idempotent: bool,
+        second_payload: &'static str,
+    ) {
+        let transport = Arc::new(TestTransport::responding_after(Duration::from_millis(100)));
+        let mut client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+        client.with_coalescing(coalescing);
+        let mut call_options = CallOptions::default();
+        call_options.with_idempotent(idempotent);
+
+        let (first, second) = tokio::join!(
+            client.invoke_method(
+                method(),
+                call_options.clone(),
+                Some(UPayload::new(
+                    "ping".into(),
+                    UPayloadFormat::UPAYLOAD_FORMAT_TEXT
+                ))
+            ),
+            client.invoke_method(
+                method(),
+                call_options,
+                Some(UPayload::new(
+                    second_payload.into(),
+                    UPayloadFormat::UPAYLOAD_FORMAT_TEXT
+                ))
+            ),
+        );
+
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+        assert_eq!(transport.sent_requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_method_stops_waiting_after_max_wait() {
+        let transport = Arc::new(TestTransport::default());
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+        let mut call_options = CallOptions::default();
+        call_options
+            .with_ttl(3_600_000)
+            .with_max_wait(Duration::from_millis(500));
+
+        let start = tokio::time::Instant::now();
+        let result = client.invoke_method(method(), call_options, None).await;
+        assert!(result.is_err_and(|e| matches!(e, ServiceInvocationError::DeadlineExceeded)));
+        assert_eq!(start.elapsed(), Duration::from_millis(500));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_method_can_be_cancelled() {
+        let transport = Arc::new(TestTransport::default());
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+        let token = CancellationToken::new();
+        let mut call_options = CallOptions::default();
+        call_options.with_cancellation_token(token.clone());
+
+        let invocation = client.invoke_method(method(), call_options, None);
+        let cancellation = async {
+            // give the client a chance to send the request
+            tokio::task::yield_now().await;
+            assert_eq!(transport.listener_count(), 1);
+            token.cancel();
+        };
+        let (result, _) = tokio::join!(invocation, cancellation);
+
+        assert!(result.is_err_and(|e| matches!(e, ServiceInvocationError::Cancelled)));
+        assert_eq!(transport.listener_count(), 0);
+        // the cancellation message is sent in the background
+        tokio::task::yield_now().await;
+        let sent_notifications = transport.sent_notifications.lock().unwrap();
+        assert_eq!(sent_notifications.len(), 1);
+        assert!(sent_notifications[0].attributes.reqid.is_some());
+        assert_eq!(
+            sent_notifications[0].attributes.sink.get_or_default().ue_id,
+            method().ue_id
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invoke_method_fails_for_cancelled_token() {
+        let transport = Arc::new(TestTransport::responding());
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut call_options = CallOptions::default();
+        call_options.with_cancellation_token(token);
+
+        let result = client.invoke_method(method(), call_options, None).await;
+        assert!(result.is_err_and(|e| matches!(e, ServiceInvocationError::Cancelled)));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_method_fails_for_transport_error() {
+        let transport = Arc::new(TestTransport::failing(UCode::UNAVAILABLE));
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+
+        let result = client
+            .invoke_method(method(), CallOptions::default(), None)
+            .await;
+        assert!(result.is_err_and(|e| matches!(
+            e,
+            ServiceInvocationError::RpcError(status) if status.get_code() == UCode::UNAVAILABLE
+        )));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_method_fails_for_invalid_priority() {
+        let transport = Arc::new(TestTransport::responding());
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+        let mut call_options = CallOptions::default();
+        call_options.with_priority(UPriority::UPRIORITY_CS2);
+
+        let result = client.invoke_method(method(), call_options, None).await;
+        assert!(result.is_err_and(|e| matches!(e, ServiceInvocationError::InvalidArgument(_))));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    /// Answers requests with a stream of response messages which are sent in reverse order.
+    struct ReversingStreamingService {
+        transport: Arc<InMemoryTransport>,
+        items: Vec<&'static str>,
+    }
+
+    #[async_trait]
+    impl UListener for ReversingStreamingService {
+        async fn on_receive(&self, msg: UMessage) {
+            let request_attributes = msg.attributes.get_or_default();
+            let mut responses: Vec<UMessage> = self
+                .items
+                .iter()
+                .map(|item| {
+                    UMessageBuilder::response_for_request(request_attributes)
+                        .build_with_payload(*item, UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+                        .unwrap()
+                })
+                .collect();
+            responses.push(
+                UMessageBuilder::response_for_request(request_attributes)
+                    .with_comm_status(UCode::OK)
+                    .build()
+                    .unwrap(),
+            );
+            for (index, response) in responses.iter_mut().enumerate() {
+                response.attributes.as_mut().unwrap().id =
+                    Some(stream_message_id(index as u32)).into();
+            }
+            // a duplicate of the first item must be ignored
+            responses.insert(0, responses[0].clone());
+            for response in responses.into_iter().rev() {
+                self.transport.send(response).await.unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invoke_streaming_method_yields_items_in_order() {
+        let transport = Arc::new(InMemoryTransport::new());
+        transport
+            .register_listener(
+                &UUri::try_from("//*/FFFF/FF/FFFF").unwrap(),
+                Some(&method()),
+                Arc::new(ReversingStreamingService {
+                    transport: transport.clone(),
+                    items: vec!["one", "two", "three"],
+                }),
+            )
+            .await
+            .unwrap();
+        let client = InMemoryRpcClient::new(transport, Arc::new(TestUriProvider));
+
+        let items: Vec<String> = client
+            .invoke_streaming_method(method(), CallOptions::default(), None)
+            .await
+            .unwrap()
+            .map(|item| String::from_utf8(item.unwrap().payload().to_vec()).unwrap())
+            .collect()
+            .await;
+        assert_eq!(items, vec!["one", "two", "three"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_streaming_method_ends_with_timeout() {
+        let transport = Arc::new(TestTransport::default());
+        let client = InMemoryRpcClient::new(transport.clone(), Arc::new(TestUriProvider));
+
+        let mut items = client
+            .invoke_streaming_method(method(), CallOptions::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(transport.listener_count(), 1);
+        assert!(items
+            .next()
+            .await
+            .is_some_and(|item| matches!(item, Err(ServiceInvocationError::DeadlineExceeded))));
+        assert!(items.next().await.is_none());
+        drop(items);
+        // the listener is unregistered asynchronously
+        tokio::task::yield_now().await;
+        assert_eq!(transport.listener_count(), 0);
+    }
+}
@@ -0,0 +1,5186 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as SyncMutex, OnceLock, RwLock as SyncRwLock};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use rand::distributions::{Distribution, WeightedIndex};
+use tokio::sync::{oneshot, Mutex, Notify, RwLock};
+use tokio::task::AbortHandle;
+use tokio::time::Instant;
+use tracing::{debug, error, info_span, warn, Instrument};
+
+use crate::{
+    LocalUriProvider, UAttributes, UAttributesError, UAttributesValidators, UCode, UListener,
+    UMessage, UMessageBuilder, UMessageError, UMessageType, UPriority, UStatus, UTransport, UUri,
+    REQUEST_CANCELLATION_RESOURCE_ID, UUID,
+};
+
+use super::rpc::{remaining_time, stream_message_id};
+use super::{
+    DecompressionError, EchoHandler, RegistrationError, RequestContext, RequestHandler, RpcServer,
+    ServiceInvocationError, StreamingRequestHandler, UPayload, DEFAULT_MAX_DECOMPRESSED_SIZE,
+    DEFAULT_TTL,
+};
+
+type Endpoint = ((UUri, u16), UUri, Arc<dyn UListener>);
+type EndpointRegistry = Arc<RwLock<HashMap<(UUri, u16), Arc<dyn UListener>>>>;
+
+// the resource ID used in the sink filter of the fallback listener
+const WILDCARD_RESOURCE_ID: u16 = 0xFFFF;
+
+/// A function that is invoked for each message that an [`InMemoryRpcServer`] drops without responding to it.
+pub type DropCallback = Arc<dyn Fn(DropReason, &UMessage) + Send + Sync>;
+
+/// A function that is invoked whenever an [`InMemoryRpcServer`] fails to send a response message.
+pub type SendErrorCallback = Arc<dyn Fn(&UStatus) + Send + Sync>;
+
+/// A function that an [`InMemoryRpcServer`] applies to each response message before sending it.
+pub type ResponseInterceptor = Arc<dyn Fn(UMessage) -> UMessage + Send + Sync>;
+
+/// A function that selects the handler to invoke for an RPC request received by an [`InMemoryRpcServer`].
+pub type RequestRouter = Arc<dyn Fn(&UMessage) -> Arc<dyn RequestHandler> + Send + Sync>;
+
+/// The routing information of an endpoint that has been registered with an [`InMemoryRpcServer`].
+///
+/// Specs can be [exported](InMemoryRpcServer::export_endpoints) from a server and can be used for
+/// [restoring](InMemoryRpcServer::restore_endpoints) the same set of endpoints later on, e.g. after
+/// the server has been re-created.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EndpointSpec {
+    /// The pattern defining origin addresses to accept requests from or `None` if requests are
+    /// accepted from all sources.
+    pub origin_filter: Option<UUri>,
+    /// The resource identifier of the (local) method to accept requests for.
+    pub resource_id: u16,
+}
+
+/// Statistics about the requests that an [`InMemoryRpcServer`] has processed for a method.
+///
+/// Requests are counted based on the outcome that has been sent back to the client, regardless of whether
+/// the response message could actually be delivered.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MethodStats {
+    successes: u64,
+    timeouts: u64,
+    errors: HashMap<UCode, u64>,
+}
+
+impl MethodStats {
+    fn record(&mut self, code: UCode) {
+        match code {
+            UCode::OK => self.successes += 1,
+            UCode::DEADLINE_EXCEEDED => self.timeouts += 1,
+            _ => *self.errors.entry(code).or_default() += 1,
+        }
+    }
+
+    /// Gets the number of requests that have been processed successfully.
+    pub fn successes(&self) -> u64 {
+        self.successes
+    }
+
+    /// Gets the number of requests that could not be processed before they expired.
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts
+    }
+
+    /// Gets the number of requests that have failed, grouped by the code sent back to the client.
+    ///
+    /// Timeouts are not included.
+    pub fn errors(&self) -> &HashMap<UCode, u64> {
+        &self.errors
+    }
+
+    /// Gets the number of requests that have failed with a particular code.
+    pub fn error_count(&self, code: UCode) -> u64 {
+        self.errors.get(&code).copied().unwrap_or_default()
+    }
+
+    /// Gets the overall number of requests that have been processed.
+    pub fn total(&self) -> u64 {
+        self.successes + self.timeouts + self.errors.values().sum::<u64>()
+    }
+}
+
+// settings that apply to all endpoints of a server and which can be changed after endpoints have been registered
+#[derive(Default)]
+struct ServerSettings {
+    drop_callback: SyncRwLock<Option<DropCallback>>,
+    send_error_callback: SyncRwLock<Option<SendErrorCallback>>,
+    response_interceptor: SyncRwLock<Option<ResponseInterceptor>>,
+    max_response_send_time: SyncRwLock<Option<Duration>>,
+    max_endpoints: SyncRwLock<Option<usize>>,
+    max_response_payload_size: SyncRwLock<Option<usize>>,
+    // None if the default limit applies
+    max_decompressed_request_size: SyncRwLock<Option<usize>>,
+    validation_mode: SyncRwLock<ValidationMode>,
+    paused: AtomicBool,
+    log_redacted_messages: AtomicBool,
+    delivery_errors: AtomicU64,
+    // the number of valid requests that are currently being processed by any of the endpoints
+    active_requests: AtomicUsize,
+    // notified whenever the number of active requests drops to zero
+    requests_completed: Notify,
+    rate_limiter: SyncMutex<RateLimiter>,
+    // the handler invocations that are currently being processed by any of the endpoints,
+    // along with the reply-to address of the request
+    in_flight_requests: SyncMutex<HashMap<UUID, (UUri, AbortHandle)>>,
+    // only initialized if statistics have been enabled
+    method_stats: OnceLock<SyncMutex<HashMap<u16, MethodStats>>>,
+}
+
+/// The reason why an [`InMemoryRpcServer`] has dropped a message without responding to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DropReason {
+    /// The message does not contain any attributes.
+    NoAttributes,
+    /// The message is not a valid request and lacks the information required for sending back an error response.
+    InvalidAndUnanswerable,
+    /// The request has been sent by the uEntity that provides the invoked method.
+    SelfAddressed,
+    /// A request with the same ID has already been processed.
+    Duplicate,
+}
+
+/// The strictness with which an [`InMemoryRpcServer`] validates incoming requests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Requests need to be [valid request messages](crate::RequestValidator).
+    #[default]
+    Strict,
+    /// Requests that lack attributes which can be inferred are accepted. The following attributes
+    /// are filled in before the request is validated:
+    ///
+    /// * A missing TTL is set to the default TTL of 10 seconds, which is also used by
+    ///   [`CallOptions`](super::CallOptions).
+    /// * A missing (unspecified) priority is set to [`UPriority::UPRIORITY_CS4`], which is the
+    ///   lowest priority allowed for requests.
+    ///
+    /// All other attributes are validated as in [`ValidationMode::Strict`].
+    Lenient,
+}
+
+/// A limit for the rate at which an [`InMemoryRpcServer`] accepts requests from a caller.
+///
+/// The limit is enforced by means of a token bucket: a caller can send up to `max_requests` requests
+/// in a burst. After that, further requests are accepted at a rate of `max_requests` per `period`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    max_requests: u32,
+    period: Duration,
+}
+
+impl RateLimit {
+    /// Creates a new limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_requests` - The maximum number of requests to accept within the given period.
+    /// * `period` - The period of time within which the bucket of tokens is refilled completely.
+    pub fn new(max_requests: u32, period: Duration) -> Self {
+        RateLimit {
+            max_requests,
+            period,
+        }
+    }
+
+    /// Gets the maximum number of requests to accept within the limit's period.
+    pub fn max_requests(&self) -> u32 {
+        self.max_requests
+    }
+
+    /// Gets the period of time within which the bucket of tokens is refilled completely.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        TokenBucket {
+            limit,
+            tokens: f64::from(limit.max_requests),
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        now.duration_since(self.last_refill) >= self.limit.period
+    }
+
+    fn try_acquire(&mut self) -> bool {
+        let now = Instant::now();
+        let capacity = f64::from(self.limit.max_requests);
+        self.tokens = if self.is_full(now) {
+            capacity
+        } else {
+            let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+            (self.tokens + elapsed * capacity / self.limit.period.as_secs_f64()).min(capacity)
+        };
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// the number of callers to keep track of before the buckets of idle callers are discarded
+const MAX_TRACKED_CALLERS: usize = 1024;
+
+#[derive(Default)]
+struct RateLimiter {
+    default_limit: Option<RateLimit>,
+    authority_limits: HashMap<String, RateLimit>,
+    // the buckets of the callers that have sent requests, keyed by authority
+    buckets: HashMap<String, TokenBucket>,
+}
+
+impl RateLimiter {
+    fn try_acquire(&mut self, authority: &str) -> bool {
+        let Some(limit) = self
+            .authority_limits
+            .get(authority)
+            .or(self.default_limit.as_ref())
+            .copied()
+        else {
+            return true;
+        };
+        if !self.buckets.contains_key(authority) && self.buckets.len() >= MAX_TRACKED_CALLERS {
+            // buckets that have been refilled completely do not need to be kept
+            let now = Instant::now();
+            self.buckets.retain(|_, bucket| !bucket.is_full(now));
+        }
+        self.buckets
+            .entry(authority.to_string())
+            .or_insert_with(|| TokenBucket::new(limit))
+            .try_acquire()
+    }
+}
+
+#[derive(Clone)]
+enum Handler {
+    Unary(Arc<dyn RequestHandler>),
+    Streaming(Arc<dyn StreamingRequestHandler>),
+    Health(Arc<dyn Fn() -> UCode + Send + Sync>),
+    Routed(RequestRouter),
+}
+
+struct RequestListener {
+    request_handler: Handler,
+    transport: Arc<dyn UTransport>,
+    // the ID of a probe message that is expected to be received and the means to signal its reception
+    pending_probe: SyncMutex<Option<(UUID, oneshot::Sender<()>)>>,
+    // the IDs of the requests that have been processed already, along with the instant at which
+    // they expire, or None if the endpoint is idempotent
+    processed_requests: Option<SyncMutex<HashMap<UUID, Instant>>>,
+    settings: Arc<ServerSettings>,
+    // the minimum priority that requests need to have in order to be processed
+    min_priority: Option<UPriority>,
+    // the maximum time that may have passed since a request has been created in order for it to be processed
+    max_age: Option<Duration>,
+}
+
+/// Aborts a request handler invocation and removes it from the in-flight requests when being dropped.
+struct InFlightRequest<'a> {
+    listener: &'a RequestListener,
+    request_id: UUID,
+    abort_handle: AbortHandle,
+}
+
+impl Drop for InFlightRequest<'_> {
+    fn drop(&mut self) {
+        // this is a no-op if the invocation has completed already
+        self.abort_handle.abort();
+        self.listener
+            .settings
+            .in_flight_requests
+            .lock()
+            .unwrap()
+            .remove(&self.request_id);
+    }
+}
+
+// Logs a redacted summary of an event in the lifecycle of a request, for auditing purposes.
+// The request ID is included for correlating the events that belong to the same request.
+#[cfg(feature = "trace-messages")]
+fn trace_request_event(
+    event: impl std::fmt::Display,
+    request_attributes: &UAttributes,
+    message: Option<&UMessage>,
+) {
+    tracing::trace!(
+        request_id = request_attributes
+            .message_id()
+            .map(|id| id.to_hyphenated_string()),
+        traceparent = request_attributes.traceparent.as_deref(),
+        redacted_message = message.map(|msg| tracing::field::debug(msg.redacted())),
+        "{}",
+        event
+    );
+}
+
+#[cfg(not(feature = "trace-messages"))]
+#[inline(always)]
+fn trace_request_event(
+    _event: impl std::fmt::Display,
+    _request_attributes: &UAttributes,
+    _message: Option<&UMessage>,
+) {
+}
+
+/// Keeps track of a request that is being processed and notifies waiters once all requests have completed.
+struct ActiveRequest<'a> {
+    settings: &'a ServerSettings,
+}
+
+impl<'a> ActiveRequest<'a> {
+    fn new(settings: &'a ServerSettings) -> Self {
+        settings.active_requests.fetch_add(1, Ordering::SeqCst);
+        ActiveRequest { settings }
+    }
+}
+
+impl Drop for ActiveRequest<'_> {
+    fn drop(&mut self) {
+        if self.settings.active_requests.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.settings.requests_completed.notify_waiters();
+        }
+    }
+}
+
+impl RequestListener {
+    fn report_drop(&self, reason: DropReason, message: &UMessage) {
+        self.log_redacted_message(format_args!("dropping message ({:?})", reason), message);
+        // do not hold the lock while invoking the callback
+        let callback = self.settings.drop_callback.read().unwrap().clone();
+        if let Some(callback) = callback {
+            callback(reason, message);
+        }
+    }
+
+    // Sends a response message, giving up once the request has expired or the configured maximum send time
+    // has elapsed, whichever comes first. This prevents a stuck transport from blocking the processing
+    // of the request indefinitely.
+    async fn send_message(
+        &self,
+        request_attributes: &UAttributes,
+        response_message: UMessage,
+    ) -> bool {
+        if request_attributes.is_no_reply_request() {
+            // the client is not interested in the outcome
+            return true;
+        }
+        let interceptor = self.settings.response_interceptor.read().unwrap().clone();
+        let response_message = match interceptor {
+            Some(intercept) => intercept(response_message),
+            None => response_message,
+        };
+        trace_request_event(
+            "sending response",
+            request_attributes,
+            Some(&response_message),
+        );
+        // the message is consumed by the transport, so we need to keep a copy for logging
+        let redacted_message = self
+            .settings
+            .log_redacted_messages
+            .load(Ordering::Relaxed)
+            .then(|| response_message.redacted());
+        let mut budget = remaining_time(request_attributes);
+        if let Some(max_send_time) = *self.settings.max_response_send_time.read().unwrap() {
+            budget = budget.min(max_send_time);
+        }
+        let error = match tokio::time::timeout(budget, self.transport.send(response_message)).await
+        {
+            Ok(Ok(())) => return true,
+            Ok(Err(status)) => {
+                debug!("failed to send response message: {}", status);
+                status
+            }
+            Err(_elapsed) => {
+                warn!(
+                    "abandoning response message, transport did not accept it within {}ms",
+                    budget.as_millis()
+                );
+                UStatus::deadline_exceeded(format!(
+                    "transport did not accept response message within {}ms",
+                    budget.as_millis()
+                ))
+            }
+        };
+        if let Some(message) = redacted_message {
+            self.log_redacted_message("failed to send response message", &message);
+        }
+        self.report_send_error(&error);
+        false
+    }
+
+    fn log_redacted_message(&self, reason: impl std::fmt::Display, message: &UMessage) {
+        if self.settings.log_redacted_messages.load(Ordering::Relaxed) {
+            debug!("{}: {:?}", reason, message.redacted());
+        }
+    }
+
+    fn report_send_error(&self, error: &UStatus) {
+        // do not hold the lock while invoking the callback
+        let callback = self.settings.send_error_callback.read().unwrap().clone();
+        if let Some(callback) = callback {
+            callback(error);
+        }
+    }
+
+    async fn send_response(
+        &self,
+        request_attributes: &UAttributes,
+        response: Result<UMessage, UMessageError>,
+    ) -> bool {
+        match response {
+            Ok(response_message) => {
+                self.send_message(request_attributes, response_message)
+                    .await
+            }
+            Err(_e) => false,
+        }
+    }
+
+    // Sends the response to a valid request. If the response could not be created, a minimal
+    // error response is sent instead, so that the client does not need to wait for the request to time out.
+    // Returns true if the (original) response has been sent successfully.
+    async fn send_response_or_error(
+        &self,
+        request_attributes: &UAttributes,
+        response: Result<UMessage, UMessageError>,
+    ) -> bool {
+        match response {
+            Ok(response_message) => {
+                self.send_message(request_attributes, response_message)
+                    .await
+            }
+            Err(e) => {
+                self.send_response(
+                    request_attributes,
+                    Self::internal_error_response(request_attributes, e),
+                )
+                .await;
+                false
+            }
+        }
+    }
+
+    fn internal_error_response(
+        request_attributes: &UAttributes,
+        error: UMessageError,
+    ) -> Result<UMessage, UMessageError> {
+        UMessageBuilder::error_response(
+            request_attributes,
+            &UStatus::internal(format!("failed to create response message: {}", error)),
+        )
+    }
+
+    // creates a builder for a response that carries the same trace context as the request
+    fn response_builder(request_attributes: &UAttributes) -> UMessageBuilder {
+        let mut builder = UMessageBuilder::response_for_request(request_attributes);
+        if let Some(traceparent) = request_attributes.traceparent.as_ref() {
+            builder.with_traceparent(traceparent);
+        }
+        builder
+    }
+
+    fn with_stream_index(
+        response: Result<UMessage, UMessageError>,
+        index: u32,
+    ) -> Result<UMessage, UMessageError> {
+        response.map(|mut message| {
+            if let Some(attributes) = message.attributes.as_mut() {
+                attributes.id = Some(stream_message_id(index)).into();
+            }
+            message
+        })
+    }
+
+    fn payload_response(
+        request_attributes: &UAttributes,
+        payload: Option<UPayload>,
+    ) -> Result<UMessage, UMessageError> {
+        let mut builder = Self::response_builder(request_attributes);
+        if let Some(payload) = payload {
+            let format = payload.payload_format();
+            // make sure that the payload is actually encoded in the format declared by the handler
+            builder
+                .with_response_format(format)
+                .build_with_payload(payload.payload(), format)
+        } else {
+            builder.build()
+        }
+    }
+
+    // Checks if a request has been sent by the same uEntity that provides the invoked method.
+    fn is_self_addressed(request_attributes: &UAttributes) -> bool {
+        let (source, sink) = (
+            request_attributes.source.get_or_default(),
+            request_attributes.sink.get_or_default(),
+        );
+        source.authority_name == sink.authority_name
+            && source.ue_id == sink.ue_id
+            && source.ue_version_major == sink.ue_version_major
+    }
+
+    // Checks if a request has been received before and records it otherwise.
+    // Requests are remembered until they expire, after which redeliveries are rejected by the validator anyway.
+    fn is_duplicate(&self, request_attributes: &UAttributes) -> bool {
+        let (Some(processed_requests), Some(request_id)) = (
+            self.processed_requests.as_ref(),
+            request_attributes.message_id(),
+        ) else {
+            return false;
+        };
+        let now = Instant::now();
+        let mut processed_requests = processed_requests.lock().unwrap();
+        processed_requests.retain(|_, expires_at| *expires_at > now);
+        processed_requests
+            .insert(
+                request_id.to_owned(),
+                now + remaining_time(request_attributes),
+            )
+            .is_some()
+    }
+
+    fn track_in_flight_request(
+        &self,
+        context: &RequestContext,
+        abort_handle: AbortHandle,
+    ) -> InFlightRequest<'_> {
+        self.settings.in_flight_requests.lock().unwrap().insert(
+            context.request_id().to_owned(),
+            (context.source().to_owned(), abort_handle.clone()),
+        );
+        InFlightRequest {
+            listener: self,
+            request_id: context.request_id().to_owned(),
+            abort_handle,
+        }
+    }
+
+    // Determines the time that has passed since a request has been created, based on the timestamp
+    // contained in its ID. Requests without a timestamp are considered infinitely old.
+    fn age(request_attributes: &UAttributes) -> Duration {
+        let Some(creation_time) = request_attributes.message_id().and_then(UUID::get_time) else {
+            return Duration::MAX;
+        };
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_millis() as u64);
+        Duration::from_millis(now.saturating_sub(creation_time))
+    }
+
+    // Fills in the missing attributes that can be inferred, if the server is in lenient mode.
+    fn apply_validation_mode(&self, mut msg: UMessage) -> UMessage {
+        if *self.settings.validation_mode.read().unwrap() == ValidationMode::Lenient {
+            if let Some(attributes) = msg.attributes.as_mut() {
+                if attributes.ttl.is_none() {
+                    attributes.ttl = Some(DEFAULT_TTL);
+                }
+                if attributes.priority.enum_value_or_default() == UPriority::UPRIORITY_UNSPECIFIED {
+                    attributes.priority = UPriority::UPRIORITY_CS4.into();
+                }
+            }
+        }
+        msg
+    }
+
+    // Checks if a response payload can be sent, given the configured maximum payload size.
+    fn verify_response_size(&self, payload: &UPayload) -> Result<(), UStatus> {
+        match *self.settings.max_response_payload_size.read().unwrap() {
+            Some(max_size) if payload.as_bytes().len() > max_size => {
+                warn!(
+                    "discarding response payload of {} bytes, exceeds maximum size of {} bytes",
+                    payload.as_bytes().len(),
+                    max_size
+                );
+                Err(UStatus::fail_with_code(
+                    UCode::RESOURCE_EXHAUSTED,
+                    format!(
+                        "response payload exceeds maximum size of {} bytes",
+                        max_size
+                    ),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn record_outcome(&self, resource_id: u16, code: UCode) {
+        if let Some(method_stats) = self.settings.method_stats.get() {
+            method_stats
+                .lock()
+                .unwrap()
+                .entry(resource_id)
+                .or_default()
+                .record(code);
+        }
+    }
+
+    async fn process_unary_request(
+        &self,
+        resource_id: u16,
+        request_handler: Arc<dyn RequestHandler>,
+        request_attributes: &UAttributes,
+        request_payload: UPayload,
+        request_timeout: Duration,
+    ) {
+        let context = RequestContext::from(request_attributes);
+        let invocation = tokio::spawn({
+            let request_handler = request_handler.clone();
+            let context = context.clone();
+            async move {
+                request_handler
+                    .invoke_method_with_context(context, request_payload)
+                    .await
+            }
+        });
+        let _in_flight_request = self.track_in_flight_request(&context, invocation.abort_handle());
+
+        let outcome = match tokio::time::timeout(request_timeout, invocation).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(join_error)) if join_error.is_cancelled() => {
+                request_handler.on_cancelled(&context).await;
+                Err(ServiceInvocationError::Cancelled)
+            }
+            Ok(Err(join_error)) => {
+                // the client would otherwise wait for a response until the request expires
+                error!("request handler panicked: {}", join_error);
+                Err(ServiceInvocationError::RpcError(UStatus::internal(
+                    "request handler failed unexpectedly",
+                )))
+            }
+            Err(_elapsed) => {
+                trace_request_event("request handler timed out", request_attributes, None);
+                Err(ServiceInvocationError::DeadlineExceeded)
+            }
+        };
+
+        let outcome = outcome.and_then(|response_payload| {
+            match response_payload
+                .as_ref()
+                .map(|payload| self.verify_response_size(payload))
+            {
+                Some(Err(status)) => Err(ServiceInvocationError::RpcError(status)),
+                _ => Ok(response_payload),
+            }
+        });
+        let response = match outcome {
+            Ok(response_payload) => {
+                self.record_outcome(resource_id, UCode::OK);
+                Self::payload_response(request_attributes, response_payload)
+            }
+            Err(e) => {
+                let status = UStatus::from(e);
+                self.record_outcome(resource_id, status.get_code());
+                UMessageBuilder::error_response(request_attributes, &status)
+            }
+        };
+        self.send_response_or_error(request_attributes, response)
+            .await;
+    }
+
+    async fn process_valid_request(&self, resource_id: u16, request_message: UMessage) {
+        let request_attributes = request_message.attributes.get_or_default();
+        if Self::is_self_addressed(request_attributes) {
+            // responding to the request would create a loop
+            self.report_drop(DropReason::SelfAddressed, &request_message);
+            return;
+        }
+        // the request has been counted as active already, so draining the server either waits for it
+        // or the request sees the server paused
+        if self.settings.paused.load(Ordering::SeqCst) {
+            self.record_outcome(resource_id, UCode::UNAVAILABLE);
+            let response = UMessageBuilder::error_response(
+                request_attributes,
+                &UStatus::unavailable("service paused"),
+            );
+            self.send_response_or_error(request_attributes, response)
+                .await;
+            return;
+        }
+        if !self
+            .settings
+            .rate_limiter
+            .lock()
+            .unwrap()
+            .try_acquire(&request_attributes.source.authority_name)
+        {
+            debug!(
+                "rejecting request from [{}], rate limit exceeded",
+                request_attributes.source.authority_name
+            );
+            self.record_outcome(resource_id, UCode::RESOURCE_EXHAUSTED);
+            let response = UMessageBuilder::error_response(
+                request_attributes,
+                &UStatus::fail_with_code(UCode::RESOURCE_EXHAUSTED, "rate limit exceeded"),
+            );
+            self.send_response_or_error(request_attributes, response)
+                .await;
+            return;
+        }
+        if let Some(min_priority) = self.min_priority {
+            let priority = request_attributes.priority.enum_value_or_default();
+            if !priority.is_at_least(min_priority) {
+                self.record_outcome(resource_id, UCode::FAILED_PRECONDITION);
+                let response = UMessageBuilder::error_response(
+                    request_attributes,
+                    &UStatus::fail_with_code(
+                        UCode::FAILED_PRECONDITION,
+                        format!(
+                            "method requires priority {} or higher",
+                            min_priority.to_priority_code()
+                        ),
+                    ),
+                );
+                self.send_response_or_error(request_attributes, response)
+                    .await;
+                return;
+            }
+        }
+        if let Some(max_age) = self.max_age {
+            if Self::age(request_attributes) > max_age {
+                self.record_outcome(resource_id, UCode::FAILED_PRECONDITION);
+                let response = UMessageBuilder::error_response(
+                    request_attributes,
+                    &UStatus::fail_with_code(
+                        UCode::FAILED_PRECONDITION,
+                        format!(
+                            "method requires requests to be at most {}ms old",
+                            max_age.as_millis()
+                        ),
+                    ),
+                );
+                self.send_response_or_error(request_attributes, response)
+                    .await;
+                return;
+            }
+        }
+        let request_timeout = remaining_time(request_attributes);
+        let max_decompressed_size = self
+            .settings
+            .max_decompressed_request_size
+            .read()
+            .unwrap()
+            .unwrap_or(DEFAULT_MAX_DECOMPRESSED_SIZE);
+        let request_payload = match UPayload::new(
+            request_message.payload.clone().unwrap_or_default(),
+            request_attributes.payload_format.enum_value_or_default(),
+        )
+        .decompressed_with_limit(max_decompressed_size)
+        {
+            Ok(payload) => payload,
+            Err(e) => {
+                let status = match e {
+                    DecompressionError::SizeLimitExceeded(_) => {
+                        UStatus::fail_with_code(UCode::RESOURCE_EXHAUSTED, e.to_string())
+                    }
+                    DecompressionError::InvalidData(_) => {
+                        UStatus::fail_with_code(UCode::INVALID_ARGUMENT, e.to_string())
+                    }
+                };
+                self.record_outcome(resource_id, status.get_code());
+                let response = UMessageBuilder::error_response(request_attributes, &status);
+                self.send_response_or_error(request_attributes, response)
+                    .await;
+                return;
+            }
+        };
+
+        match &self.request_handler {
+            Handler::Unary(request_handler) => {
+                self.process_unary_request(
+                    resource_id,
+                    request_handler.clone(),
+                    request_attributes,
+                    request_payload,
+                    request_timeout,
+                )
+                .await;
+            }
+            Handler::Routed(router) => {
+                self.process_unary_request(
+                    resource_id,
+                    router(&request_message),
+                    request_attributes,
+                    request_payload,
+                    request_timeout,
+                )
+                .await;
+            }
+            Handler::Health(check) => {
+                let health = check();
+                self.record_outcome(resource_id, health);
+                let response = Self::response_builder(request_attributes)
+                    .with_comm_status(health)
+                    .build();
+                self.send_response_or_error(request_attributes, response)
+                    .await;
+            }
+            Handler::Streaming(request_handler) => {
+                let deadline = Instant::now() + request_timeout;
+                let mut items = request_handler.invoke_method(resource_id, request_payload);
+                let mut index: u32 = 0;
+                loop {
+                    let response = match tokio::time::timeout_at(deadline, items.next()).await {
+                        Ok(Some(Ok(item))) => {
+                            if let Err(status) = self.verify_response_size(&item) {
+                                self.record_outcome(resource_id, status.get_code());
+                                UMessageBuilder::error_response(request_attributes, &status)
+                            } else if self
+                                .send_response_or_error(
+                                    request_attributes,
+                                    Self::with_stream_index(
+                                        Self::payload_response(request_attributes, Some(item)),
+                                        index,
+                                    ),
+                                )
+                                .await
+                            {
+                                index = index.wrapping_add(1);
+                                continue;
+                            } else {
+                                // the client will not be able to process the remaining items
+                                self.record_outcome(resource_id, UCode::UNAVAILABLE);
+                                return;
+                            }
+                        }
+                        Ok(Some(Err(e))) => {
+                            let status = UStatus::from(e);
+                            self.record_outcome(resource_id, status.get_code());
+                            UMessageBuilder::error_response(request_attributes, &status)
+                        }
+                        Ok(None) => {
+                            self.record_outcome(resource_id, UCode::OK);
+                            Self::response_builder(request_attributes)
+                                .with_comm_status(UCode::OK)
+                                .build()
+                        }
+                        Err(_elapsed) => {
+                            trace_request_event(
+                                "request handler timed out",
+                                request_attributes,
+                                None,
+                            );
+                            self.record_outcome(resource_id, UCode::DEADLINE_EXCEEDED);
+                            UMessageBuilder::error_response(
+                                request_attributes,
+                                &UStatus::from(ServiceInvocationError::DeadlineExceeded),
+                            )
+                        }
+                    };
+                    self.send_response_or_error(
+                        request_attributes,
+                        Self::with_stream_index(response, index),
+                    )
+                    .await;
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn process_invalid_request(
+        &self,
+        validation_error: UAttributesError,
+        request_message: UMessage,
+    ) {
+        self.log_redacted_message(
+            format_args!("received invalid request ({})", validation_error),
+            &request_message,
+        );
+        // all we need is a valid source address and a message ID to be able to send back an error message
+        let request_attributes = request_message.attributes.get_or_default();
+        trace_request_event(
+            format_args!("rejecting invalid request ({})", validation_error),
+            request_attributes,
+            None,
+        );
+        if request_attributes.message_id().is_none()
+            || !request_attributes
+                .source_uri()
+                .is_some_and(|uri| uri.is_rpc_response())
+        {
+            self.report_drop(DropReason::InvalidAndUnanswerable, &request_message);
+            return;
+        }
+
+        let response = UMessageBuilder::error_response(
+            request_attributes,
+            &UStatus::fail_with_code(UCode::INVALID_ARGUMENT, validation_error.to_string()),
+        );
+        self.send_response(request_attributes, response).await;
+    }
+}
+
+#[async_trait]
+impl UListener for RequestListener {
+    async fn on_receive(&self, msg: UMessage) {
+        let msg = self.apply_validation_mode(msg);
+        let Some(attributes) = msg.attributes.as_ref() else {
+            self.report_drop(DropReason::NoAttributes, &msg);
+            return;
+        };
+
+        {
+            let mut pending_probe = self.pending_probe.lock().unwrap();
+            if pending_probe
+                .as_ref()
+                .is_some_and(|(probe_id, _)| attributes.message_id() == Some(probe_id))
+            {
+                // the probe is not meant to be processed by the request handler
+                if let Some((_, probe_received)) = pending_probe.take() {
+                    let _ = probe_received.send(());
+                }
+                return;
+            }
+        }
+
+        // correlate all events emitted while processing the request with the client's trace
+        let span = info_span!(
+            "rpc_request",
+            request_id = attributes.message_id().map(|id| id.to_hyphenated_string()),
+            traceparent = attributes.traceparent.as_deref()
+        );
+        trace_request_event("received request", attributes, Some(&msg));
+        let validator = UAttributesValidators::Request.validator();
+        if let Err(e) = validator
+            .validate(attributes)
+            .and_then(|_| validator.is_expired(attributes))
+        {
+            self.process_invalid_request(e, msg).instrument(span).await;
+        } else {
+            if attributes.reqid.is_some() {
+                // requests do not refer to other messages, cancellations are sent as notifications
+                self.process_invalid_request(
+                    UAttributesError::validation_error("request must not refer to another message"),
+                    msg,
+                )
+                .instrument(span)
+                .await;
+                return;
+            }
+            if self.is_duplicate(attributes) {
+                // the request has been redelivered by the transport
+                debug!(parent: &span, "ignoring duplicate request");
+                self.report_drop(DropReason::Duplicate, &msg);
+                return;
+            }
+            // the validator has made sure that the sink contains a valid method resource ID
+            let resource_id = attributes.sink.resource_id as u16;
+            let _active_request = ActiveRequest::new(&self.settings);
+            self.process_valid_request(resource_id, msg)
+                .instrument(span)
+                .await;
+        }
+    }
+
+    async fn on_error(&self, err: UStatus) {
+        warn!(
+            "transport failed to deliver request [code: {:?}]: {}",
+            err.get_code(),
+            err.get_message()
+        );
+        self.settings
+            .delivery_errors
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// A listener that passes requests to a fallback handler, unless they can be processed by
+/// one of the regular endpoints.
+struct FallbackListener {
+    endpoints: EndpointRegistry,
+    delegate: Arc<RequestListener>,
+}
+
+#[async_trait]
+impl UListener for FallbackListener {
+    async fn on_receive(&self, msg: UMessage) {
+        let Some(attributes) = msg.attributes.as_ref() else {
+            return;
+        };
+        if attributes.type_.enum_value_or_default() != UMessageType::UMESSAGE_TYPE_REQUEST {
+            return;
+        }
+        if self
+            .endpoints
+            .read()
+            .await
+            .keys()
+            .any(|(origin_filter, resource_id)| {
+                u32::from(*resource_id) == attributes.sink.resource_id
+                    && origin_filter.matches(&attributes.source)
+            })
+        {
+            // the request is being processed by a regular endpoint
+            return;
+        }
+        self.delegate.on_receive(msg).await;
+    }
+
+    async fn on_error(&self, err: UStatus) {
+        self.delegate.on_error(err).await;
+    }
+}
+
+/// A listener that aborts the handler invocations for the requests that
+/// [cancellation messages](UMessageBuilder::request_cancellation) refer to.
+struct CancellationListener {
+    settings: Arc<ServerSettings>,
+}
+
+#[async_trait]
+impl UListener for CancellationListener {
+    async fn on_receive(&self, msg: UMessage) {
+        let Some(attributes) = msg.attributes.as_ref() else {
+            return;
+        };
+        if UAttributesValidators::Notification
+            .validator()
+            .validate(attributes)
+            .is_err()
+            || attributes.source.resource_id != u32::from(REQUEST_CANCELLATION_RESOURCE_ID)
+        {
+            return;
+        }
+        let Some(request_id) = attributes.request_id() else {
+            return;
+        };
+        // only the client that has sent the original request may cancel it
+        let reply_to_address = UUri {
+            resource_id: UUri::RPC_RESPONSE_ID,
+            ..attributes.source.get_or_default().to_owned()
+        };
+        if let Some((_, abort_handle)) = self
+            .settings
+            .in_flight_requests
+            .lock()
+            .unwrap()
+            .get(request_id)
+            .filter(|(source, _)| *source == reply_to_address)
+        {
+            abort_handle.abort();
+        }
+    }
+}
+
+/// Unregisters an endpoint's listener from the transport and removes it from the endpoint registry.
+async fn unregister_listener(
+    transport: &dyn UTransport,
+    endpoints: &EndpointRegistry,
+    listener_key: &(UUri, u16),
+    sink_filter: &UUri,
+    listener: Arc<dyn UListener>,
+) -> Result<(), RegistrationError> {
+    transport
+        .unregister_listener(&listener_key.0, Some(sink_filter), listener.clone())
+        .await
+        .map_err(RegistrationError::from)?;
+
+    let mut listeners = endpoints.write().await;
+    // the endpoint might have been removed and re-registered in the meantime
+    if listeners
+        .get(listener_key)
+        .is_some_and(|registered| Arc::ptr_eq(registered, &listener))
+    {
+        listeners.remove(listener_key);
+    }
+    Ok(())
+}
+
+/// An endpoint that is unregistered from its [`InMemoryRpcServer`] when the guard is dropped.
+///
+/// Guards are created by means of [`InMemoryRpcServer::register_endpoint_guarded`].
+///
+/// # Drop semantics
+///
+/// Unregistering an endpoint requires interaction with the transport, which is asynchronous. Dropping
+/// the guard therefore only spawns a task on the current Tokio runtime which unregisters the endpoint in
+/// the background. Consequently, the endpoint might still process requests for a short time after the
+/// guard has been dropped, and errors that occur while unregistering the endpoint are only logged.
+/// If no runtime is available when the guard is dropped, the endpoint is not unregistered at all.
+///
+/// Applications that need to know when the endpoint is gone should use [`EndpointGuard::unregister`]
+/// instead of dropping the guard.
+pub struct EndpointGuard {
+    transport: Arc<dyn UTransport>,
+    endpoints: EndpointRegistry,
+    listener_key: (UUri, u16),
+    sink_filter: UUri,
+    // None once the endpoint has been unregistered
+    listener: Option<Arc<dyn UListener>>,
+}
+
+impl EndpointGuard {
+    /// Gets the resource identifier of the method that the endpoint accepts requests for.
+    pub fn resource_id(&self) -> u16 {
+        self.listener_key.1
+    }
+
+    /// Unregisters the endpoint and waits for the unregistration to complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the endpoint's listener cannot be unregistered from the transport.
+    pub async fn unregister(mut self) -> Result<(), RegistrationError> {
+        let Some(listener) = self.listener.take() else {
+            return Ok(());
+        };
+        unregister_listener(
+            self.transport.as_ref(),
+            &self.endpoints,
+            &self.listener_key,
+            &self.sink_filter,
+            listener,
+        )
+        .await
+    }
+}
+
+impl Drop for EndpointGuard {
+    fn drop(&mut self) {
+        let Some(listener) = self.listener.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!(
+                "cannot unregister endpoint [resource ID: {:#06X}] of dropped guard, no runtime available",
+                self.resource_id()
+            );
+            return;
+        };
+        let transport = self.transport.clone();
+        let endpoints = self.endpoints.clone();
+        let listener_key = self.listener_key.clone();
+        let sink_filter = self.sink_filter.clone();
+        // unregistering is asynchronous, so it cannot be done while dropping the guard
+        runtime.spawn(async move {
+            if let Err(e) = unregister_listener(
+                transport.as_ref(),
+                &endpoints,
+                &listener_key,
+                &sink_filter,
+                listener,
+            )
+            .await
+            {
+                warn!(
+                    "failed to unregister endpoint [resource ID: {:#06X}] of dropped guard: {}",
+                    listener_key.1, e
+                );
+            }
+        });
+    }
+}
+
+/// A listener that is only registered temporarily for verifying that a transport accepts listeners.
+struct ProbeListener;
+
+#[async_trait]
+impl UListener for ProbeListener {
+    async fn on_receive(&self, _msg: UMessage) {}
+}
+
+/// An [`RpcServer`] which keeps all information about registered endpoints in memory.
+///
+/// The server requires an implementations of [`UTransport`] for receiving RPC Request messages
+/// and sending back RPC Response messages. Each registered endpoint is backed by a listener that
+/// the server registers with the transport for the endpoint's origin filter and method URI.
+///
+/// Besides regular [`RequestHandler`]s, the server also supports
+/// [streaming endpoints](Self::register_streaming_endpoint) which send back multiple
+/// response messages for a single request.
+///
+/// Requests are processed within a `rpc_request` [tracing](https://docs.rs/tracing) span which
+/// records the request's message ID and [traceparent](crate::UMessage::traceparent). The
+/// traceparent is also copied to all response messages sent for the request.
+///
+/// # Origin filters
+///
+/// An endpoint's origin filter is a pattern that is [matched](UUri::matches) against the reply-to address
+/// of incoming requests. Each of the filter's components may contain a wildcard independently of the others,
+/// e.g. `up://*/A100/1/0` accepts requests from entity type `0xA100` (version 1) on any authority,
+/// while `up://my-vehicle/FFFF/FF/0` accepts requests from any entity on authority `my-vehicle`.
+/// Registering an endpoint without an origin filter is equivalent to using a filter that matches any address.
+/// Origin filters are passed on to the transport as they are, i.e. the authority name of a filter is matched
+/// case sensitively. Applications should therefore use [normalized](UUri::normalize) authority names for
+/// origin filters as well as for the reply-to addresses of requests.
+/// The server's [own address](LocalUriProvider::get_source_uri) cannot be used as an origin filter, and requests
+/// that originate from the uEntity providing the invoked method are dropped, in order to prevent request loops.
+///
+/// There is no precedence among endpoints with more or less specific origin filters. Instead, the origin
+/// filters of all endpoints registered for the same resource ID must not overlap, i.e. there must not be any
+/// address matching more than one of these filters. This guarantees that each request is processed by exactly
+/// one endpoint. An attempt to register an endpoint with an overlapping origin filter fails with
+/// [`RegistrationError::InvalidFilter`].
+///
+/// # Duplicate requests
+///
+/// Some transports may deliver the same request message more than once. The server therefore keeps track of the
+/// IDs of the requests that have been processed by an endpoint until the requests expire, and ignores requests
+/// with an ID that has been seen before. Endpoints for [idempotent methods](Self::register_idempotent_endpoint)
+/// and [health endpoints](Self::register_health_endpoint) do not perform this check, because processing the
+/// same request multiple times does not cause any harm. Note that requests are identified by their message ID
+/// only, i.e. an invocation that is retried using a new message ID is processed again.
+///
+/// # Request cancellation
+///
+/// Clients may cancel a pending request by sending a [cancellation message](UMessageBuilder::request_cancellation)
+/// that refers to the request. Cancellation messages are notifications which are sent to the uEntity that provides
+/// the invoked method, from the client's reply-to address with its resource ID set to
+/// [`REQUEST_CANCELLATION_RESOURCE_ID`](crate::REQUEST_CANCELLATION_RESOURCE_ID). Servers that do not support
+/// cancellation never receive them on their endpoints' listeners. Requests that carry a `reqid` attribute
+/// themselves are rejected with [`UCode::INVALID_ARGUMENT`].
+///
+/// Cancellation is disabled by default and needs to be [enabled](Self::enable_request_cancellation) explicitly.
+/// Once enabled, the server aborts the handler's invocation upon receiving a cancellation message, notifies the
+/// handler by means of [`RequestHandler::on_cancelled`] and answers the request with [`UCode::CANCELLED`]. For that
+/// purpose, the handlers of regular endpoints are invoked on separate tasks. Note that the transport needs to
+/// deliver messages to the server's listeners concurrently, in order for the cancellation message to be processed
+/// while the request is still being processed. Cancellation messages for requests that have been completed already
+/// or that have been sent by a different client are ignored. Streaming endpoints do not support cancellation.
+///
+/// # One-way requests
+///
+/// [One-way requests](UMessageBuilder::request_no_reply) are processed like any other request, but
+/// the outcome of processing them is not sent back to the client.
+///
+/// # Fallback handler
+///
+/// A [fallback handler](Self::register_fallback_handler) can be registered for processing requests
+/// for methods that no endpoint has been registered for, e.g. in order to implement a gateway service.
+/// Regular endpoints always take precedence over the fallback handler, i.e. the fallback handler is only
+/// invoked for requests that none of the registered endpoints accepts.
+pub struct InMemoryRpcServer {
+    transport: Arc<dyn UTransport>,
+    uri_provider: Arc<dyn LocalUriProvider>,
+    request_listeners: EndpointRegistry,
+    fallback_listener: Mutex<Option<Arc<dyn UListener>>>,
+    cancellation_listener: Mutex<Option<Arc<dyn UListener>>>,
+    settings: Arc<ServerSettings>,
+}
+
+impl InMemoryRpcServer {
+    /// Creates a new RPC server for a given transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport to use for receiving requests and sending responses.
+    /// * `uri_provider` - The helper for creating URIs that represent local resources.
+    pub fn new(transport: Arc<dyn UTransport>, uri_provider: Arc<dyn LocalUriProvider>) -> Self {
+        InMemoryRpcServer {
+            transport,
+            uri_provider,
+            request_listeners: Arc::new(RwLock::new(HashMap::new())),
+            fallback_listener: Mutex::new(None),
+            cancellation_listener: Mutex::new(None),
+            settings: Arc::new(ServerSettings::default()),
+        }
+    }
+
+    /// Creates a new RPC server for a given transport after verifying that the transport supports
+    /// the registration of listeners.
+    ///
+    /// In contrast to [`Self::new`], this function probes the transport by registering a listener
+    /// that ignores all messages and unregistering it again right away. This allows detecting
+    /// misconfigured transports when the server is being created instead of when the first endpoint
+    /// is being registered. Note that the listener might receive requests for any of the service's
+    /// methods while it is registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport to use for receiving requests and sending responses.
+    /// * `uri_provider` - The helper for creating URIs that represent local resources.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transport fails to register or unregister the probe listener.
+    pub async fn try_new(
+        transport: Arc<dyn UTransport>,
+        uri_provider: Arc<dyn LocalUriProvider>,
+    ) -> Result<Self, RegistrationError> {
+        let sink_filter = uri_provider.get_resource_uri(WILDCARD_RESOURCE_ID);
+        let listener: Arc<dyn UListener> = Arc::new(ProbeListener);
+        transport
+            .register_listener(&UUri::any(), Some(&sink_filter), listener.clone())
+            .await
+            .map_err(RegistrationError::TransportError)?;
+        transport
+            .unregister_listener(&UUri::any(), Some(&sink_filter), listener)
+            .await
+            .map_err(RegistrationError::TransportError)?;
+        Ok(Self::new(transport, uri_provider))
+    }
+
+    /// Sets a function to invoke for each message that the server drops without responding to it.
+    ///
+    /// This allows operators to count and inspect messages that would otherwise be discarded silently,
+    /// e.g. messages without attributes or invalid requests that lack the information required for
+    /// sending back an error response. The callback applies to all endpoints, including the ones that
+    /// have been registered already. It is invoked on the task processing the message and should
+    /// therefore return quickly.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The function to invoke or `None` to remove a previously set function.
+    pub fn set_drop_callback(&self, callback: Option<DropCallback>) {
+        *self.settings.drop_callback.write().unwrap() = callback;
+    }
+
+    /// Sets a function to invoke whenever a response message cannot be sent.
+    ///
+    /// The function is passed the error reported by the transport, or a status with code
+    /// [`UCode::DEADLINE_EXCEEDED`] if the transport did not accept the message in time
+    /// (see [`Self::set_max_response_send_time`]). This is the only way for operators to learn that
+    /// responses are being lost, e.g. in order to count send failures and raise an alert. The callback
+    /// applies to all endpoints, including the ones that have been registered already. It is invoked
+    /// on the task processing the request and should therefore return quickly.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The function to invoke or `None` to remove a previously set function.
+    pub fn set_send_error_callback(&self, callback: Option<SendErrorCallback>) {
+        *self.settings.send_error_callback.write().unwrap() = callback;
+    }
+
+    /// Sets a function to apply to each response message right before it is sent.
+    ///
+    /// This allows uniformly adjusting all response messages sent by the server, e.g. for signing them
+    /// or tagging them with a tenant ID. The function is applied to the responses to valid requests
+    /// as well as to the error responses sent for invalid requests. It applies to all endpoints,
+    /// including the ones that have been registered already.
+    ///
+    /// The function must not change the message's type nor its `reqid` attribute. Otherwise, clients
+    /// will not be able to correlate the response with their request.
+    ///
+    /// # Arguments
+    ///
+    /// * `interceptor` - The function to apply or `None` to remove a previously set function.
+    pub fn set_response_interceptor(&self, interceptor: Option<ResponseInterceptor>) {
+        *self.settings.response_interceptor.write().unwrap() = interceptor;
+    }
+
+    /// Sets whether messages that the server fails to process should be logged.
+    ///
+    /// If enabled, the server logs requests that it drops or considers invalid, as well as responses
+    /// that cannot be sent, at debug level. The messages are [redacted](UMessage::redacted) before
+    /// being logged, so that sensitive payload data does not end up in log files. Logging is disabled
+    /// by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - `true` if messages should be logged.
+    pub fn set_log_redacted_messages(&self, enabled: bool) {
+        self.settings
+            .log_redacted_messages
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// Pauses the processing of requests, e.g. during a maintenance window.
+    ///
+    /// While the server is paused, requests are answered with [`UCode::UNAVAILABLE`] without
+    /// invoking any handlers. Requests that are being processed already are allowed to complete.
+    pub fn pause(&self) {
+        self.settings.paused.store(true, Ordering::Release);
+    }
+
+    /// Resumes the processing of requests after the server has been [paused](Self::pause).
+    pub fn resume(&self) {
+        self.settings.paused.store(false, Ordering::Release);
+    }
+
+    /// Checks if the server has been [paused](Self::pause).
+    pub fn is_paused(&self) -> bool {
+        self.settings.paused.load(Ordering::Acquire)
+    }
+
+    /// Stops accepting requests and waits for the requests that are being processed to complete.
+    ///
+    /// The server is [paused](Self::pause), i.e. requests received from now on are answered with
+    /// [`UCode::UNAVAILABLE`]. This is useful for shutting down a service instance gracefully, e.g. during a
+    /// rolling restart, without failing requests that have been accepted already. The server can be
+    /// [resumed](Self::resume) afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum amount of time to wait for the requests to complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns the number of requests that are still being processed, if they have not completed
+    /// within the given time.
+    pub async fn drain(&self, timeout: Duration) -> Result<(), usize> {
+        self.settings.paused.store(true, Ordering::SeqCst);
+        let deadline = Instant::now() + timeout;
+        loop {
+            // create the future before checking the count, so that no notification is missed
+            let requests_completed = self.settings.requests_completed.notified();
+            let active_requests = self.settings.active_requests.load(Ordering::SeqCst);
+            if active_requests == 0 {
+                return Ok(());
+            }
+            if tokio::time::timeout_at(deadline, requests_completed)
+                .await
+                .is_err()
+            {
+                return match self.settings.active_requests.load(Ordering::SeqCst) {
+                    0 => Ok(()),
+                    active_requests => Err(active_requests),
+                };
+            }
+        }
+    }
+
+    /// Gets the number of errors that the transport has [reported](UListener::on_error) to the
+    /// server's listeners.
+    ///
+    /// Each error indicates a request that the transport has failed to deliver to the server,
+    /// e.g. because it could not be decoded.
+    pub fn delivery_error_count(&self) -> u64 {
+        self.settings.delivery_errors.load(Ordering::Relaxed)
+    }
+
+    /// Enables the collection of [statistics](MethodStats) about processed requests.
+    ///
+    /// Once enabled, the server counts the outcome of each request that it processes, grouped by the
+    /// resource ID of the invoked method. Statistics are disabled by default, in order to avoid the overhead
+    /// of collecting them, and cannot be disabled again once they have been enabled.
+    pub fn enable_method_stats(&self) {
+        self.settings
+            .method_stats
+            .get_or_init(|| SyncMutex::new(HashMap::new()));
+    }
+
+    /// Gets statistics about the requests that have been processed for a method.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource_id` - The resource identifier of the (local) method.
+    ///
+    /// # Returns
+    ///
+    /// The statistics or `None` if statistics are [not enabled](Self::enable_method_stats)
+    /// or no request has been processed for the method yet.
+    pub fn method_stats(&self, resource_id: u16) -> Option<MethodStats> {
+        self.settings
+            .method_stats
+            .get()
+            .and_then(|method_stats| method_stats.lock().unwrap().get(&resource_id).cloned())
+    }
+
+    /// Gets statistics about the requests that have been processed for all methods.
+    ///
+    /// # Returns
+    ///
+    /// The statistics for each method that at least one request has been processed for.
+    /// The map is empty if statistics are [not enabled](Self::enable_method_stats).
+    pub fn all_stats(&self) -> HashMap<u16, MethodStats> {
+        self.settings
+            .method_stats
+            .get()
+            .map(|method_stats| method_stats.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+
+    /// Sets the maximum amount of time to wait for the transport to accept a response message.
+    ///
+    /// The server waits for the transport to send a response message at most until the request has expired.
+    /// This function can be used to further limit the waiting time, so that a stuck transport does not block
+    /// the processing of requests for too long. Response messages that cannot be sent in time are discarded.
+    /// The limit applies to all endpoints, including the ones that have been registered already.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_send_time` - The maximum time to wait or `None` to wait until the request expires.
+    pub fn set_max_response_send_time(&self, max_send_time: Option<Duration>) {
+        *self.settings.max_response_send_time.write().unwrap() = max_send_time;
+    }
+
+    /// Sets the strictness with which incoming requests are validated.
+    ///
+    /// Some (legacy) clients omit attributes that are required for valid requests. By default, the server
+    /// answers such requests with [`UCode::INVALID_ARGUMENT`]. Using [`ValidationMode::Lenient`], the server
+    /// fills in the missing attributes that can be inferred and processes the requests. The mode applies to
+    /// all endpoints, including the ones that have been registered already.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The validation mode to use.
+    pub fn set_validation_mode(&self, mode: ValidationMode) {
+        *self.settings.validation_mode.write().unwrap() = mode;
+    }
+
+    /// Sets the rate limit that applies to callers which no specific limit has been set for.
+    ///
+    /// Requests are counted per authority of the request's source, regardless of the method being invoked.
+    /// Requests that exceed the limit are answered with [`UCode::RESOURCE_EXHAUSTED`] without invoking the
+    /// request handler. By default, no limit applies.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The limit or `None` to accept requests at any rate.
+    pub fn set_default_rate_limit(&self, limit: Option<RateLimit>) {
+        let mut rate_limiter = self.settings.rate_limiter.lock().unwrap();
+        rate_limiter.default_limit = limit;
+        rate_limiter.buckets.clear();
+    }
+
+    /// Sets the rate limit that applies to callers from a given authority.
+    ///
+    /// This limit takes precedence over the [default limit](Self::set_default_rate_limit).
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The authority of the callers.
+    /// * `limit` - The limit or `None` to apply the default limit to the callers.
+    pub fn set_rate_limit(&self, authority: &str, limit: Option<RateLimit>) {
+        let mut rate_limiter = self.settings.rate_limiter.lock().unwrap();
+        match limit {
+            Some(limit) => rate_limiter
+                .authority_limits
+                .insert(authority.to_string(), limit),
+            None => rate_limiter.authority_limits.remove(authority),
+        };
+        rate_limiter.buckets.remove(authority);
+    }
+
+    /// Sets the maximum size of the payload of response messages.
+    ///
+    /// Transports usually limit the size of the messages that they are able to deliver. This function
+    /// can be used to detect response payloads that exceed the transport's limit before trying to send
+    /// them. If a handler returns a payload that is larger than the given size, the payload is discarded
+    /// and the request is answered with [`UCode::RESOURCE_EXHAUSTED`] instead. For streaming endpoints,
+    /// this also terminates the stream of responses. The limit applies to all endpoints, including the
+    /// ones that have been registered already. By default, the size of payloads is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - The maximum number of bytes or `None` to not limit the size of payloads.
+    pub fn set_max_response_payload_size(&self, max_size: Option<usize>) {
+        *self.settings.max_response_payload_size.write().unwrap() = max_size;
+    }
+
+    /// Sets the maximum size of the decompressed payload of request messages.
+    ///
+    /// The server transparently [decompresses](UPayload::decompressed_with_limit) the payload of incoming
+    /// requests, which also applies to payloads of format [`UPayloadFormat::UPAYLOAD_FORMAT_RAW`](crate::UPayloadFormat::UPAYLOAD_FORMAT_RAW)
+    /// whose data happens to start with the compression header's magic bytes (see [`UPayload::compressed`]).
+    /// The limit prevents a client from exhausting the server's memory by means of a small payload that
+    /// decompresses to a huge amount of data. Requests with a payload that exceeds the limit once
+    /// decompressed are answered with [`UCode::RESOURCE_EXHAUSTED`]. The limit applies to all endpoints,
+    /// including the ones that have been registered already. By default, the limit is
+    /// [`DEFAULT_MAX_DECOMPRESSED_SIZE`].
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - The maximum number of bytes.
+    pub fn set_max_decompressed_request_size(&self, max_size: usize) {
+        *self.settings.max_decompressed_request_size.write().unwrap() = Some(max_size);
+    }
+
+    /// Sets the maximum number of endpoints that can be registered with the server.
+    ///
+    /// This allows bounding the memory used for keeping track of endpoints on resource constrained devices.
+    /// Once the limit has been reached, attempts to register further endpoints fail with
+    /// [`RegistrationError::CapacityExceeded`] until other endpoints have been unregistered. Lowering
+    /// the limit does not affect endpoints that have been registered already. The
+    /// [fallback handler](Self::register_fallback_handler) does not count towards the limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_endpoints` - The maximum number of endpoints or `None` to not limit the number of endpoints.
+    pub fn set_max_endpoints(&self, max_endpoints: Option<usize>) {
+        *self.settings.max_endpoints.write().unwrap() = max_endpoints;
+    }
+
+    fn listener_key(origin_filter: Option<&UUri>, resource_id: u16) -> (UUri, u16) {
+        (
+            origin_filter.map_or_else(UUri::any, UUri::to_owned),
+            resource_id,
+        )
+    }
+
+    fn new_listener(&self, request_handler: Handler) -> Arc<RequestListener> {
+        // health checks do not have any side effects
+        let idempotent = matches!(request_handler, Handler::Health(_));
+        self.new_configured_listener(request_handler, idempotent, None, None)
+    }
+
+    fn new_configured_listener(
+        &self,
+        request_handler: Handler,
+        idempotent: bool,
+        min_priority: Option<UPriority>,
+        max_age: Option<Duration>,
+    ) -> Arc<RequestListener> {
+        Arc::new(RequestListener {
+            request_handler,
+            transport: self.transport.clone(),
+            pending_probe: SyncMutex::new(None),
+            processed_requests: (!idempotent).then(|| SyncMutex::new(HashMap::new())),
+            settings: self.settings.clone(),
+            min_priority,
+            max_age,
+        })
+    }
+
+    fn validate_origin_filter(origin_filter: Option<&UUri>) -> Result<(), RegistrationError> {
+        if let Some(filter) = origin_filter {
+            if !filter.is_rpc_response() {
+                return Err(RegistrationError::InvalidFilter(
+                    "origin filter must have resource ID 0".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies that a request can not be dispatched to more than one endpoint.
+    fn verify_no_conflict<'a, I>(
+        existing_keys: I,
+        listener_key: &(UUri, u16),
+    ) -> Result<(), RegistrationError>
+    where
+        I: Iterator<Item = &'a (UUri, u16)>,
+    {
+        let (origin_filter, resource_id) = listener_key;
+        for (existing_filter, _) in existing_keys.filter(|(_, id)| id == resource_id) {
+            if existing_filter == origin_filter {
+                return Err(RegistrationError::DuplicateEndpoint);
+            }
+            if existing_filter.overlaps(origin_filter) {
+                return Err(RegistrationError::InvalidFilter(format!(
+                    "origin filter overlaps with origin filter [{}] of existing endpoint",
+                    existing_filter.to_uri(false)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_sink_filter(sink_filter: &UUri) -> Result<(), RegistrationError> {
+        sink_filter
+            .verify_rpc_method()
+            .map_err(|e| RegistrationError::InvalidFilter(e.to_string()))
+    }
+
+    /// Verifies that an origin filter does not refer to the server's own address,
+    /// which would make the server process the requests that it sends itself.
+    fn verify_not_self_addressed(
+        &self,
+        origin_filter: Option<&UUri>,
+    ) -> Result<(), RegistrationError> {
+        let own_address = self.uri_provider.get_source_uri();
+        if origin_filter.is_some_and(|filter| filter.normalized() == own_address.normalized()) {
+            return Err(RegistrationError::InvalidFilter(format!(
+                "origin filter must not be the server's own address [{}]",
+                own_address.to_uri(false)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Creates the listener for an endpoint that does not conflict with any of the given endpoints.
+    fn new_endpoint<'a, I>(
+        &self,
+        existing_keys: I,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        listener: Arc<RequestListener>,
+    ) -> Result<Endpoint, RegistrationError>
+    where
+        I: Iterator<Item = &'a (UUri, u16)>,
+    {
+        Self::validate_origin_filter(origin_filter)?;
+        self.verify_not_self_addressed(origin_filter)?;
+        let sink_filter = self.uri_provider.get_resource_uri(resource_id);
+        Self::validate_sink_filter(&sink_filter)?;
+        let listener_key = Self::listener_key(origin_filter, resource_id);
+        Self::verify_no_conflict(existing_keys, &listener_key)?;
+        Ok((listener_key, sink_filter, listener))
+    }
+
+    async fn unregister_from_transport(&self, endpoints: &[Endpoint]) {
+        for (listener_key, sink_filter, listener) in endpoints {
+            let _ = self
+                .transport
+                .unregister_listener(&listener_key.0, Some(sink_filter), listener.clone())
+                .await;
+        }
+    }
+
+    fn verify_capacity(&self, required_endpoints: usize) -> Result<(), RegistrationError> {
+        match *self.settings.max_endpoints.read().unwrap() {
+            Some(max_endpoints) if required_endpoints > max_endpoints => {
+                warn!(
+                    "cannot register endpoint, server supports at most {} endpoints",
+                    max_endpoints
+                );
+                Err(RegistrationError::CapacityExceeded)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Registers the listeners of endpoints with the transport and adds them to the endpoint registry.
+    ///
+    /// The registry is not locked while waiting for the transport, so that (potentially slow) registrations
+    /// do not block each other. Consequently, a conflicting endpoint might have been added to the registry
+    /// in the meantime, in which case none of the given endpoints is added.
+    async fn add_endpoints(&self, endpoints: Vec<Endpoint>) -> Result<(), RegistrationError> {
+        self.add_endpoints_with_lock_mode(endpoints, true).await
+    }
+
+    /// Adds endpoints like [`Self::add_endpoints`] but, if `wait_for_lock` is `false`, fails with
+    /// [`RegistrationError::WouldBlock`] instead of waiting for the endpoint registry to become available.
+    async fn add_endpoints_with_lock_mode(
+        &self,
+        endpoints: Vec<Endpoint>,
+        wait_for_lock: bool,
+    ) -> Result<(), RegistrationError> {
+        for (index, (listener_key, sink_filter, listener)) in endpoints.iter().enumerate() {
+            if let Err(e) = self
+                .transport
+                .register_listener(&listener_key.0, Some(sink_filter), listener.clone())
+                .await
+            {
+                // roll back the endpoints that have already been registered
+                self.unregister_from_transport(&endpoints[..index]).await;
+                return Err(RegistrationError::TransportError(e));
+            }
+        }
+
+        let mut listeners = if wait_for_lock {
+            self.request_listeners.write().await
+        } else if let Ok(listeners) = self.request_listeners.try_write() {
+            listeners
+        } else {
+            self.unregister_from_transport(&endpoints).await;
+            return Err(RegistrationError::WouldBlock);
+        };
+        if let Err(e) = endpoints
+            .iter()
+            .try_for_each(|(listener_key, _, _)| {
+                Self::verify_no_conflict(listeners.keys(), listener_key)
+            })
+            .and_then(|_| self.verify_capacity(listeners.len() + endpoints.len()))
+        {
+            drop(listeners);
+            self.unregister_from_transport(&endpoints).await;
+            return Err(e);
+        }
+        listeners.extend(
+            endpoints
+                .into_iter()
+                .map(|(listener_key, _sink_filter, listener)| (listener_key, listener)),
+        );
+        Ok(())
+    }
+
+    async fn add_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        request_handler: Handler,
+    ) -> Result<(), RegistrationError> {
+        let endpoint = self.new_endpoint(
+            self.request_listeners.read().await.keys(),
+            origin_filter,
+            resource_id,
+            self.new_listener(request_handler),
+        )?;
+        self.add_endpoints(vec![endpoint]).await
+    }
+
+    async fn remove_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+    ) -> Result<(), RegistrationError> {
+        Self::validate_origin_filter(origin_filter)?;
+        let sink_filter = self.uri_provider.get_resource_uri(resource_id);
+        Self::validate_sink_filter(&sink_filter)?;
+
+        let listener_key = Self::listener_key(origin_filter, resource_id);
+        let Some(listener) = self
+            .request_listeners
+            .read()
+            .await
+            .get(&listener_key)
+            .cloned()
+        else {
+            return Err(RegistrationError::NoSuchListener);
+        };
+        unregister_listener(
+            self.transport.as_ref(),
+            &self.request_listeners,
+            &listener_key,
+            &sink_filter,
+            listener,
+        )
+        .await
+    }
+
+    /// Registers multiple endpoints for RPC requests at once.
+    ///
+    /// The registration is performed in an all-or-nothing fashion: all endpoints are validated before
+    /// any of them gets registered. If the registration of one of the endpoints with the transport fails,
+    /// the endpoints that have already been registered are unregistered again.
+    ///
+    /// # Arguments
+    ///
+    /// * `specs` - The origin filter, resource ID and request handler of each endpoint. Please refer to
+    ///   [`RpcServer::register_endpoint`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the endpoints cannot be registered. In this case, none of the endpoints
+    /// is registered.
+    pub async fn register_endpoints(
+        &self,
+        specs: Vec<(Option<UUri>, u16, Arc<dyn RequestHandler>)>,
+    ) -> Result<(), RegistrationError> {
+        let mut endpoints = Vec::with_capacity(specs.len());
+        {
+            let listeners = self.request_listeners.read().await;
+            for (origin_filter, resource_id, request_handler) in specs {
+                let endpoint = self.new_endpoint(
+                    listeners
+                        .keys()
+                        .chain(endpoints.iter().map(|(key, _, _)| key)),
+                    origin_filter.as_ref(),
+                    resource_id,
+                    self.new_listener(Handler::Unary(request_handler)),
+                )?;
+                endpoints.push(endpoint);
+            }
+        }
+        self.add_endpoints(endpoints).await
+    }
+
+    /// Gets the routing information of all registered endpoints.
+    ///
+    /// Request handlers cannot be exported. Applications need to supply them again when
+    /// [restoring](Self::restore_endpoints) the endpoints.
+    ///
+    /// # Returns
+    ///
+    /// The specs of the endpoints, ordered by resource ID. The fallback handler is not included.
+    pub async fn export_endpoints(&self) -> Vec<EndpointSpec> {
+        let mut specs: Vec<EndpointSpec> = self
+            .request_listeners
+            .read()
+            .await
+            .keys()
+            .map(|(origin_filter, resource_id)| EndpointSpec {
+                origin_filter: Some(origin_filter)
+                    .filter(|uri| **uri != UUri::any())
+                    .cloned(),
+                resource_id: *resource_id,
+            })
+            .collect();
+        specs.sort_by_key(|spec| {
+            (
+                spec.resource_id,
+                spec.origin_filter.as_ref().map(String::from),
+            )
+        });
+        specs
+    }
+
+    /// Registers endpoints for RPC requests based on previously [exported](Self::export_endpoints) specs.
+    ///
+    /// The request handlers are not part of the specs. Instead, the given function is invoked for each spec in
+    /// order to determine the handler to register. All endpoints are registered as regular (unary) endpoints
+    /// and in an all-or-nothing fashion, as done by [`Self::register_endpoints`].
+    ///
+    /// # Arguments
+    ///
+    /// * `specs` - The origin filter and resource ID of each endpoint.
+    /// * `resolve_handler` - The function to invoke for getting the handler of an endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the endpoints cannot be registered. In this case, none of the endpoints
+    /// is registered.
+    pub async fn restore_endpoints<F>(
+        &self,
+        specs: &[EndpointSpec],
+        mut resolve_handler: F,
+    ) -> Result<(), RegistrationError>
+    where
+        F: FnMut(&EndpointSpec) -> Arc<dyn RequestHandler>,
+    {
+        let specs = specs
+            .iter()
+            .map(|spec| {
+                (
+                    spec.origin_filter.clone(),
+                    spec.resource_id,
+                    resolve_handler(spec),
+                )
+            })
+            .collect();
+        self.register_endpoints(specs).await
+    }
+
+    /// Registers an endpoint for RPC requests which produces multiple responses per request.
+    ///
+    /// Each item of the stream returned by the handler is sent back to the client in a separate
+    /// response message. Please refer to [`StreamingRequestHandler`] for details regarding how
+    /// the end of the stream is signaled to the client.
+    ///
+    /// The request's TTL applies to the stream as a whole. If the handler has not completed the stream
+    /// before the TTL has expired, the stream is terminated with [`UCode::DEADLINE_EXCEEDED`].
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - A pattern defining origin addresses to accept requests from. If `None`, requests
+    ///   will be accepted from all sources.
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `request_handler` - The handler to invoke for each incoming request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot be registered or if a listener has already been registered
+    /// for the given resource ID.
+    pub async fn register_streaming_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        request_handler: Arc<dyn StreamingRequestHandler>,
+    ) -> Result<(), RegistrationError> {
+        self.add_endpoint(
+            origin_filter,
+            resource_id,
+            Handler::Streaming(request_handler),
+        )
+        .await
+    }
+
+    /// Unregisters a previously [registered streaming endpoint](Self::register_streaming_endpoint).
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - The origin pattern that the endpoint had been registered for.
+    /// * `resource_id` - The (local) resource identifier that the endpoint had been registered for.
+    /// * `request_handler` - The handler to unregister.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot be unregistered.
+    pub async fn unregister_streaming_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        _request_handler: Arc<dyn StreamingRequestHandler>,
+    ) -> Result<(), RegistrationError> {
+        self.remove_endpoint(origin_filter, resource_id).await
+    }
+
+    /// Registers an endpoint that can be used for checking the health of the service.
+    ///
+    /// The endpoint accepts requests from all sources and answers them with a response message
+    /// that contains no payload and has its `commstatus` set to the outcome of the given check.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `check` - The function determining the service's health or `None` to always report
+    ///   [`UCode::OK`]. The function is invoked for each request and should therefore return quickly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot be registered or if a listener has already been registered
+    /// for the given resource ID.
+    pub async fn register_health_endpoint(
+        &self,
+        resource_id: u16,
+        check: Option<Arc<dyn Fn() -> UCode + Send + Sync>>,
+    ) -> Result<(), RegistrationError> {
+        let check = check.unwrap_or_else(|| Arc::new(|| UCode::OK));
+        self.add_endpoint(None, resource_id, Handler::Health(check))
+            .await
+    }
+
+    /// Unregisters a previously [registered health endpoint](Self::register_health_endpoint).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot be unregistered.
+    pub async fn unregister_health_endpoint(
+        &self,
+        resource_id: u16,
+    ) -> Result<(), RegistrationError> {
+        self.remove_endpoint(None, resource_id).await
+    }
+
+    /// Registers an endpoint that answers requests with a response containing the request payload.
+    ///
+    /// The endpoint accepts requests from all sources and uses an [`EchoHandler`] for processing them.
+    /// This is useful for loopback tests and for verifying that transports and clients work as
+    /// expected. The endpoint can be unregistered using [`RpcServer::unregister_endpoint`].
+    ///
+    /// # Arguments
+    ///
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RpcServer::register_endpoint`].
+    pub async fn register_echo_endpoint(&self, resource_id: u16) -> Result<(), RegistrationError> {
+        self.register_endpoint(None, resource_id, Arc::new(EchoHandler))
+            .await
+    }
+
+    /// Registers an endpoint for RPC requests to an idempotent method.
+    ///
+    /// Invoking an idempotent method multiple times with the same arguments has the same effect as invoking
+    /// it once. Consequently, the server does not perform [duplicate request detection](Self#duplicate-requests)
+    /// for the endpoint but passes each request to the handler, including requests that have been redelivered
+    /// by the transport. Otherwise, this function behaves like [`RpcServer::register_endpoint`].
+    ///
+    /// Clients should [mark invocations](super::CallOptions::with_idempotent) of the method accordingly,
+    /// so that failed invocations can be retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - A pattern defining origin addresses to accept requests from. If `None`, requests
+    ///   will be accepted from all sources.
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `request_handler` - The handler to invoke for each incoming request.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RpcServer::register_endpoint`].
+    pub async fn register_idempotent_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        request_handler: Arc<dyn RequestHandler>,
+    ) -> Result<(), RegistrationError> {
+        let endpoint = self.new_endpoint(
+            self.request_listeners.read().await.keys(),
+            origin_filter,
+            resource_id,
+            self.new_configured_listener(Handler::Unary(request_handler), true, None, None),
+        )?;
+        self.add_endpoints(vec![endpoint]).await
+    }
+
+    /// Registers an endpoint for RPC requests that need to have a minimum priority.
+    ///
+    /// This is useful for methods that must only be invoked with an appropriate priority, e.g. safety
+    /// critical commands. Requests that have a [lower priority](UPriority::is_at_least) than the given one
+    /// are answered with [`UCode::FAILED_PRECONDITION`] without invoking the handler. Otherwise, this
+    /// function behaves like [`RpcServer::register_endpoint`].
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - A pattern defining origin addresses to accept requests from. If `None`, requests
+    ///   will be accepted from all sources.
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `request_handler` - The handler to invoke for each incoming request.
+    /// * `min_priority` - The minimum priority that requests need to have.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RpcServer::register_endpoint`].
+    pub async fn register_endpoint_with_min_priority(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        request_handler: Arc<dyn RequestHandler>,
+        min_priority: UPriority,
+    ) -> Result<(), RegistrationError> {
+        let endpoint = self.new_endpoint(
+            self.request_listeners.read().await.keys(),
+            origin_filter,
+            resource_id,
+            self.new_configured_listener(
+                Handler::Unary(request_handler),
+                false,
+                Some(min_priority),
+                None,
+            ),
+        )?;
+        self.add_endpoints(vec![endpoint]).await
+    }
+
+    /// Registers an endpoint for RPC requests that need to be fresh.
+    ///
+    /// This mitigates the replay of stale commands, e.g. a request to unlock the doors that has been recorded
+    /// and is sent again later on. In contrast to a request's TTL, which is chosen by the client, the maximum
+    /// age is enforced by the server. Requests that have been [created](UUID::get_time) longer ago than the
+    /// given maximum age are answered with [`UCode::FAILED_PRECONDITION`] without invoking the handler.
+    /// Otherwise, this function behaves like [`RpcServer::register_endpoint`].
+    ///
+    /// Note that the age of a request is determined based on the local system time. The clocks of the client
+    /// and the server therefore need to be synchronized sufficiently.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - A pattern defining origin addresses to accept requests from. If `None`, requests
+    ///   will be accepted from all sources.
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `request_handler` - The handler to invoke for each incoming request.
+    /// * `max_age` - The maximum time that may have passed since a request has been created.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RpcServer::register_endpoint`].
+    pub async fn register_endpoint_with_max_age(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        request_handler: Arc<dyn RequestHandler>,
+        max_age: Duration,
+    ) -> Result<(), RegistrationError> {
+        let endpoint = self.new_endpoint(
+            self.request_listeners.read().await.keys(),
+            origin_filter,
+            resource_id,
+            self.new_configured_listener(
+                Handler::Unary(request_handler),
+                false,
+                None,
+                Some(max_age),
+            ),
+        )?;
+        self.add_endpoints(vec![endpoint]).await
+    }
+
+    /// Registers an endpoint for RPC requests that selects the handler to invoke for each request.
+    ///
+    /// This is useful for methods whose requests need to be processed differently depending on their
+    /// content or attributes, e.g. for dispatching different versions of a request message to dedicated
+    /// handlers. The endpoint accepts requests from all sources. Otherwise, this function behaves like
+    /// [`RpcServer::register_endpoint`].
+    ///
+    /// The router is invoked for every request on the hot path of request processing. It should therefore
+    /// be cheap and must not block, e.g. by only inspecting the request and cloning a pre-created handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `router` - The function to invoke for selecting the handler of an incoming request.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RpcServer::register_endpoint`].
+    pub async fn register_endpoint_with_router(
+        &self,
+        resource_id: u16,
+        router: RequestRouter,
+    ) -> Result<(), RegistrationError> {
+        self.add_endpoint(None, resource_id, Handler::Routed(router))
+            .await
+    }
+
+    /// Unregisters a previously [registered routing endpoint](Self::register_endpoint_with_router).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot be unregistered.
+    pub async fn unregister_endpoint_with_router(
+        &self,
+        resource_id: u16,
+    ) -> Result<(), RegistrationError> {
+        self.remove_endpoint(None, resource_id).await
+    }
+
+    /// Registers an endpoint that distributes RPC requests among multiple handlers.
+    ///
+    /// Each request is passed to one of the given handlers, which is chosen randomly with a probability
+    /// proportional to the handler's weight. This is useful for gradually rolling out a new implementation
+    /// of a method, e.g. for canary deployments or A/B testing, while clients keep using the same method ID.
+    /// The endpoint accepts requests from all sources and can be unregistered using
+    /// [`Self::unregister_endpoint_with_router`]. Otherwise, this function behaves like
+    /// [`RpcServer::register_endpoint`].
+    ///
+    /// Note that the selection is not deterministic. In particular, the share of requests processed by
+    /// a handler only approximates its relative weight over a large number of requests, and subsequent
+    /// requests from the same client may be processed by different handlers.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `handlers` - The handlers to invoke for incoming requests, along with their weights.
+    ///   Handlers with a weight of zero are never invoked.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RpcServer::register_endpoint`]. Returns [`RegistrationError::InvalidArgument`]
+    /// if no handlers are given or if all of them have a weight of zero.
+    pub async fn register_endpoint_weighted(
+        &self,
+        resource_id: u16,
+        handlers: Vec<(Arc<dyn RequestHandler>, u32)>,
+    ) -> Result<(), RegistrationError> {
+        let (handlers, weights): (Vec<_>, Vec<_>) = handlers
+            .into_iter()
+            .map(|(handler, weight)| (handler, u64::from(weight)))
+            .unzip();
+        let distribution = WeightedIndex::new(weights).map_err(|e| {
+            RegistrationError::InvalidArgument(format!("invalid handler weights: {}", e))
+        })?;
+        let router: RequestRouter = Arc::new(move |_request| {
+            handlers[distribution.sample(&mut rand::thread_rng())].clone()
+        });
+        self.register_endpoint_with_router(resource_id, router)
+            .await
+    }
+
+    /// Registers an endpoint for RPC requests that is unregistered automatically.
+    ///
+    /// This function behaves like [`RpcServer::register_endpoint`] but returns a guard which unregisters
+    /// the endpoint when it is dropped. Please refer to [`EndpointGuard`] for the details and limitations
+    /// of unregistering the endpoint while dropping the guard.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - A pattern defining origin addresses to accept requests from. If `None`, requests
+    ///   will be accepted from all sources.
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `request_handler` - The handler to invoke for each incoming request.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RpcServer::register_endpoint`].
+    pub async fn register_endpoint_guarded(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        request_handler: Arc<dyn RequestHandler>,
+    ) -> Result<EndpointGuard, RegistrationError> {
+        let endpoint = self.new_endpoint(
+            self.request_listeners.read().await.keys(),
+            origin_filter,
+            resource_id,
+            self.new_listener(Handler::Unary(request_handler)),
+        )?;
+        let (listener_key, sink_filter, listener) = endpoint.clone();
+        self.add_endpoints(vec![endpoint]).await?;
+        Ok(EndpointGuard {
+            transport: self.transport.clone(),
+            endpoints: self.request_listeners.clone(),
+            listener_key,
+            sink_filter,
+            listener: Some(listener),
+        })
+    }
+
+    /// Registers an endpoint for RPC requests without waiting for concurrent registrations to complete.
+    ///
+    /// This function behaves like [`RpcServer::register_endpoint`] but fails instead of waiting if the
+    /// server's endpoint registry is being accessed by another operation, either before the listener is
+    /// registered with the transport or when the endpoint is added to the registry afterwards. In the latter
+    /// case, the listener is unregistered from the transport again. Callers may then decide whether
+    /// to retry later on or to fall back to the (blocking) `register_endpoint` function.
+    ///
+    /// Note that the function still waits for the transport to register the listener.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - A pattern defining origin addresses to accept requests from. If `None`, requests
+    ///   will be accepted from all sources.
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `request_handler` - The handler to invoke for each incoming request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistrationError::WouldBlock`] if the server's endpoint registry is locked by another
+    /// operation. Otherwise, returns the same errors as [`RpcServer::register_endpoint`].
+    pub async fn try_register_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        request_handler: Arc<dyn RequestHandler>,
+    ) -> Result<(), RegistrationError> {
+        let endpoint = self.new_endpoint(
+            self.request_listeners
+                .try_read()
+                .map_err(|_| RegistrationError::WouldBlock)?
+                .keys(),
+            origin_filter,
+            resource_id,
+            self.new_listener(Handler::Unary(request_handler)),
+        )?;
+        self.add_endpoints_with_lock_mode(vec![endpoint], false)
+            .await
+    }
+
+    /// Registers an endpoint for RPC requests and confirms that requests actually reach the endpoint.
+    ///
+    /// Some transports need some time to propagate a listener registration before messages are
+    /// actually routed to the listener. This function therefore sends a probe request message to the
+    /// newly registered endpoint after it has been registered and waits for the probe to arrive.
+    /// The probe is consumed by the server and is not passed to the request handler.
+    ///
+    /// The probe is sent from the server's [own address](LocalUriProvider::get_source_uri). Consequently,
+    /// the transport needs to support delivering messages to listeners of the same uEntity. If the
+    /// origin filter does not match the server's address, the endpoint's listener is additionally
+    /// registered for requests from the server's address until the probe has arrived, so that the
+    /// probe bypasses the origin filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - A pattern defining origin addresses to accept requests from. If `None`, requests
+    ///   will be accepted from all sources.
+    /// * `resource_id` - The resource identifier of the (local) method to accept requests for.
+    /// * `request_handler` - The handler to invoke for each incoming request.
+    /// * `timeout` - The maximum amount of time to wait for the probe to arrive.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RpcServer::register_endpoint`]. Returns [`RegistrationError::Unknown`]
+    /// if the probe cannot be sent or does not arrive within the given time. In this case, the endpoint
+    /// is unregistered again.
+    pub async fn register_endpoint_and_confirm(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        request_handler: Arc<dyn RequestHandler>,
+        timeout: Duration,
+    ) -> Result<(), RegistrationError> {
+        let listener = self.new_listener(Handler::Unary(request_handler));
+        let endpoint = self.new_endpoint(
+            self.request_listeners.read().await.keys(),
+            origin_filter,
+            resource_id,
+            listener.clone(),
+        )?;
+        let sink_filter = endpoint.1.clone();
+        self.add_endpoints(vec![endpoint]).await?;
+
+        let probe_id = UUID::build();
+        let (probe_received, probe_arrival) = oneshot::channel();
+        *listener.pending_probe.lock().unwrap() = Some((probe_id.clone(), probe_received));
+        let probe_source = self.uri_provider.get_source_uri();
+        let ttl = u32::try_from(timeout.as_millis())
+            .unwrap_or(u32::MAX)
+            .max(1);
+        // the probe is consumed by the listener before any requests are passed to the handler,
+        // so the listener can safely accept (self-addressed) requests from the server's address
+        let bypass_listener: Option<Arc<dyn UListener>> = origin_filter
+            .is_some_and(|filter| !filter.matches(&probe_source))
+            .then(|| listener.clone() as Arc<dyn UListener>);
+
+        let confirmation = async {
+            if let Some(bypass_listener) = bypass_listener.as_ref() {
+                self.transport
+                    .register_listener(&probe_source, Some(&sink_filter), bypass_listener.clone())
+                    .await?;
+            }
+            let probe = UMessageBuilder::request(sink_filter.clone(), probe_source.clone(), ttl)
+                .with_message_id(probe_id)
+                .build()
+                .map_err(|e| UStatus::fail_with_code(UCode::INTERNAL, e.to_string()))?;
+            self.transport.send(probe).await?;
+            tokio::time::timeout(timeout, probe_arrival)
+                .await
+                .map_err(UStatus::from)?
+                .map_err(|_| UStatus::fail_with_code(UCode::INTERNAL, "probe has been discarded"))
+        }
+        .await;
+        if let Some(bypass_listener) = bypass_listener {
+            // this fails if the listener could not be registered in the first place
+            let _ = self
+                .transport
+                .unregister_listener(&probe_source, Some(&sink_filter), bypass_listener)
+                .await;
+        }
+
+        if let Err(status) = confirmation {
+            listener.pending_probe.lock().unwrap().take();
+            let _ = self.remove_endpoint(origin_filter, resource_id).await;
+            return Err(RegistrationError::Unknown(UStatus::fail_with_code(
+                status.get_code(),
+                format!(
+                    "failed to confirm registration of endpoint: {}",
+                    status.get_message()
+                ),
+            )));
+        }
+        Ok(())
+    }
+
+    /// Registers a handler for requests that are not accepted by any of the registered endpoints.
+    ///
+    /// The handler is invoked for requests from any origin that are sent to any of the (local)
+    /// methods of the service which no matching endpoint has been registered for.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_handler` - The handler to invoke for each unmatched request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistrationError::DuplicateEndpoint`] if a fallback handler has already been registered.
+    /// Otherwise, returns an error if the listener cannot be registered with the transport.
+    pub async fn register_fallback_handler(
+        &self,
+        request_handler: Arc<dyn RequestHandler>,
+    ) -> Result<(), RegistrationError> {
+        let mut fallback_listener = self.fallback_listener.lock().await;
+        if fallback_listener.is_some() {
+            return Err(RegistrationError::DuplicateEndpoint);
+        }
+        let listener: Arc<dyn UListener> = Arc::new(FallbackListener {
+            endpoints: self.request_listeners.clone(),
+            delegate: self.new_listener(Handler::Unary(request_handler)),
+        });
+        self.transport
+            .register_listener(
+                &UUri::any(),
+                Some(&self.uri_provider.get_resource_uri(WILDCARD_RESOURCE_ID)),
+                listener.clone(),
+            )
+            .await
+            .map_err(RegistrationError::TransportError)?;
+        *fallback_listener = Some(listener);
+        Ok(())
+    }
+
+    /// Unregisters a previously [registered fallback handler](Self::register_fallback_handler).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistrationError::NoSuchListener`] if no fallback handler is registered.
+    /// Otherwise, returns an error if the listener cannot be unregistered from the transport.
+    pub async fn unregister_fallback_handler(&self) -> Result<(), RegistrationError> {
+        let mut fallback_listener = self.fallback_listener.lock().await;
+        let Some(listener) = fallback_listener.as_ref() else {
+            return Err(RegistrationError::NoSuchListener);
+        };
+        self.transport
+            .unregister_listener(
+                &UUri::any(),
+                Some(&self.uri_provider.get_resource_uri(WILDCARD_RESOURCE_ID)),
+                listener.clone(),
+            )
+            .await
+            .map_err(RegistrationError::from)?;
+        fallback_listener.take();
+        Ok(())
+    }
+
+    // matches the source of cancellation messages sent by any client
+    fn cancellation_source_filter() -> UUri {
+        UUri {
+            resource_id: u32::from(REQUEST_CANCELLATION_RESOURCE_ID),
+            ..UUri::any()
+        }
+    }
+
+    /// Enables the cancellation of requests that are being processed by the server's endpoints.
+    ///
+    /// The server registers a listener with the transport for [cancellation messages](UMessageBuilder::request_cancellation)
+    /// that are addressed to the server's [own uEntity](LocalUriProvider::get_source_uri). See
+    /// [request cancellation](Self#request-cancellation) for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistrationError::DuplicateEndpoint`] if cancellation has already been enabled.
+    /// Otherwise, returns an error if the listener cannot be registered with the transport.
+    pub async fn enable_request_cancellation(&self) -> Result<(), RegistrationError> {
+        let mut cancellation_listener = self.cancellation_listener.lock().await;
+        if cancellation_listener.is_some() {
+            return Err(RegistrationError::DuplicateEndpoint);
+        }
+        let listener: Arc<dyn UListener> = Arc::new(CancellationListener {
+            settings: self.settings.clone(),
+        });
+        self.transport
+            .register_listener(
+                &Self::cancellation_source_filter(),
+                Some(&self.uri_provider.get_source_uri()),
+                listener.clone(),
+            )
+            .await
+            .map_err(RegistrationError::TransportError)?;
+        *cancellation_listener = Some(listener);
+        Ok(())
+    }
+
+    /// Registers the listeners of all endpoints, of the fallback handler and of the
+    /// [request cancellation](Self::enable_request_cancellation) with the transport again.
+    ///
+    /// Transports might lose their listener registrations when the connection to the underlying
+    /// messaging infrastructure gets interrupted. Applications can use this function for restoring
+    /// the server's routing once the transport has reconnected.
+    ///
+    /// The listeners are registered one by one. A failure to register one of the listeners does not
+    /// prevent the remaining listeners from being registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the listeners cannot be registered with the transport. If a single
+    /// listener has failed, the error is the one reported by the transport. Otherwise, the error is
+    /// [`RegistrationError::Unknown`] with a status that lists all endpoints that have failed.
+    pub async fn reregister_all(&self) -> Result<(), RegistrationError> {
+        // do not hold the lock while waiting for the transport
+        let mut listeners: Vec<(UUri, UUri, Arc<dyn UListener>)> = self
+            .request_listeners
+            .read()
+            .await
+            .iter()
+            .map(|((origin_filter, resource_id), listener)| {
+                (
+                    origin_filter.to_owned(),
+                    self.uri_provider.get_resource_uri(*resource_id),
+                    listener.clone(),
+                )
+            })
+            .collect();
+        if let Some(fallback_listener) = self.fallback_listener.lock().await.as_ref() {
+            listeners.push((
+                UUri::any(),
+                self.uri_provider.get_resource_uri(WILDCARD_RESOURCE_ID),
+                fallback_listener.clone(),
+            ));
+        }
+        if let Some(cancellation_listener) = self.cancellation_listener.lock().await.as_ref() {
+            listeners.push((
+                Self::cancellation_source_filter(),
+                self.uri_provider.get_source_uri(),
+                cancellation_listener.clone(),
+            ));
+        }
+
+        let mut failures = Vec::new();
+        for (origin_filter, sink_filter, listener) in listeners {
+            if let Err(e) = self
+                .transport
+                .register_listener(&origin_filter, Some(&sink_filter), listener)
+                .await
+            {
+                let endpoint = format!(
+                    "{} <- {}",
+                    sink_filter.to_uri(false),
+                    origin_filter.to_uri(false)
+                );
+                warn!("failed to re-register endpoint [{}]: {}", endpoint, e);
+                failures.push((endpoint, RegistrationError::TransportError(e)));
+            }
+        }
+
+        if failures.len() > 1 {
+            let details = failures
+                .iter()
+                .map(|(endpoint, e)| format!("[{}]: {}", endpoint, e))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(RegistrationError::Unknown(UStatus::unavailable(format!(
+                "failed to re-register {} endpoints: {}",
+                failures.len(),
+                details
+            ))));
+        }
+        failures.pop().map_or(Ok(()), |(_endpoint, e)| Err(e))
+    }
+
+    #[cfg(test)]
+    async fn contains_endpoint(&self, origin_filter: Option<&UUri>, resource_id: u16) -> bool {
+        let listener_key = Self::listener_key(origin_filter, resource_id);
+        self.request_listeners
+            .read()
+            .await
+            .contains_key(&listener_key)
+    }
+}
+
+#[async_trait]
+impl RpcServer for InMemoryRpcServer {
+    async fn register_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        request_handler: Arc<dyn RequestHandler>,
+    ) -> Result<(), RegistrationError> {
+        self.add_endpoint(origin_filter, resource_id, Handler::Unary(request_handler))
+            .await
+    }
+
+    async fn unregister_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        resource_id: u16,
+        _request_handler: Arc<dyn RequestHandler>,
+    ) -> Result<(), RegistrationError> {
+        self.remove_endpoint(origin_filter, resource_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::stream::{self, BoxStream};
+    use protobuf::Message;
+    use test_case::test_case;
+
+    use crate::communication::{stream_index, MethodId};
+    use crate::UPayloadFormat;
+
+    use super::*;
+
+    struct TestUriProvider;
+
+    impl LocalUriProvider for TestUriProvider {
+        fn get_authority(&self) -> String {
+            "my-vehicle".to_string()
+        }
+        fn get_resource_uri(&self, resource_id: u16) -> UUri {
+            UUri {
+                authority_name: self.get_authority(),
+                ue_id: 0x4210,
+                ue_version_major: 0x01,
+                resource_id: resource_id as u32,
+                ..Default::default()
+            }
+        }
+        fn get_source_uri(&self) -> UUri {
+            self.get_resource_uri(0x0000)
+        }
+    }
+
+    type Registration = (UUri, Option<UUri>, Arc<dyn UListener>);
+
+    #[derive(Default)]
+    struct TestTransport {
+        sent_messages: SyncMutex<Vec<UMessage>>,
+        listeners: SyncMutex<Vec<Registration>>,
+        registration_error: Option<UCode>,
+        failing_resource_id: Option<u16>,
+        registration_delay: Option<Duration>,
+        loopback: bool,
+        stuck_send: bool,
+        send_error: Option<UCode>,
+    }
+
+    impl TestTransport {
+        fn failing_registration(code: UCode) -> Self {
+            TestTransport {
+                registration_error: Some(code),
+                ..Default::default()
+            }
+        }
+
+        fn failing_registration_for(resource_id: u16) -> Self {
+            TestTransport {
+                failing_resource_id: Some(resource_id),
+                ..Default::default()
+            }
+        }
+
+        fn loopback() -> Self {
+            TestTransport {
+                loopback: true,
+                ..Default::default()
+            }
+        }
+
+        fn stuck_send() -> Self {
+            TestTransport {
+                stuck_send: true,
+                ..Default::default()
+            }
+        }
+
+        fn failing_send(code: UCode) -> Self {
+            TestTransport {
+                send_error: Some(code),
+                ..Default::default()
+            }
+        }
+
+        fn slow_registration(delay: Duration) -> Self {
+            TestTransport {
+                registration_delay: Some(delay),
+                ..Default::default()
+            }
+        }
+
+        fn listener_count(&self) -> usize {
+            self.listeners.lock().unwrap().len()
+        }
+
+        fn sent_messages(&self) -> Vec<UMessage> {
+            self.sent_messages.lock().unwrap().clone()
+        }
+
+        fn listener_for(&self, sink: &UUri) -> Option<Arc<dyn UListener>> {
+            self.listeners
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(_source, sink_filter, _listener)| sink_filter.as_ref() == Some(sink))
+                .map(|(_source, _sink, listener)| listener.clone())
+        }
+    }
+
+    #[async_trait]
+    impl UTransport for TestTransport {
+        async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+            if self.stuck_send {
+                return std::future::pending().await;
+            }
+            if let Some(code) = self.send_error {
+                return Err(UStatus::fail_with_code(code, "send failed"));
+            }
+            self.sent_messages.lock().unwrap().push(message.clone());
+            if self.loopback {
+                let listeners: Vec<Arc<dyn UListener>> = self
+                    .listeners
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|(source, sink, _listener)| {
+                        source.matches(&message.attributes.source)
+                            && sink.as_ref() == message.attributes.sink.as_ref()
+                    })
+                    .map(|(_source, _sink, listener)| listener.clone())
+                    .collect();
+                for listener in listeners {
+                    listener.on_receive(message.clone()).await;
+                }
+            }
+            Ok(())
+        }
+
+        async fn register_listener(
+            &self,
+            source_filter: &UUri,
+            sink_filter: Option<&UUri>,
+            listener: Arc<dyn UListener>,
+        ) -> Result<(), UStatus> {
+            if let Some(delay) = self.registration_delay {
+                tokio::time::sleep(delay).await;
+            }
+            if let Some(code) = self.registration_error {
+                return Err(UStatus::fail_with_code(code, "registration failed"));
+            }
+            if self.failing_resource_id.is_some_and(|resource_id| {
+                sink_filter.is_some_and(|uri| uri.resource_id == resource_id as u32)
+            }) {
+                return Err(UStatus::fail_with_code(
+                    UCode::UNAVAILABLE,
+                    "registration failed",
+                ));
+            }
+            self.listeners.lock().unwrap().push((
+                source_filter.to_owned(),
+                sink_filter.cloned(),
+                listener,
+            ));
+            Ok(())
+        }
+
+        async fn unregister_listener(
+            &self,
+            source_filter: &UUri,
+            sink_filter: Option<&UUri>,
+            listener: Arc<dyn UListener>,
+        ) -> Result<(), UStatus> {
+            let mut listeners = self.listeners.lock().unwrap();
+            let len = listeners.len();
+            listeners.retain(|(source, sink, l)| {
+                !(source == source_filter
+                    && sink.as_ref() == sink_filter
+                    && Arc::ptr_eq(l, &listener))
+            });
+            if listeners.len() < len {
+                Ok(())
+            } else {
+                Err(UStatus::fail_with_code(
+                    UCode::NOT_FOUND,
+                    "no such listener",
+                ))
+            }
+        }
+    }
+
+    struct CallerHandler;
+
+    #[async_trait]
+    impl RequestHandler for CallerHandler {
+        async fn invoke_method(
+            &self,
+            _resource_id: u16,
+            _payload: UPayload,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            Err(ServiceInvocationError::RpcError(UStatus::fail_with_code(
+                UCode::UNIMPLEMENTED,
+                "context is required",
+            )))
+        }
+
+        async fn invoke_method_with_context(
+            &self,
+            context: RequestContext,
+            _payload: UPayload,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            if context.token() != Some("my-token") {
+                return Err(ServiceInvocationError::RpcError(UStatus::fail_with_code(
+                    UCode::PERMISSION_DENIED,
+                    "invalid token",
+                )));
+            }
+            if context.permission_level().is_some_and(|level| level < 5) {
+                return Err(ServiceInvocationError::RpcError(UStatus::fail_with_code(
+                    UCode::PERMISSION_DENIED,
+                    "insufficient permission level",
+                )));
+            }
+            Ok(Some(UPayload::new(
+                context.source().to_uri(false).into(),
+                UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+            )))
+        }
+    }
+
+    struct FailingHandler;
+
+    #[async_trait]
+    impl RequestHandler for FailingHandler {
+        async fn invoke_method(
+            &self,
+            _resource_id: u16,
+            _payload: UPayload,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            Err(ServiceInvocationError::RpcError(UStatus::fail_with_code(
+                UCode::NOT_FOUND,
+                "no such vehicle",
+            )))
+        }
+    }
+
+    struct PanickingHandler;
+
+    #[async_trait]
+    impl RequestHandler for PanickingHandler {
+        async fn invoke_method(
+            &self,
+            _resource_id: u16,
+            _payload: UPayload,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            panic!("handler bug");
+        }
+    }
+
+    struct SlowHandler;
+
+    #[async_trait]
+    impl RequestHandler for SlowHandler {
+        async fn invoke_method(
+            &self,
+            _resource_id: u16,
+            _payload: UPayload,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(None)
+        }
+    }
+
+    #[derive(Default)]
+    struct NeverEndingHandler {
+        cancelled_requests: SyncMutex<Vec<UUID>>,
+    }
+
+    #[async_trait]
+    impl RequestHandler for NeverEndingHandler {
+        async fn invoke_method(
+            &self,
+            _resource_id: u16,
+            _payload: UPayload,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            std::future::pending().await
+        }
+
+        async fn on_cancelled(&self, context: &RequestContext) {
+            self.cancelled_requests
+                .lock()
+                .unwrap()
+                .push(context.request_id().to_owned());
+        }
+    }
+
+    struct CountingHandler {
+        items: Vec<Result<&'static str, UCode>>,
+    }
+
+    impl StreamingRequestHandler for CountingHandler {
+        fn invoke_method(
+            &self,
+            _resource_id: u16,
+            _payload: UPayload,
+        ) -> BoxStream<'static, Result<UPayload, ServiceInvocationError>> {
+            let items = self.items.clone().into_iter().map(|item| {
+                item.map(|text| UPayload::new(text.into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT))
+                    .map_err(|code| {
+                        ServiceInvocationError::from(UStatus::fail_with_code(code, "failed"))
+                    })
+            });
+            stream::iter(items).boxed()
+        }
+    }
+
+    fn new_server(transport: Arc<TestTransport>) -> InMemoryRpcServer {
+        InMemoryRpcServer::new(transport, Arc::new(TestUriProvider))
+    }
+
+    fn request_message(resource_id: u16) -> UMessage {
+        UMessageBuilder::request(
+            TestUriProvider.get_resource_uri(resource_id),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_export_and_restore_endpoints() {
+        let origin_filter = UUri::try_from("//my-cloud/BA4C/1/0").unwrap();
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x2000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        server
+            .register_endpoint(Some(&origin_filter), 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+
+        let specs = server.export_endpoints().await;
+        assert_eq!(
+            specs,
+            vec![
+                EndpointSpec {
+                    origin_filter: Some(origin_filter.clone()),
+                    resource_id: 0x1000
+                },
+                EndpointSpec {
+                    origin_filter: None,
+                    resource_id: 0x2000
+                },
+            ]
+        );
+
+        let restored_transport = Arc::new(TestTransport::default());
+        let restored_server = new_server(restored_transport.clone());
+        let mut resolved = Vec::new();
+        restored_server
+            .restore_endpoints(&specs, |spec| {
+                resolved.push(spec.resource_id);
+                Arc::new(EchoHandler)
+            })
+            .await
+            .unwrap();
+        assert_eq!(resolved, vec![0x1000, 0x2000]);
+        assert_eq!(restored_server.export_endpoints().await, specs);
+        assert_eq!(restored_transport.listener_count(), 2);
+
+        let listener = restored_transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+        listener.on_receive(request_message(0x1000)).await;
+        assert_eq!(
+            restored_transport.sent_messages()[0].payload,
+            Some("hello".into())
+        );
+    }
+
+    fn request_message_from(authority: &str, resource_id: u16) -> UMessage {
+        UMessageBuilder::request(
+            TestUriProvider.get_resource_uri(resource_id),
+            UUri::try_from(format!("//{}/BA4C/1/0", authority).as_str()).unwrap(),
+            5_000,
+        )
+        .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_exceeding_rate_limit_are_throttled() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.set_default_rate_limit(Some(RateLimit::new(2, Duration::from_secs(1))));
+        server.set_rate_limit(
+            "trusted-ecu",
+            Some(RateLimit::new(10, Duration::from_secs(1))),
+        );
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        for _ in 0..3 {
+            listener
+                .on_receive(request_message_from("noisy-ecu", 0x1000))
+                .await;
+            listener
+                .on_receive(request_message_from("trusted-ecu", 0x1000))
+                .await;
+        }
+        // half of the period is sufficient for one more request
+        tokio::time::advance(Duration::from_millis(500)).await;
+        listener
+            .on_receive(request_message_from("noisy-ecu", 0x1000))
+            .await;
+
+        let commstatus_of = |authority: &str| {
+            transport
+                .sent_messages()
+                .iter()
+                .filter(|msg| msg.attributes.sink.authority_name == authority)
+                .map(|msg| msg.attributes.commstatus)
+                .collect::<Vec<_>>()
+        };
+        let throttled = Some(protobuf::EnumOrUnknown::from(UCode::RESOURCE_EXHAUSTED));
+        assert_eq!(
+            commstatus_of("noisy-ecu"),
+            vec![None, None, throttled, None]
+        );
+        assert_eq!(commstatus_of("trusted-ecu"), vec![None, None, None]);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_endpoint_guard_unregisters_endpoint() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let guard = server
+            .register_endpoint_guarded(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        assert_eq!(guard.resource_id(), 0x1000);
+        assert!(server.contains_endpoint(None, 0x1000).await);
+        assert_eq!(transport.listener_count(), 1);
+
+        drop(guard);
+        // give the background task a chance to unregister the endpoint
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+        assert_eq!(transport.listener_count(), 0);
+        // the resource ID can be used again
+        assert!(server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_guard_can_be_unregistered_explicitly() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let guard = server
+            .register_endpoint_guarded(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+
+        assert!(guard.unregister().await.is_ok());
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_errors_are_counted() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        server
+            .register_fallback_handler(Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        assert_eq!(server.delivery_error_count(), 0);
+
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+        listener
+            .on_error(UStatus::fail_with_code(
+                UCode::DATA_LOSS,
+                "corrupted message",
+            ))
+            .await;
+        let fallback_listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(WILDCARD_RESOURCE_ID))
+            .unwrap();
+        fallback_listener
+            .on_error(UStatus::unavailable("connection lost"))
+            .await;
+
+        assert_eq!(server.delivery_error_count(), 2);
+        assert!(transport.sent_messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_try_new_succeeds_for_working_transport() {
+        let transport = Arc::new(TestTransport::default());
+        let result = InMemoryRpcServer::try_new(transport.clone(), Arc::new(TestUriProvider)).await;
+        assert!(result.is_ok());
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_try_new_fails_for_rejecting_transport() {
+        let transport = Arc::new(TestTransport::failing_registration(UCode::UNIMPLEMENTED));
+        let result = InMemoryRpcServer::try_new(transport.clone(), Arc::new(TestUriProvider)).await;
+        assert!(result.is_err_and(|e| matches!(
+            e,
+            RegistrationError::TransportError(status) if status.get_code() == UCode::UNIMPLEMENTED
+        )));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_register_endpoint_registers_listener() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let origin_filter = UUri::try_from("//my-cloud/BA4C/1/0").unwrap();
+
+        let result = server
+            .register_endpoint(Some(&origin_filter), 0x1000, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_ok());
+        assert!(server.contains_endpoint(Some(&origin_filter), 0x1000).await);
+        assert!(transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .is_some());
+
+        let result = server
+            .register_endpoint(Some(&origin_filter), 0x1000, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::DuplicateEndpoint)));
+    }
+
+    #[tokio::test]
+    async fn test_register_endpoint_fails_if_max_endpoints_reached() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.set_max_endpoints(Some(2));
+
+        for resource_id in [0x1000, 0x1001] {
+            assert!(server
+                .register_endpoint(None, resource_id, Arc::new(EchoHandler))
+                .await
+                .is_ok());
+        }
+        let result = server
+            .register_endpoint(None, 0x1002, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::CapacityExceeded)));
+        // the listener has not been left behind at the transport
+        assert!(transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1002))
+            .is_none());
+
+        assert!(server
+            .unregister_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .is_ok());
+        assert!(server
+            .register_endpoint(None, 0x1002, Arc::new(EchoHandler))
+            .await
+            .is_ok());
+    }
+
+    #[test_case(Some(UUri::try_from("//my-cloud/BA4C/1/1A").unwrap()), 0x1000; "for origin filter with method resource ID")]
+    #[test_case(None, 0x0000; "for response resource ID")]
+    #[test_case(None, 0x8000; "for event resource ID")]
+    #[test_case(Some(TestUriProvider.get_source_uri()), 0x1000; "for server's own address")]
+    #[tokio::test]
+    async fn test_register_endpoint_fails_for_invalid_filter(
+        origin_filter: Option<UUri>,
+        resource_id: u16,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+
+        let result = server
+            .register_endpoint(origin_filter.as_ref(), resource_id, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::InvalidFilter(_))));
+        assert!(
+            !server
+                .contains_endpoint(origin_filter.as_ref(), resource_id)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_register_endpoint_accepts_wildcard_authority_filters() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let filter_one = UUri::try_from("up://*/A100/1/0").unwrap();
+        let filter_two = UUri::try_from("up://*/A200/1/0").unwrap();
+
+        assert!(server
+            .register_endpoint(Some(&filter_one), 0x1000, Arc::new(EchoHandler))
+            .await
+            .is_ok());
+        // different entity type, i.e. the filters do not overlap
+        assert!(server
+            .register_endpoint(Some(&filter_two), 0x1000, Arc::new(EchoHandler))
+            .await
+            .is_ok());
+        assert!(server.contains_endpoint(Some(&filter_one), 0x1000).await);
+        assert!(server.contains_endpoint(Some(&filter_two), 0x1000).await);
+    }
+
+    #[test_case("up://*/A100/1/0", "up://my-cloud/A100/1/0"; "for specific authority")]
+    #[test_case("up://*/A100/1/0", "up://*/FFFF/1/0"; "for wildcard entity")]
+    #[test_case("up://my-cloud/A100/1/0", "up://*/A100/FF/0"; "for wildcard authority and version")]
+    #[tokio::test]
+    async fn test_register_endpoint_fails_for_overlapping_filter(
+        existing_filter: &str,
+        new_filter: &str,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let existing_filter = UUri::try_from(existing_filter).unwrap();
+        let new_filter = UUri::try_from(new_filter).unwrap();
+        server
+            .register_endpoint(Some(&existing_filter), 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+
+        let result = server
+            .register_endpoint(Some(&new_filter), 0x1000, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::InvalidFilter(_))));
+        let result = server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::InvalidFilter(_))));
+        // but the filter can be used for a different method
+        assert!(server
+            .register_endpoint(Some(&new_filter), 0x2000, Arc::new(EchoHandler))
+            .await
+            .is_ok());
+    }
+
+    #[test_case(UCode::UNIMPLEMENTED; "for unsupported push delivery")]
+    #[test_case(UCode::UNAVAILABLE; "for unavailable transport")]
+    #[test_case(UCode::RESOURCE_EXHAUSTED; "for exhausted transport resources")]
+    #[tokio::test]
+    async fn test_register_endpoint_fails_for_transport_error(code: UCode) {
+        let transport = Arc::new(TestTransport::failing_registration(code));
+        let server = new_server(transport.clone());
+
+        let result = server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_err_and(|e| matches!(
+            e,
+            RegistrationError::TransportError(status) if status.get_code() == code
+        )));
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+    }
+
+    #[tokio::test]
+    async fn test_reregister_all_registers_all_listeners_again() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        for resource_id in [0x1000, 0x2000, 0x3000] {
+            server
+                .register_endpoint(None, resource_id, Arc::new(EchoHandler))
+                .await
+                .unwrap();
+        }
+        server
+            .register_fallback_handler(Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        // simulate the transport losing its registrations
+        transport.listeners.lock().unwrap().clear();
+
+        assert!(server.reregister_all().await.is_ok());
+
+        assert_eq!(transport.listener_count(), 4);
+        assert!(transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x2000))
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_origin_filter_matches_mixed_case_source() {
+        let transport = Arc::new(TestTransport::loopback());
+        let server = new_server(transport.clone());
+        let origin_filter = UUri::try_from("//My-Cloud/BA4C/1/0").unwrap();
+        server
+            .register_endpoint(Some(&origin_filter), 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        server
+            .register_fallback_handler(Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let request = request_message_from("My-Cloud", 0x1000);
+
+        transport.send(request.clone()).await.unwrap();
+        // the fallback handler must not process requests that are processed by the endpoint
+        let fallback_listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(WILDCARD_RESOURCE_ID))
+            .unwrap();
+        fallback_listener.on_receive(request.clone()).await;
+
+        let responses: Vec<UMessage> = transport
+            .sent_messages()
+            .into_iter()
+            .filter(|msg| msg.attributes.reqid == request.attributes.id)
+            .collect();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].attributes.commstatus, None);
+        assert_eq!(responses[0].attributes.sink.authority_name, "My-Cloud");
+        assert!(
+            !server
+                .contains_endpoint(Some(&origin_filter.normalized()), 0x1000)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_register_endpoints_registers_all_endpoints() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let origin_filter = UUri::try_from("//my-cloud/BA4C/1/0").unwrap();
+
+        let result = server
+            .register_endpoints(vec![
+                (None, 0x1000, Arc::new(EchoHandler)),
+                (Some(origin_filter.clone()), 0x1100, Arc::new(EchoHandler)),
+                (None, 0x2000, Arc::new(FailingHandler)),
+            ])
+            .await;
+        assert!(result.is_ok());
+        assert!(server.contains_endpoint(None, 0x1000).await);
+        assert!(server.contains_endpoint(Some(&origin_filter), 0x1100).await);
+        assert!(server.contains_endpoint(None, 0x2000).await);
+        assert_eq!(transport.listener_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_register_endpoints_fails_for_invalid_spec() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+
+        let result = server
+            .register_endpoints(vec![
+                (None, 0x1000, Arc::new(EchoHandler)),
+                (None, 0x9000, Arc::new(EchoHandler)),
+            ])
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::InvalidFilter(_))));
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_register_endpoints_fails_for_duplicate_spec() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+
+        let result = server
+            .register_endpoints(vec![
+                (None, 0x1000, Arc::new(EchoHandler)),
+                (None, 0x1000, Arc::new(FailingHandler)),
+            ])
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::DuplicateEndpoint)));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_register_endpoints_rolls_back_on_transport_error() {
+        let transport = Arc::new(TestTransport::failing_registration_for(0x3000));
+        let server = new_server(transport.clone());
+
+        let result = server
+            .register_endpoints(vec![
+                (None, 0x1000, Arc::new(EchoHandler)),
+                (None, 0x2000, Arc::new(EchoHandler)),
+                (None, 0x3000, Arc::new(EchoHandler)),
+            ])
+            .await;
+        assert!(result.is_err_and(|e| matches!(
+            e,
+            RegistrationError::TransportError(status) if status.get_code() == UCode::UNAVAILABLE
+        )));
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+        assert!(!server.contains_endpoint(None, 0x2000).await);
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unregister_endpoint() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let handler = Arc::new(EchoHandler);
+
+        let result = server
+            .unregister_endpoint(None, 0x1000, handler.clone())
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::NoSuchListener)));
+
+        server
+            .register_endpoint(None, 0x1000, handler.clone())
+            .await
+            .unwrap();
+        let result = server.unregister_endpoint(None, 0x1000, handler).await;
+        assert!(result.is_ok());
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+        assert!(transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_register_and_unregister_method_endpoint() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let handler = Arc::new(EchoHandler);
+        let method_id = MethodId::new(0x1000).unwrap();
+
+        server
+            .register_method_endpoint(None, method_id, handler.clone())
+            .await
+            .unwrap();
+        assert!(server.contains_endpoint(None, 0x1000).await);
+
+        server
+            .unregister_method_endpoint(None, method_id, handler)
+            .await
+            .unwrap();
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+    }
+
+    #[tokio::test]
+    async fn test_request_is_answered_with_handler_output() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let request = request_message(0x1000);
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        let response = &sent_messages[0];
+        assert_eq!(response.attributes.reqid, request.attributes.id);
+        assert!(response.attributes.commstatus.is_none());
+        assert_eq!(response.payload, Some("hello".into()));
+        assert_eq!(
+            response.attributes.payload_format,
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT.into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_reply_request_is_processed_without_response() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.enable_method_stats();
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let request = UMessageBuilder::request_no_reply(
+            TestUriProvider.get_resource_uri(0x1000),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request).await;
+
+        assert!(transport.sent_messages().is_empty());
+        assert_eq!(server.method_stats(0x1000).unwrap().successes(), 1);
+    }
+
+    #[test_case(UPriority::UPRIORITY_CS4, Some(UCode::FAILED_PRECONDITION); "for priority below floor")]
+    #[test_case(UPriority::UPRIORITY_CS6, None; "for priority at floor")]
+    #[tokio::test]
+    async fn test_endpoint_enforces_min_priority(
+        priority: UPriority,
+        expected_status: Option<UCode>,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint_with_min_priority(
+                None,
+                0x1000,
+                Arc::new(EchoHandler),
+                UPriority::UPRIORITY_CS6,
+            )
+            .await
+            .unwrap();
+        let mut request = request_message(0x1000);
+        request.attributes.as_mut().unwrap().priority = priority.into();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            expected_status.map(protobuf::EnumOrUnknown::from)
+        );
+        if expected_status.is_none() {
+            // the handler has been invoked
+            assert_eq!(sent_messages[0].payload, Some("hello".into()));
+        }
+    }
+
+    #[test_case(Duration::from_secs(10), Some(UCode::FAILED_PRECONDITION); "for stale request")]
+    #[test_case(Duration::from_secs(1), None; "for fresh request")]
+    #[tokio::test]
+    async fn test_endpoint_enforces_max_age(age: Duration, expected_status: Option<UCode>) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint_with_max_age(
+                None,
+                0x1000,
+                Arc::new(EchoHandler),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+        let created_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            - age;
+        let mut request = request_message(0x1000);
+        let attributes = request.attributes.as_mut().unwrap();
+        attributes.id = Some(UUID::build_for_timestamp(created_at)).into();
+        // make sure that the request has not expired yet
+        attributes.ttl = Some(60_000);
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            expected_status.map(protobuf::EnumOrUnknown::from)
+        );
+    }
+
+    #[test_case(Some("my-token"), None, None; "for authorized caller")]
+    #[test_case(None, None, Some(UCode::PERMISSION_DENIED); "for unauthorized caller")]
+    #[test_case(Some("my-token"), Some(5), None; "for caller with sufficient permission level")]
+    #[test_case(Some("my-token"), Some(2), Some(UCode::PERMISSION_DENIED); "for caller with insufficient permission level")]
+    #[tokio::test]
+    async fn test_request_context_is_passed_to_handler(
+        token: Option<&str>,
+        permission_level: Option<u32>,
+        expected_status: Option<UCode>,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(CallerHandler))
+            .await
+            .unwrap();
+        let mut request = request_message(0x1000);
+        request.attributes.as_mut().unwrap().token = token.map(str::to_string);
+        request.attributes.as_mut().unwrap().permission_level = permission_level;
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        let response = &sent_messages[0];
+        assert_eq!(
+            response.attributes.commstatus,
+            expected_status.map(protobuf::EnumOrUnknown::from)
+        );
+        if expected_status.is_none() {
+            assert_eq!(response.payload, Some("//my-cloud/BA4C/1/0".into()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_internal_error_is_sent_if_response_cannot_be_created() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let listener = server.new_listener(Handler::Unary(Arc::new(EchoHandler)));
+        let request = request_message(0x1000);
+
+        let response_sent = listener
+            .send_response_or_error(
+                request.attributes.get_or_default(),
+                Err(UMessageError::PayloadError("cannot serialize".to_string())),
+            )
+            .await;
+
+        assert!(!response_sent);
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        let response = &sent_messages[0];
+        assert_eq!(response.attributes.reqid, request.attributes.id);
+        assert_eq!(response.attributes.sink, request.attributes.source);
+        assert_eq!(response.attributes.commstatus, Some(UCode::INTERNAL.into()));
+        let status = UStatus::parse_from_tokio_bytes(response.payload.as_ref().unwrap()).unwrap();
+        assert_eq!(status.get_code(), UCode::INTERNAL);
+    }
+
+    #[tokio::test]
+    async fn test_self_addressed_request_is_dropped() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let request = UMessageBuilder::request(
+            TestUriProvider.get_resource_uri(0x1000),
+            TestUriProvider.get_source_uri(),
+            5_000,
+        )
+        .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request).await;
+
+        assert!(transport.sent_messages().is_empty());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_compressed_request_payload_is_decompressed() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let payload = UPayload::new("hello".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .compressed(crate::communication::CompressionAlgorithm::Gzip)
+            .unwrap();
+        let format = payload.payload_format();
+        let request = UMessageBuilder::request(
+            TestUriProvider.get_resource_uri(0x1000),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .build_with_payload(payload.payload(), format)
+        .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].payload, Some("hello".into()));
+        assert_eq!(
+            sent_messages[0].attributes.payload_format,
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT.into()
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_request_payload_exceeding_decompression_limit_is_rejected() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        server.set_max_decompressed_request_size(64 * 1024);
+        // a few hundred bytes of compressed data that decompress to 1 MiB
+        let payload = UPayload::new(
+            vec![0_u8; 1024 * 1024].into(),
+            UPayloadFormat::UPAYLOAD_FORMAT_RAW,
+        )
+        .compressed(crate::communication::CompressionAlgorithm::Gzip)
+        .unwrap();
+        let format = payload.payload_format();
+        let request = UMessageBuilder::request(
+            TestUriProvider.get_resource_uri(0x1000),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .build_with_payload(payload.payload(), format)
+        .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::RESOURCE_EXHAUSTED.into())
+        );
+    }
+
+    #[test_case(false, 1; "for regular endpoint")]
+    #[test_case(true, 2; "for idempotent endpoint")]
+    #[tokio::test]
+    async fn test_redelivered_request_processing(idempotent: bool, expected_responses: usize) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        if idempotent {
+            server
+                .register_idempotent_endpoint(None, 0x1000, Arc::new(EchoHandler))
+                .await
+                .unwrap();
+        } else {
+            server
+                .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+                .await
+                .unwrap();
+        }
+        let request = UMessageBuilder::request(
+            TestUriProvider.get_resource_uri(0x1000),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+        listener.on_receive(request).await;
+
+        assert_eq!(transport.sent_messages().len(), expected_responses);
+    }
+
+    fn self_addressed_request() -> UMessage {
+        UMessageBuilder::request(
+            TestUriProvider.get_resource_uri(0x1000),
+            TestUriProvider.get_source_uri(),
+            5_000,
+        )
+        .build()
+        .unwrap()
+    }
+
+    fn redelivered_request() -> Vec<UMessage> {
+        let request = request_message(0x1000);
+        vec![request.clone(), request]
+    }
+
+    fn unanswerable_request() -> UMessage {
+        let mut request = request_message(0x1000);
+        request.attributes.as_mut().unwrap().source.clear();
+        request
+    }
+
+    #[test_case(vec![UMessage::new()], DropReason::NoAttributes; "for message without attributes")]
+    #[test_case(vec![unanswerable_request()], DropReason::InvalidAndUnanswerable; "for request without reply-to address")]
+    #[test_case(vec![self_addressed_request()], DropReason::SelfAddressed; "for self-addressed request")]
+    #[test_case(redelivered_request(), DropReason::Duplicate; "for duplicate request")]
+    #[tokio::test]
+    async fn test_drop_callback_is_invoked_for_dropped_message(
+        messages: Vec<UMessage>,
+        expected_reason: DropReason,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        // set the callback after registration, to verify that it applies to existing endpoints
+        let dropped = Arc::new(SyncMutex::new(Vec::new()));
+        let recorder = dropped.clone();
+        server.set_drop_callback(Some(Arc::new(move |reason, msg: &UMessage| {
+            recorder.lock().unwrap().push((reason, msg.to_owned()));
+        })));
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        for msg in messages.iter() {
+            listener.on_receive(msg.to_owned()).await;
+        }
+
+        let dropped = dropped.lock().unwrap();
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].0, expected_reason);
+        assert_eq!(&dropped[0].1, messages.last().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_request_cancellation_aborts_handler() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let request_handler = Arc::new(NeverEndingHandler::default());
+        server
+            .register_endpoint(None, 0x1000, request_handler.clone())
+            .await
+            .unwrap();
+        server.enable_request_cancellation().await.unwrap();
+        let request = request_message(0x1000);
+        let cancellation = UMessageBuilder::request_cancellation(&request.attributes)
+            .build()
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+        let cancellation_listener = transport
+            .listener_for(&TestUriProvider.get_source_uri())
+            .unwrap();
+
+        tokio::join!(listener.on_receive(request.clone()), async {
+            tokio::task::yield_now().await;
+            cancellation_listener.on_receive(cancellation).await;
+        });
+
+        assert_eq!(
+            *request_handler.cancelled_requests.lock().unwrap(),
+            vec![request.attributes.id.get_or_default().to_owned()]
+        );
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, request.attributes.id);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::CANCELLED.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_cancellation_from_other_client_is_ignored() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let request_handler = Arc::new(NeverEndingHandler::default());
+        server
+            .register_endpoint(None, 0x1000, request_handler.clone())
+            .await
+            .unwrap();
+        server.enable_request_cancellation().await.unwrap();
+        let mut request = request_message(0x1000);
+        request.attributes.as_mut().unwrap().ttl = Some(100);
+        let mut cancellation = UMessageBuilder::request_cancellation(&request.attributes)
+            .build()
+            .unwrap();
+        cancellation.attributes.as_mut().unwrap().source =
+            Some(UUri::try_from("//my-cloud/BA4D/1/FFFE").unwrap()).into();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+        let cancellation_listener = transport
+            .listener_for(&TestUriProvider.get_source_uri())
+            .unwrap();
+
+        tokio::join!(listener.on_receive(request), async {
+            tokio::task::yield_now().await;
+            cancellation_listener.on_receive(cancellation).await;
+        });
+
+        assert!(request_handler
+            .cancelled_requests
+            .lock()
+            .unwrap()
+            .is_empty());
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::DEADLINE_EXCEEDED.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_referring_to_other_message_is_rejected() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let request_handler = Arc::new(NeverEndingHandler::default());
+        server
+            .register_endpoint(None, 0x1000, request_handler.clone())
+            .await
+            .unwrap();
+        let mut request = request_message(0x1000);
+        request.attributes.as_mut().unwrap().reqid = Some(UUID::build()).into();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, request.attributes.id);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::INVALID_ARGUMENT.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enable_request_cancellation_fails_if_enabled_already() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.enable_request_cancellation().await.unwrap();
+
+        assert!(matches!(
+            server.enable_request_cancellation().await,
+            Err(RegistrationError::DuplicateEndpoint)
+        ));
+        assert_eq!(transport.listener_count(), 1);
+    }
+
+    #[test_case(None, Duration::from_millis(1_000); "for request TTL")]
+    #[test_case(Some(Duration::from_millis(100)), Duration::from_millis(100); "for max send time")]
+    #[tokio::test(start_paused = true)]
+    async fn test_response_send_is_abandoned_after_budget(
+        max_send_time: Option<Duration>,
+        expected_budget: Duration,
+    ) {
+        let transport = Arc::new(TestTransport::stuck_send());
+        let server = new_server(transport.clone());
+        server.set_max_response_send_time(max_send_time);
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let mut request = request_message(0x1000);
+        request.attributes.as_mut().unwrap().ttl = Some(1_000);
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        let start = Instant::now();
+        tokio::time::timeout(Duration::from_secs(10), listener.on_receive(request))
+            .await
+            .expect("processing of request should have completed");
+        // the server should have waited for the transport until the budget was used up
+        assert!(start.elapsed() > expected_budget / 2);
+        assert!(start.elapsed() <= expected_budget);
+    }
+
+    #[test_case(true; "for valid request")]
+    #[test_case(false; "for invalid request")]
+    #[tokio::test]
+    async fn test_send_error_callback_is_invoked_for_failed_send(valid_request: bool) {
+        let transport = Arc::new(TestTransport::failing_send(UCode::UNAVAILABLE));
+        let server = new_server(transport.clone());
+        let send_errors = Arc::new(SyncMutex::new(Vec::new()));
+        server.set_send_error_callback(Some(Arc::new({
+            let send_errors = send_errors.clone();
+            move |status: &UStatus| send_errors.lock().unwrap().push(status.get_code())
+        })));
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let mut request = request_message(0x1000);
+        if !valid_request {
+            request.attributes.as_mut().unwrap().ttl = Some(0);
+        }
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request).await;
+
+        assert_eq!(*send_errors.lock().unwrap(), vec![UCode::UNAVAILABLE]);
+    }
+
+    #[test_case(true; "for valid request")]
+    #[test_case(false; "for invalid request")]
+    #[tokio::test]
+    async fn test_response_interceptor_is_applied(valid_request: bool) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.set_response_interceptor(Some(Arc::new(|mut response: UMessage| {
+            response.attributes.mut_or_insert_default().token = Some("tenant-a".to_string());
+            response
+        })));
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let mut request = request_message(0x1000);
+        if !valid_request {
+            request.attributes.as_mut().unwrap().ttl = Some(0);
+        }
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, request.attributes.id);
+        assert_eq!(
+            sent_messages[0].attributes.token,
+            Some("tenant-a".to_string())
+        );
+    }
+
+    #[test_case("hello", None; "for request routed to echo handler")]
+    #[test_case("bye", Some(UCode::NOT_FOUND); "for request routed to failing handler")]
+    #[tokio::test]
+    async fn test_router_selects_handler_by_payload(
+        payload: &'static str,
+        expected_commstatus: Option<UCode>,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let echo_handler: Arc<dyn RequestHandler> = Arc::new(EchoHandler);
+        let failing_handler: Arc<dyn RequestHandler> = Arc::new(FailingHandler);
+        server
+            .register_endpoint_with_router(
+                0x1000,
+                Arc::new(move |request: &UMessage| {
+                    if request.payload == Some("hello".into()) {
+                        echo_handler.clone()
+                    } else {
+                        failing_handler.clone()
+                    }
+                }),
+            )
+            .await
+            .unwrap();
+        let mut request = request_message(0x1000);
+        request.payload = Some(payload.into());
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, request.attributes.id);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            expected_commstatus.map(protobuf::EnumOrUnknown::from)
+        );
+        assert!(server.unregister_endpoint_with_router(0x1000).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_weighted_endpoint_distributes_requests_by_weight() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint_weighted(
+                0x1000,
+                vec![
+                    (Arc::new(EchoHandler), 9),
+                    (Arc::new(FailingHandler), 1),
+                    (Arc::new(PanickingHandler), 0),
+                ],
+            )
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        for _ in 0..1_000 {
+            listener.on_receive(request_message(0x1000)).await;
+        }
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1_000);
+        let echoed = sent_messages
+            .iter()
+            .filter(|msg| msg.attributes.commstatus.is_none())
+            .count();
+        let failed = sent_messages
+            .iter()
+            .filter(|msg| msg.attributes.commstatus == Some(UCode::NOT_FOUND.into()))
+            .count();
+        // the expected share of echoed requests is 900, the bounds are more than 5 standard deviations away
+        assert!((850..=950).contains(&echoed), "echoed {} requests", echoed);
+        assert_eq!(echoed + failed, 1_000);
+        assert!(server.unregister_endpoint_with_router(0x1000).await.is_ok());
+    }
+
+    #[test_case(vec![]; "for no handlers")]
+    #[test_case(vec![(Arc::new(EchoHandler) as Arc<dyn RequestHandler>, 0)]; "for zero weights only")]
+    #[tokio::test]
+    async fn test_weighted_endpoint_rejects_invalid_weights(
+        handlers: Vec<(Arc<dyn RequestHandler>, u32)>,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+
+        let result = server.register_endpoint_weighted(0x1000, handlers).await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::InvalidArgument(_))));
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_handler_results_in_internal_error() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.enable_method_stats();
+        server
+            .register_endpoint(None, 0x1000, Arc::new(PanickingHandler))
+            .await
+            .unwrap();
+        let request = request_message(0x1000);
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, request.attributes.id);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::INTERNAL.into())
+        );
+        assert_eq!(
+            server
+                .method_stats(0x1000)
+                .unwrap()
+                .error_count(UCode::INTERNAL),
+            1
+        );
+    }
+
+    #[test_case(None, None; "for unlimited payload size")]
+    #[test_case(Some(5), None; "for payload within limit")]
+    #[test_case(Some(4), Some(UCode::RESOURCE_EXHAUSTED); "for oversized payload")]
+    #[tokio::test]
+    async fn test_oversized_response_payload_is_replaced_with_error(
+        max_size: Option<usize>,
+        expected_status: Option<UCode>,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.set_max_response_payload_size(max_size);
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        // the handler echoes the 5 bytes of the request payload
+        listener.on_receive(request_message(0x1000)).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            expected_status.map(protobuf::EnumOrUnknown::from)
+        );
+        if expected_status.is_none() {
+            assert_eq!(sent_messages[0].payload, Some("hello".into()));
+        }
+    }
+
+    #[test_case(ValidationMode::Strict, Some(UCode::INVALID_ARGUMENT); "for strict mode")]
+    #[test_case(ValidationMode::Lenient, None; "for lenient mode")]
+    #[tokio::test]
+    async fn test_validation_mode_for_request_without_ttl_and_priority(
+        mode: ValidationMode,
+        expected_status: Option<UCode>,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.set_validation_mode(mode);
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let mut request = request_message(0x1000);
+        let attributes = request.attributes.as_mut().unwrap();
+        attributes.ttl = None;
+        attributes.priority = UPriority::UPRIORITY_UNSPECIFIED.into();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, request.attributes.id);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            expected_status.map(protobuf::EnumOrUnknown::from)
+        );
+        if expected_status.is_none() {
+            assert_eq!(sent_messages[0].payload, Some("hello".into()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lenient_mode_still_rejects_request_with_invalid_ttl() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.set_validation_mode(ValidationMode::Lenient);
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let mut request = request_message(0x1000);
+        request.attributes.as_mut().unwrap().ttl = Some(0);
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::INVALID_ARGUMENT.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paused_server_rejects_requests() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        server.pause();
+        assert!(server.is_paused());
+        listener.on_receive(request_message(0x1000)).await;
+        server.resume();
+        listener.on_receive(request_message(0x1000)).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 2);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::UNAVAILABLE.into())
+        );
+        let status =
+            UStatus::parse_from_tokio_bytes(sent_messages[0].payload.as_ref().unwrap()).unwrap();
+        assert_eq!(status.get_message(), "service paused");
+        // the handler has only been invoked after the server had been resumed
+        assert!(sent_messages[1].attributes.commstatus.is_none());
+        assert_eq!(sent_messages[1].payload, Some("hello".into()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_waits_for_active_requests() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(SlowHandler))
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+        // the handler is stopped once the request expires after 5s
+        let processing = tokio::spawn(async move {
+            listener.on_receive(request_message(0x1000)).await;
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let start = Instant::now();
+
+        assert_eq!(server.drain(Duration::from_secs(1)).await, Err(1));
+        assert!(server.is_paused());
+        assert!(transport.sent_messages().is_empty());
+
+        assert_eq!(server.drain(Duration::from_secs(10)).await, Ok(()));
+        // the request's deadline is derived from the system clock, which keeps running while tokio's clock is paused
+        assert!(start.elapsed() >= Duration::from_millis(4_800));
+        assert!(processing.is_finished());
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::DEADLINE_EXCEEDED.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drain_succeeds_without_active_requests() {
+        let server = new_server(Arc::new(TestTransport::default()));
+        assert_eq!(server.drain(Duration::ZERO).await, Ok(()));
+        assert!(server.is_paused());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_method_stats_count_outcomes() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.enable_method_stats();
+        server
+            .register_endpoints(vec![
+                (None, 0x1000, Arc::new(EchoHandler)),
+                (None, 0x2000, Arc::new(FailingHandler)),
+                (None, 0x3000, Arc::new(SlowHandler)),
+            ])
+            .await
+            .unwrap();
+        let mut expiring_request = request_message(0x3000);
+        expiring_request.attributes.as_mut().unwrap().ttl = Some(100);
+
+        for request in [
+            request_message(0x1000),
+            request_message(0x1000),
+            request_message(0x2000),
+            expiring_request,
+        ] {
+            let listener = transport
+                .listener_for(request.attributes.sink.get_or_default())
+                .unwrap();
+            listener.on_receive(request).await;
+        }
+
+        let echo_stats = server.method_stats(0x1000).unwrap();
+        assert_eq!(echo_stats.successes(), 2);
+        assert_eq!(echo_stats.total(), 2);
+        let failing_stats = server.method_stats(0x2000).unwrap();
+        assert_eq!(failing_stats.error_count(UCode::NOT_FOUND), 1);
+        assert_eq!(failing_stats.total(), 1);
+        let slow_stats = server.method_stats(0x3000).unwrap();
+        assert_eq!(slow_stats.timeouts(), 1);
+        assert!(slow_stats.errors().is_empty());
+        assert_eq!(server.all_stats().len(), 3);
+        assert!(server.method_stats(0x4000).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_method_stats_are_disabled_by_default() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request_message(0x1000)).await;
+
+        assert_eq!(transport.sent_messages().len(), 1);
+        assert!(server.method_stats(0x1000).is_none());
+        assert!(server.all_stats().is_empty());
+    }
+
+    struct MislabelingHandler;
+
+    #[async_trait]
+    impl RequestHandler for MislabelingHandler {
+        async fn invoke_method(
+            &self,
+            _resource_id: u16,
+            _payload: UPayload,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            let protobuf = UStatus::fail_with_code(UCode::NOT_FOUND, "no such vehicle")
+                .write_to_bytes()
+                .unwrap();
+            Ok(Some(UPayload::new(
+                protobuf.into(),
+                UPayloadFormat::UPAYLOAD_FORMAT_JSON,
+            )))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_response_with_mismatching_payload_format_is_replaced_by_error() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(MislabelingHandler))
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request_message(0x1000)).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::INTERNAL.into())
+        );
+        assert_eq!(
+            sent_messages[0].attributes.payload_format,
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF.into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_is_answered_with_handler_error() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(FailingHandler))
+            .await
+            .unwrap();
+        let request = request_message(0x1000);
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        let response = &sent_messages[0];
+        assert_eq!(response.attributes.reqid, request.attributes.id);
+        assert_eq!(
+            response.attributes.commstatus,
+            Some(UCode::NOT_FOUND.into())
+        );
+        let status = UStatus::parse_from_tokio_bytes(response.payload.as_ref().unwrap()).unwrap();
+        assert_eq!(status.get_code(), UCode::NOT_FOUND);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_request_times_out() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(SlowHandler))
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request_message(0x1000)).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::DEADLINE_EXCEEDED.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fallback_handler_processes_unmatched_requests_only() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        server
+            .register_fallback_handler(Arc::new(FailingHandler))
+            .await
+            .unwrap();
+        assert!(matches!(
+            server
+                .register_fallback_handler(Arc::new(FailingHandler))
+                .await,
+            Err(RegistrationError::DuplicateEndpoint)
+        ));
+        let fallback_listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(WILDCARD_RESOURCE_ID))
+            .unwrap();
+
+        // a request for a registered endpoint is ignored by the fallback handler
+        fallback_listener.on_receive(request_message(0x1000)).await;
+        assert!(transport.sent_messages().is_empty());
+
+        // a request for an unregistered method is processed by the fallback handler
+        let request = request_message(0x2000);
+        fallback_listener.on_receive(request.clone()).await;
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, request.attributes.id);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::NOT_FOUND.into())
+        );
+
+        server.unregister_fallback_handler().await.unwrap();
+        assert!(transport
+            .listener_for(&TestUriProvider.get_resource_uri(WILDCARD_RESOURCE_ID))
+            .is_none());
+        assert!(matches!(
+            server.unregister_fallback_handler().await,
+            Err(RegistrationError::NoSuchListener)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_request_times_out_at_absolute_deadline() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(SlowHandler))
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+        // the request has been created 4.9 seconds ago and thus expires in 100ms
+        let creation_time = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            - Duration::from_millis(4_900);
+        let mut request = request_message(0x1000);
+        request.attributes.as_mut().unwrap().id =
+            Some(UUID::build_for_timestamp(creation_time)).into();
+
+        let start = Instant::now();
+        listener.on_receive(request).await;
+
+        assert!(start.elapsed() <= Duration::from_millis(100));
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::DEADLINE_EXCEEDED.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_is_rejected() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+        let request_id = UUID::build();
+        let request = UMessage {
+            attributes: Some(UAttributes {
+                // TTL is missing
+                ttl: None,
+                ..UAttributes::request(
+                    request_id.clone(),
+                    TestUriProvider.get_resource_uri(0x1000),
+                    UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+                    Some(UPriority::UPRIORITY_CS4),
+                    None,
+                    None,
+                )
+            })
+            .into(),
+            ..Default::default()
+        };
+
+        listener.on_receive(request).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, Some(request_id).into());
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(UCode::INVALID_ARGUMENT.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_echo_endpoint_returns_request_payload() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server.register_echo_endpoint(0x1000).await.unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+        let request = UMessageBuilder::request(
+            TestUriProvider.get_resource_uri(0x1000),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .build_with_protobuf_payload(&UStatus::fail("echo me"))
+        .unwrap();
+        let request_id = request.attributes.id.clone();
+
+        listener.on_receive(request).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, request_id);
+        assert!(sent_messages[0].attributes.commstatus.is_none());
+        assert_eq!(
+            sent_messages[0].attributes.payload_format,
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF.into()
+        );
+        let echoed =
+            UStatus::parse_from_tokio_bytes(sent_messages[0].payload.as_ref().unwrap()).unwrap();
+        assert_eq!(echoed, UStatus::fail("echo me"));
+
+        server
+            .unregister_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[test_case(None, UCode::OK; "for default check")]
+    #[test_case(Some(UCode::UNAVAILABLE), UCode::UNAVAILABLE; "for failing check")]
+    #[tokio::test]
+    async fn test_health_endpoint_reports_check_outcome(
+        check_result: Option<UCode>,
+        expected_code: UCode,
+    ) {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        let check = check_result.map(|code| {
+            let check: Arc<dyn Fn() -> UCode + Send + Sync> = Arc::new(move || code);
+            check
+        });
+        server
+            .register_health_endpoint(0x0001, check)
+            .await
+            .unwrap();
+        let request = request_message(0x0001);
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x0001))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].attributes.reqid, request.attributes.id);
+        assert_eq!(
+            sent_messages[0].attributes.commstatus,
+            Some(expected_code.into())
+        );
+        assert!(sent_messages[0].payload.is_none());
+
+        assert!(server.unregister_health_endpoint(0x0001).await.is_ok());
+        assert!(!server.contains_endpoint(None, 0x0001).await);
+    }
+
+    #[tokio::test]
+    async fn test_try_register_endpoint_succeeds_if_not_contended() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+
+        let result = server
+            .try_register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_ok());
+        assert!(server.contains_endpoint(None, 0x1000).await);
+        assert_eq!(transport.listener_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_try_register_endpoint_fails_if_contended() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+
+        let guard = server.request_listeners.write().await;
+        let result = server
+            .try_register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::WouldBlock)));
+        drop(guard);
+
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+        assert_eq!(transport.listener_count(), 0);
+        assert!(server
+            .try_register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_try_register_endpoint_fails_if_contended_after_transport_registration() {
+        let transport = Arc::new(TestTransport::slow_registration(Duration::from_millis(100)));
+        let server = new_server(transport.clone());
+
+        let (result, _) = tokio::join!(
+            server.try_register_endpoint(None, 0x1000, Arc::new(EchoHandler)),
+            async {
+                // lock the registry while the listener is being registered with the transport
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                let _guard = server.request_listeners.write().await;
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        );
+
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::WouldBlock)));
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_lookups_do_not_block_each_other() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+
+        let guard = server.request_listeners.read().await;
+        let lookup = tokio::time::timeout(
+            Duration::from_secs(1),
+            server.contains_endpoint(None, 0x1000),
+        )
+        .await;
+        assert!(lookup.is_ok_and(|found| found));
+        // but registrations need to wait for the lookup to complete
+        let result = server
+            .try_register_endpoint(None, 0x1001, Arc::new(EchoHandler))
+            .await;
+        assert!(result.is_err_and(|e| matches!(e, RegistrationError::WouldBlock)));
+        drop(guard);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_registrations_on_slow_transport_proceed_in_parallel() {
+        let transport = Arc::new(TestTransport::slow_registration(Duration::from_millis(100)));
+        let server = new_server(transport.clone());
+
+        let start = Instant::now();
+        let (first, second) = tokio::join!(
+            server.register_endpoint(None, 0x1000, Arc::new(EchoHandler)),
+            server.register_endpoint(None, 0x1001, Arc::new(EchoHandler)),
+        );
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+        assert!(start.elapsed() < Duration::from_millis(200));
+        assert!(server.contains_endpoint(None, 0x1000).await);
+        assert!(server.contains_endpoint(None, 0x1001).await);
+        assert_eq!(transport.listener_count(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrent_registrations_of_same_endpoint_fail_for_all_but_one() {
+        let transport = Arc::new(TestTransport::slow_registration(Duration::from_millis(100)));
+        let server = new_server(transport.clone());
+
+        let (first, second) = tokio::join!(
+            server.register_endpoint(None, 0x1000, Arc::new(EchoHandler)),
+            server.register_endpoint(None, 0x1000, Arc::new(EchoHandler)),
+        );
+        assert!(first.is_ok() != second.is_ok());
+        assert!(first
+            .and(second)
+            .is_err_and(|e| matches!(e, RegistrationError::DuplicateEndpoint)));
+        assert!(server.contains_endpoint(None, 0x1000).await);
+        assert_eq!(transport.listener_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_register_endpoint_and_confirm_succeeds_for_routed_probe() {
+        let transport = Arc::new(TestTransport::loopback());
+        let server = new_server(transport.clone());
+        let origin_filter = UUri::try_from("//my-cloud/BA4C/1/0").unwrap();
+
+        let result = server
+            .register_endpoint_and_confirm(
+                Some(&origin_filter),
+                0x1000,
+                Arc::new(EchoHandler),
+                Duration::from_secs(1),
+            )
+            .await;
+        assert!(result.is_ok());
+        assert!(server.contains_endpoint(Some(&origin_filter), 0x1000).await);
+        // the probe has not been passed to the handler, so no response has been sent
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.source,
+            Some(TestUriProvider.get_source_uri()).into()
+        );
+        // the listener for bypassing the origin filter has been removed again
+        assert_eq!(transport.listener_count(), 1);
+        assert!(transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_register_endpoint_and_confirm_succeeds_for_wildcard_filter() {
+        let transport = Arc::new(TestTransport::loopback());
+        let server = new_server(transport.clone());
+        let origin_filter = UUri::try_from("//*/FFFF/FF/0").unwrap();
+
+        let result = server
+            .register_endpoint_and_confirm(
+                Some(&origin_filter),
+                0x1000,
+                Arc::new(EchoHandler),
+                Duration::from_secs(1),
+            )
+            .await;
+        assert!(result.is_ok());
+        assert!(server.contains_endpoint(Some(&origin_filter), 0x1000).await);
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(
+            sent_messages[0].attributes.source,
+            Some(TestUriProvider.get_source_uri()).into()
+        );
+        assert_eq!(transport.listener_count(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_register_endpoint_and_confirm_fails_for_lost_probe() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+
+        let result = server
+            .register_endpoint_and_confirm(
+                None,
+                0x1000,
+                Arc::new(EchoHandler),
+                Duration::from_secs(1),
+            )
+            .await;
+        assert!(result.is_err_and(|e| matches!(
+            e,
+            RegistrationError::Unknown(status) if status.get_code() == UCode::DEADLINE_EXCEEDED
+        )));
+        assert!(!server.contains_endpoint(None, 0x1000).await);
+        assert_eq!(transport.listener_count(), 0);
+    }
+
+    // a minimal subscriber which records the field values of all created spans
+    #[derive(Clone, Default)]
+    struct SpanRecorder {
+        fields: Arc<SyncMutex<HashMap<String, String>>>,
+    }
+
+    impl tracing::field::Visit for SpanRecorder {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.fields
+                .lock()
+                .unwrap()
+                .insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.fields
+                .lock()
+                .unwrap()
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            span.record(&mut self.clone());
+            tracing::span::Id::from_u64(1)
+        }
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+        fn event(&self, _event: &tracing::Event<'_>) {}
+        fn enter(&self, _span: &tracing::span::Id) {}
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[tokio::test]
+    async fn test_trace_context_is_propagated_to_span_and_response() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoint(None, 0x1000, Arc::new(EchoHandler))
+            .await
+            .unwrap();
+        let request = UMessageBuilder::request(
+            TestUriProvider.get_resource_uri(0x1000),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .with_traceparent(traceparent)
+        .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let fields = recorder.fields.lock().unwrap().clone();
+        assert_eq!(
+            fields.get("traceparent").map(String::as_str),
+            Some(traceparent)
+        );
+        assert_eq!(
+            fields.get("request_id"),
+            Some(&request.attributes.id.to_hyphenated_string())
+        );
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0].traceparent(), Some(traceparent));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_request_is_answered_with_multiple_responses() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_streaming_endpoint(
+                None,
+                0x1000,
+                Arc::new(CountingHandler {
+                    items: vec![Ok("one"), Ok("two"), Ok("three")],
+                }),
+            )
+            .await
+            .unwrap();
+        let request = request_message(0x1000);
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request.clone()).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 4);
+        assert!(sent_messages
+            .iter()
+            .all(|msg| msg.attributes.reqid == request.attributes.id));
+        let indices: Vec<Option<u32>> = sent_messages
+            .iter()
+            .map(|msg| stream_index(&msg.attributes))
+            .collect();
+        assert_eq!(indices, vec![Some(0), Some(1), Some(2), Some(3)]);
+        // the index is carried in the message ID, which remains a valid uProtocol UUID
+        assert!(sent_messages
+            .iter()
+            .all(|msg| msg.attributes.token.is_none()
+                && msg
+                    .attributes
+                    .id
+                    .as_ref()
+                    .is_some_and(UUID::is_uprotocol_uuid)));
+        let items: Vec<Option<Bytes>> = sent_messages[..3]
+            .iter()
+            .inspect(|msg| assert!(msg.attributes.commstatus.is_none()))
+            .map(|msg| msg.payload.clone())
+            .collect();
+        assert_eq!(
+            items,
+            vec![Some("one".into()), Some("two".into()), Some("three".into())]
+        );
+        assert_eq!(
+            sent_messages[3].attributes.commstatus,
+            Some(UCode::OK.into())
+        );
+        assert!(sent_messages[3].payload.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_streaming_request_ends_with_error() {
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_streaming_endpoint(
+                None,
+                0x1000,
+                Arc::new(CountingHandler {
+                    items: vec![Ok("one"), Err(UCode::UNAVAILABLE), Ok("three")],
+                }),
+            )
+            .await
+            .unwrap();
+        let listener = transport
+            .listener_for(&TestUriProvider.get_resource_uri(0x1000))
+            .unwrap();
+
+        listener.on_receive(request_message(0x1000)).await;
+
+        let sent_messages = transport.sent_messages();
+        assert_eq!(sent_messages.len(), 2);
+        assert!(sent_messages[0].attributes.commstatus.is_none());
+        assert_eq!(stream_index(&sent_messages[0].attributes), Some(0));
+        assert_eq!(
+            sent_messages[1].attributes.commstatus,
+            Some(UCode::UNAVAILABLE.into())
+        );
+        assert_eq!(stream_index(&sent_messages[1].attributes), Some(1));
+    }
+
+    /// A subscriber that keeps the trace level events emitted while processing requests.
+    #[cfg(feature = "trace-messages")]
+    #[derive(Clone, Default)]
+    struct CapturingSubscriber {
+        events: Arc<SyncMutex<Vec<CapturedEvent>>>,
+        next_span_id: Arc<AtomicU64>,
+    }
+
+    #[cfg(feature = "trace-messages")]
+    #[derive(Debug, Default)]
+    struct CapturedEvent {
+        message: String,
+        request_id: Option<String>,
+        has_redacted_message: bool,
+    }
+
+    #[cfg(feature = "trace-messages")]
+    impl tracing::field::Visit for CapturedEvent {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            if field.name() == "request_id" {
+                self.request_id = Some(value.to_string());
+            } else {
+                self.record_debug(field, &value);
+            }
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            match field.name() {
+                "message" => self.message = format!("{:?}", value),
+                "redacted_message" => self.has_redacted_message = true,
+                _ => {}
+            }
+        }
+    }
+
+    #[cfg(feature = "trace-messages")]
+    impl tracing::Subscriber for CapturingSubscriber {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(self.next_span_id.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+        fn event(&self, event: &tracing::Event<'_>) {
+            if *event.metadata().level() == tracing::Level::TRACE {
+                let mut captured = CapturedEvent::default();
+                event.record(&mut captured);
+                self.events.lock().unwrap().push(captured);
+            }
+        }
+        fn enter(&self, _span: &tracing::span::Id) {}
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "trace-messages")]
+    #[tokio::test(start_paused = true)]
+    async fn test_request_lifecycle_is_traced() {
+        let subscriber = CapturingSubscriber::default();
+        let _guard = tracing::subscriber::set_default(subscriber.clone());
+        let transport = Arc::new(TestTransport::default());
+        let server = new_server(transport.clone());
+        server
+            .register_endpoints(vec![
+                (None, 0x1000, Arc::new(EchoHandler)),
+                (None, 0x2000, Arc::new(SlowHandler)),
+            ])
+            .await
+            .unwrap();
+
+        let valid_request = request_message(0x1000);
+        let mut invalid_request = request_message(0x1000);
+        invalid_request.attributes.as_mut().unwrap().ttl = None;
+        let mut expiring_request = request_message(0x2000);
+        expiring_request.attributes.as_mut().unwrap().ttl = Some(100);
+        let mut expected_events = vec![];
+        for (request, outcome) in [
+            (valid_request, "sending response"),
+            (invalid_request, "rejecting invalid request"),
+            (expiring_request, "request handler timed out"),
+        ] {
+            let request_id = request
+                .attributes
+                .message_id()
+                .map(|id| id.to_hyphenated_string());
+            expected_events.push(("received request", request_id.clone(), true));
+            if outcome != "sending response" {
+                expected_events.push((outcome, request_id.clone(), false));
+            }
+            expected_events.push(("sending response", request_id, true));
+            let listener = transport
+                .listener_for(request.attributes.sink.get_or_default())
+                .unwrap();
+            listener.on_receive(request).await;
+        }
+
+        let events = subscriber.events.lock().unwrap();
+        assert_eq!(events.len(), expected_events.len());
+        for (event, (message, request_id, has_redacted_message)) in
+            events.iter().zip(expected_events)
+        {
+            assert!(event.message.starts_with(message), "{:?}", event);
+            assert_eq!(event.request_id, request_id);
+            assert_eq!(event.has_redacted_message, has_redacted_message);
+        }
+        assert_eq!(transport.sent_messages().len(), 3);
+    }
+}
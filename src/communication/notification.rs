@@ -14,6 +14,7 @@
 use std::{error::Error, fmt::Display, sync::Arc};
 
 use async_trait::async_trait;
+use futures::future::join_all;
 
 use crate::communication::RegistrationError;
 use crate::{UListener, UStatus, UUri};
@@ -68,6 +69,56 @@ pub trait Notifier: Send + Sync {
         call_options: CallOptions,
         payload: Option<UPayload>,
     ) -> Result<(), NotificationError>;
+
+    /// Sends the same notification to multiple uEntities.
+    ///
+    /// The notifications are sent concurrently. The payload's data is shared among all notification
+    /// messages instead of being copied for each destination. A failure to notify one destination
+    /// does not prevent the notification from being sent to the remaining destinations.
+    ///
+    /// Each notification message gets its own message ID. A [message ID](CallOptions::with_message_id)
+    /// contained in the call options can therefore only be used if there is a single destination.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource_id` - The (local) resource identifier repesenting the origin of the notification.
+    /// * `destinations` - URIs representing the uEntities that the notification should be sent to.
+    /// * `call_options` - Options to include in the notification messages.
+    /// * `payload` - The payload to include in the notification messages.
+    ///
+    /// # Returns
+    ///
+    /// The outcome of sending the notification to each destination, in the order of the given destinations.
+    /// If the call options contain a message ID and there is more than one destination, the outcome is a
+    /// [`NotificationError::InvalidArgument`] for each destination and no notification is sent at all.
+    async fn notify_many(
+        &self,
+        resource_id: &UUri,
+        destinations: &[UUri],
+        call_options: CallOptions,
+        payload: Option<UPayload>,
+    ) -> Vec<Result<(), NotificationError>> {
+        if call_options.message_id().is_some() && destinations.len() > 1 {
+            return destinations
+                .iter()
+                .map(|_| {
+                    Err(NotificationError::InvalidArgument(
+                        "message ID cannot be used for multiple notifications".to_string(),
+                    ))
+                })
+                .collect();
+        }
+        join_all(destinations.iter().map(|destination| {
+            // cloning the payload only increments the reference count of its data
+            self.notify(
+                resource_id,
+                destination,
+                call_options.clone(),
+                payload.clone(),
+            )
+        }))
+        .await
+    }
 }
 
 /// A client for listening to Notification messages sent to this uEntity.
@@ -112,3 +163,135 @@ pub trait NotificationListener: Send + Sync {
         listener: Arc<dyn UListener>,
     ) -> Result<(), RegistrationError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{UMessage, UMessageBuilder, UPayloadFormat, UTransport, UUID};
+
+    use super::*;
+
+    /// A transport that records all messages that it has been asked to send.
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent_messages: Mutex<Vec<UMessage>>,
+    }
+
+    #[async_trait]
+    impl UTransport for RecordingTransport {
+        async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+            if message.attributes.sink.authority_name == "unreachable-vehicle" {
+                return Err(UStatus::unavailable("no route to destination"));
+            }
+            self.sent_messages.lock().unwrap().push(message);
+            Ok(())
+        }
+    }
+
+    struct TransportNotifier {
+        transport: RecordingTransport,
+    }
+
+    #[async_trait]
+    impl Notifier for TransportNotifier {
+        async fn notify(
+            &self,
+            resource_id: &UUri,
+            destination: &UUri,
+            call_options: CallOptions,
+            payload: Option<UPayload>,
+        ) -> Result<(), NotificationError> {
+            let mut builder =
+                UMessageBuilder::notification(resource_id.to_owned(), destination.to_owned());
+            if let Some(message_id) = call_options.message_id() {
+                builder.with_message_id(message_id);
+            }
+            let message = match payload {
+                Some(payload) => {
+                    let payload_format = payload.payload_format();
+                    builder.build_with_payload(payload.payload(), payload_format)
+                }
+                None => builder.build(),
+            }
+            .map_err(|e| NotificationError::InvalidArgument(e.to_string()))?;
+            self.transport
+                .send(message)
+                .await
+                .map_err(NotificationError::NotifyError)
+        }
+    }
+
+    fn new_notifier() -> TransportNotifier {
+        TransportNotifier {
+            transport: RecordingTransport::default(),
+        }
+    }
+
+    fn destinations() -> Vec<UUri> {
+        vec![
+            UUri::try_from("//my-vehicle/A1/1/0").unwrap(),
+            UUri::try_from("//unreachable-vehicle/A2/1/0").unwrap(),
+            UUri::try_from("//my-vehicle/A3/1/0").unwrap(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_notify_many_sends_notification_to_each_destination() {
+        let notifier = new_notifier();
+        let destinations = destinations();
+        let payload = UPayload::new("door open".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+
+        let results = notifier
+            .notify_many(
+                &UUri::try_from("//my-vehicle/4210/1/B24D").unwrap(),
+                &destinations,
+                CallOptions::new(5_000, None, None, None),
+                Some(payload.clone()),
+            )
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(NotificationError::NotifyError(_))));
+        assert!(results[2].is_ok());
+        let sent_messages = notifier.transport.sent_messages.lock().unwrap();
+        assert_eq!(sent_messages.len(), 2);
+        assert_eq!(
+            sent_messages
+                .iter()
+                .map(|msg| msg.attributes.sink.get_or_default().to_owned())
+                .collect::<Vec<_>>(),
+            vec![destinations[0].clone(), destinations[2].clone()]
+        );
+        assert!(sent_messages
+            .iter()
+            .all(|msg| msg.payload.as_deref() == Some(payload.as_bytes())));
+        assert_ne!(
+            sent_messages[0].attributes.id,
+            sent_messages[1].attributes.id
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notify_many_fails_for_message_id_and_multiple_destinations() {
+        let notifier = new_notifier();
+        let mut call_options = CallOptions::new(5_000, None, None, None);
+        call_options.with_message_id(UUID::build());
+
+        let results = notifier
+            .notify_many(
+                &UUri::try_from("//my-vehicle/4210/1/B24D").unwrap(),
+                &destinations(),
+                call_options,
+                None,
+            )
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, Err(NotificationError::InvalidArgument(_)))));
+        assert!(notifier.transport.sent_messages.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,385 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use protobuf::Enum;
+use tokio::time::Instant;
+
+use crate::{UCode, UUri, UUID};
+
+use super::{CallOptions, RpcClient, ServiceInvocationError, UPayload};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+/// An [`RpcClient`] that retries failed invocations of another client.
+///
+/// An invocation is retried if the invoked method has been marked as
+/// [idempotent](CallOptions::with_idempotent) and the invocation fails with an error whose code is
+/// contained in the configured set of retryable codes (by default all codes that are
+/// [retryable](UCode::is_retryable)). All other errors are propagated to the caller immediately.
+/// In particular, invocations of methods that are not idempotent are never retried, because the
+/// service provider might have processed the failed attempt already.
+///
+/// The TTL given in the [`CallOptions`] is treated as the deadline for the overall invocation,
+/// i.e. including all retries. Each attempt is performed with the remaining time as its TTL and
+/// with a freshly generated message ID, so that service providers do not consider a retry a
+/// duplicate of the previous attempt.
+///
+/// The delay between attempts grows exponentially, starting with the initial backoff and being
+/// capped at the maximum backoff.
+pub struct RetryingRpcClient {
+    delegate: Arc<dyn RpcClient>,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    retryable_codes: Vec<UCode>,
+}
+
+impl RetryingRpcClient {
+    /// Creates a new client for a delegate.
+    ///
+    /// The client performs at most 3 attempts with an initial backoff of 100ms
+    /// and retries on all codes that are [retryable](UCode::is_retryable).
+    ///
+    /// # Arguments
+    ///
+    /// * `delegate` - The client to use for performing the actual invocations.
+    pub fn new(delegate: Arc<dyn RpcClient>) -> Self {
+        RetryingRpcClient {
+            delegate,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            retryable_codes: UCode::VALUES
+                .iter()
+                .filter(|code| code.is_retryable())
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Sets the maximum number of attempts to perform for an invocation.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_attempts` - The number of attempts, including the first one. A value of `0` is treated as `1`.
+    pub fn with_max_attempts(&mut self, max_attempts: u32) -> &mut Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay between the first and the second attempt.
+    ///
+    /// The delay is doubled for each subsequent attempt.
+    pub fn with_initial_backoff(&mut self, backoff: Duration) -> &mut Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Sets the maximum delay between two attempts.
+    pub fn with_max_backoff(&mut self, backoff: Duration) -> &mut Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Sets the codes indicating a transient failure that is worth retrying.
+    pub fn with_retryable_codes<I: IntoIterator<Item = UCode>>(&mut self, codes: I) -> &mut Self {
+        self.retryable_codes = codes.into_iter().collect();
+        self
+    }
+
+    fn is_retryable(&self, error: &ServiceInvocationError) -> bool {
+        let code = match error {
+            ServiceInvocationError::Cancelled => UCode::CANCELLED,
+            ServiceInvocationError::DeadlineExceeded => UCode::DEADLINE_EXCEEDED,
+            ServiceInvocationError::InvalidArgument(_) => UCode::INVALID_ARGUMENT,
+            ServiceInvocationError::NotFound(_) => UCode::NOT_FOUND,
+            ServiceInvocationError::RpcError(status) => status.get_code(),
+        };
+        self.retryable_codes.contains(&code)
+    }
+
+    fn backoff(&self, failed_attempts: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(2_u32.saturating_pow(failed_attempts - 1))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+#[async_trait]
+impl RpcClient for RetryingRpcClient {
+    async fn invoke_method(
+        &self,
+        method: UUri,
+        call_options: CallOptions,
+        payload: Option<UPayload>,
+    ) -> Result<Option<UPayload>, ServiceInvocationError> {
+        let deadline = Instant::now() + Duration::from_millis(call_options.ttl() as u64);
+        let mut attempt_options = call_options;
+        let mut attempt = 1;
+
+        loop {
+            let result = self
+                .delegate
+                .invoke_method(method.clone(), attempt_options.clone(), payload.clone())
+                .await;
+
+            let error = match result {
+                Err(e)
+                    if attempt < self.max_attempts
+                        && attempt_options.is_idempotent()
+                        && self.is_retryable(&e) =>
+                {
+                    e
+                }
+                _ => return result,
+            };
+
+            let backoff = self.backoff(attempt);
+            let remaining = deadline.saturating_duration_since(Instant::now() + backoff);
+            if remaining.is_zero() {
+                // no time left for another attempt
+                return Err(error);
+            }
+            tokio::time::sleep(backoff).await;
+
+            attempt += 1;
+            let remaining_ttl = u32::try_from(remaining.as_millis()).unwrap_or(u32::MAX);
+            attempt_options
+                .with_ttl(remaining_ttl.max(1))
+                .with_message_id(UUID::build());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use test_case::test_case;
+
+    use crate::{UPayloadFormat, UStatus};
+
+    use super::*;
+
+    type InvocationResult = Result<Option<UPayload>, ServiceInvocationError>;
+
+    struct ScriptedRpcClient {
+        results: Mutex<VecDeque<InvocationResult>>,
+        invocations: Mutex<Vec<CallOptions>>,
+    }
+
+    impl ScriptedRpcClient {
+        fn new(results: Vec<InvocationResult>) -> Self {
+            ScriptedRpcClient {
+                results: Mutex::new(results.into()),
+                invocations: Mutex::new(vec![]),
+            }
+        }
+
+        fn invocations(&self) -> Vec<CallOptions> {
+            self.invocations.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl RpcClient for ScriptedRpcClient {
+        async fn invoke_method(
+            &self,
+            _method: UUri,
+            call_options: CallOptions,
+            _payload: Option<UPayload>,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            self.invocations.lock().unwrap().push(call_options);
+            self.results
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("unexpected invocation")
+        }
+    }
+
+    fn failure(code: UCode) -> InvocationResult {
+        Err(ServiceInvocationError::from(UStatus::fail_with_code(
+            code, "failure",
+        )))
+    }
+
+    fn success() -> InvocationResult {
+        Ok(Some(UPayload::new(
+            "response".into(),
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+        )))
+    }
+
+    fn idempotent_call() -> CallOptions {
+        let mut call_options = CallOptions::default();
+        call_options.with_idempotent(true);
+        call_options
+    }
+
+    fn method() -> UUri {
+        UUri::try_from("//my-vehicle/A8000/2/1A50").unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_method_retries_with_fresh_message_ids() {
+        let delegate = Arc::new(ScriptedRpcClient::new(vec![
+            failure(UCode::UNAVAILABLE),
+            failure(UCode::UNAVAILABLE),
+            success(),
+        ]));
+        let client = RetryingRpcClient::new(delegate.clone());
+        let mut call_options = idempotent_call();
+        call_options.with_message_id(UUID::build());
+
+        let result = client
+            .invoke_method(method(), call_options, None)
+            .await
+            .expect("invocation should have succeeded after retries");
+        assert!(result.is_some());
+
+        let invocations = delegate.invocations();
+        assert_eq!(invocations.len(), 3);
+        let ids: Vec<UUID> = invocations
+            .iter()
+            .map(|options| options.message_id().unwrap())
+            .collect();
+        assert_ne!(ids[0], ids[1]);
+        assert_ne!(ids[1], ids[2]);
+        assert_ne!(ids[0], ids[2]);
+        // the TTL of each attempt shrinks by the time spent waiting
+        assert_eq!(invocations[0].ttl(), 10_000);
+        assert_eq!(invocations[1].ttl(), 9_900);
+        assert_eq!(invocations[2].ttl(), 9_700);
+    }
+
+    #[test_case(UCode::INVALID_ARGUMENT; "for INVALID_ARGUMENT")]
+    #[test_case(UCode::NOT_FOUND; "for NOT_FOUND")]
+    #[test_case(UCode::PERMISSION_DENIED; "for PERMISSION_DENIED")]
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_method_propagates_non_retryable_error(code: UCode) {
+        let delegate = Arc::new(ScriptedRpcClient::new(vec![failure(code)]));
+        let client = RetryingRpcClient::new(delegate.clone());
+
+        let result = client
+            .invoke_method(method(), idempotent_call(), None)
+            .await;
+        assert!(result.is_err());
+        assert_eq!(delegate.invocations().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_method_does_not_retry_non_idempotent_method() {
+        let delegate = Arc::new(ScriptedRpcClient::new(vec![failure(UCode::UNAVAILABLE)]));
+        let client = RetryingRpcClient::new(delegate.clone());
+
+        let result = client
+            .invoke_method(method(), CallOptions::default(), None)
+            .await;
+        assert!(result.is_err_and(|e| matches!(
+            e,
+            ServiceInvocationError::RpcError(status) if status.get_code() == UCode::UNAVAILABLE
+        )));
+        assert_eq!(delegate.invocations().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_method_gives_up_after_max_attempts() {
+        let delegate = Arc::new(ScriptedRpcClient::new(vec![
+            failure(UCode::UNAVAILABLE),
+            failure(UCode::UNAVAILABLE),
+        ]));
+        let mut client = RetryingRpcClient::new(delegate.clone());
+        client.with_max_attempts(2);
+
+        let result = client
+            .invoke_method(method(), idempotent_call(), None)
+            .await;
+        assert!(result.is_err_and(|e| matches!(
+            e,
+            ServiceInvocationError::RpcError(status) if status.get_code() == UCode::UNAVAILABLE
+        )));
+        assert_eq!(delegate.invocations().len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_method_respects_overall_deadline() {
+        let delegate = Arc::new(ScriptedRpcClient::new(vec![
+            failure(UCode::UNAVAILABLE),
+            failure(UCode::UNAVAILABLE),
+        ]));
+        let mut client = RetryingRpcClient::new(delegate.clone());
+        client
+            .with_max_attempts(10)
+            .with_initial_backoff(Duration::from_millis(400));
+        let mut call_options = idempotent_call();
+        call_options.with_ttl(1_000);
+
+        let result = client.invoke_method(method(), call_options, None).await;
+        assert!(result.is_err());
+        // second attempt after 400ms, third attempt would start after another 800ms
+        assert_eq!(delegate.invocations().len(), 2);
+        assert_eq!(delegate.invocations()[1].ttl(), 600);
+    }
+
+    #[test_case(UCode::UNAVAILABLE; "for UNAVAILABLE")]
+    #[test_case(UCode::ABORTED; "for ABORTED")]
+    #[test_case(UCode::RESOURCE_EXHAUSTED; "for RESOURCE_EXHAUSTED")]
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_method_retries_on_retryable_codes(code: UCode) {
+        let delegate = Arc::new(ScriptedRpcClient::new(vec![failure(code), success()]));
+        let client = RetryingRpcClient::new(delegate.clone());
+
+        let result = client
+            .invoke_method(method(), idempotent_call(), None)
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(delegate.invocations().len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_invoke_method_retries_on_configured_codes() {
+        let delegate = Arc::new(ScriptedRpcClient::new(vec![
+            failure(UCode::INTERNAL),
+            success(),
+        ]));
+        let mut client = RetryingRpcClient::new(delegate.clone());
+        client.with_retryable_codes([UCode::INTERNAL]);
+
+        let result = client
+            .invoke_method(method(), idempotent_call(), None)
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(delegate.invocations().len(), 2);
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let mut client = RetryingRpcClient::new(Arc::new(ScriptedRpcClient::new(vec![])));
+        client
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500));
+        assert_eq!(client.backoff(1), Duration::from_millis(100));
+        assert_eq!(client.backoff(2), Duration::from_millis(200));
+        assert_eq!(client.backoff(3), Duration::from_millis(400));
+        assert_eq!(client.backoff(4), Duration::from_millis(500));
+        assert_eq!(client.backoff(40), Duration::from_millis(500));
+    }
+}
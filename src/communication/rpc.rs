@@ -14,18 +14,23 @@
 use std::error::Error;
 use std::fmt::Display;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 use protobuf::Message;
+use tokio::time::Instant;
 
 use crate::communication::RegistrationError;
-use crate::{UCode, UStatus, UUri};
+use crate::{UAttributes, UCode, UPayloadFormat, UPriority, UStatus, UUri, UUID};
 
-use super::{CallOptions, UPayload};
+use super::{CallOptions, UPayload, DEFAULT_TTL};
 
 /// An error indicating a problem with publishing a message to a topic.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ServiceInvocationError {
+    /// Indicates that the client has cancelled the request before a response has been received.
+    Cancelled,
     /// Indicates that a request's time-to-live (TTL) has expired.
     ///
     /// Note that this only means that the reply to the request has not been received in time. The request
@@ -33,6 +38,8 @@ pub enum ServiceInvocationError {
     DeadlineExceeded,
     /// Indicates that the request cannot be processed because some of its parameters are not as expected.
     InvalidArgument(String),
+    /// Indicates that the service provider could not find the entity that the request refers to.
+    NotFound(String),
     /// Indicates an unspecific error that occurred at the Transport Layer while trying to publish a message.
     RpcError(UStatus),
 }
@@ -40,20 +47,42 @@ pub enum ServiceInvocationError {
 impl From<UStatus> for ServiceInvocationError {
     fn from(value: UStatus) -> Self {
         match value.code.enum_value() {
+            Ok(UCode::CANCELLED) => ServiceInvocationError::Cancelled,
             Ok(UCode::DEADLINE_EXCEEDED) => ServiceInvocationError::DeadlineExceeded,
             Ok(UCode::INVALID_ARGUMENT) => {
                 ServiceInvocationError::InvalidArgument(value.get_message())
             }
+            Ok(UCode::NOT_FOUND) => ServiceInvocationError::NotFound(value.get_message()),
             _ => ServiceInvocationError::RpcError(value),
         }
     }
 }
 
+impl From<ServiceInvocationError> for UStatus {
+    fn from(value: ServiceInvocationError) -> Self {
+        match value {
+            ServiceInvocationError::Cancelled => {
+                UStatus::fail_with_code(UCode::CANCELLED, "request has been cancelled")
+            }
+            ServiceInvocationError::DeadlineExceeded => {
+                UStatus::fail_with_code(UCode::DEADLINE_EXCEEDED, "request timed out")
+            }
+            ServiceInvocationError::InvalidArgument(msg) => {
+                UStatus::fail_with_code(UCode::INVALID_ARGUMENT, msg)
+            }
+            ServiceInvocationError::NotFound(msg) => UStatus::fail_with_code(UCode::NOT_FOUND, msg),
+            ServiceInvocationError::RpcError(status) => status,
+        }
+    }
+}
+
 impl Display for ServiceInvocationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            ServiceInvocationError::Cancelled => f.write_str("request has been cancelled"),
             ServiceInvocationError::DeadlineExceeded => f.write_str("request timed out"),
             ServiceInvocationError::InvalidArgument(s) => f.write_str(s.as_str()),
+            ServiceInvocationError::NotFound(s) => f.write_str(s.as_str()),
             ServiceInvocationError::RpcError(s) => {
                 f.write_fmt(format_args!("failed to send invoke method: {}", s))
             }
@@ -137,6 +166,182 @@ impl dyn RpcClient {
     }
 }
 
+/// The resource identifier of an RPC method.
+///
+/// RPC methods are identified by resource IDs in range `[0x0001, 0x7FFF]`. Creating a method identifier
+/// requires the resource ID to be in this range, so that functions accepting a `MethodId` do not need to
+/// check the range themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MethodId(u16);
+
+impl MethodId {
+    /// Creates a method identifier for a resource ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistrationError::InvalidFilter`] if the given resource ID is not in range `[0x0001, 0x7FFF]`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::communication::MethodId;
+    ///
+    /// assert_eq!(MethodId::new(0x1A50).unwrap().value(), 0x1A50);
+    /// assert!(MethodId::new(0x0000).is_err());
+    /// assert!(MethodId::new(0x8000).is_err());
+    /// ```
+    pub fn new(resource_id: u16) -> Result<Self, RegistrationError> {
+        if (UUri::RPC_METHOD_ID_MIN..=UUri::RPC_METHOD_ID_MAX).contains(&u32::from(resource_id)) {
+            Ok(MethodId(resource_id))
+        } else {
+            Err(RegistrationError::InvalidFilter(format!(
+                "resource ID {:#06X} is not in range [{:#06X}, {:#06X}]",
+                resource_id,
+                UUri::RPC_METHOD_ID_MIN,
+                UUri::RPC_METHOD_ID_MAX
+            )))
+        }
+    }
+
+    /// Gets the resource ID of this method.
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+}
+
+impl TryFrom<u16> for MethodId {
+    type Error = RegistrationError;
+
+    fn try_from(resource_id: u16) -> Result<Self, Self::Error> {
+        MethodId::new(resource_id)
+    }
+}
+
+impl From<MethodId> for u16 {
+    fn from(method_id: MethodId) -> Self {
+        method_id.0
+    }
+}
+
+/// Information about an incoming RPC request that is not part of the request's payload.
+///
+/// Request handlers can use this information for performing per-caller authorization or logging.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestContext {
+    resource_id: u16,
+    source: UUri,
+    request_id: UUID,
+    priority: UPriority,
+    token: Option<String>,
+    permission_level: Option<u32>,
+    deadline: Instant,
+}
+
+impl RequestContext {
+    /// Gets the resource identifier of the method being invoked.
+    pub fn resource_id(&self) -> u16 {
+        self.resource_id
+    }
+
+    /// Gets the address that the client expects to receive the response at.
+    pub fn source(&self) -> &UUri {
+        &self.source
+    }
+
+    /// Gets the identifier of the request message.
+    pub fn request_id(&self) -> &UUID {
+        &self.request_id
+    }
+
+    /// Gets the priority of the request message.
+    pub fn priority(&self) -> UPriority {
+        self.priority
+    }
+
+    /// Gets the access token that the client has included in the request.
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    /// Gets the permission level that the client has included in the request.
+    pub fn permission_level(&self) -> Option<u32> {
+        self.permission_level
+    }
+
+    /// Gets the amount of time that is left for processing the request.
+    ///
+    /// The deadline is determined from the request's creation time and TTL when the context is
+    /// created, i.e. upon arrival of the request. Handlers that invoke other services while processing
+    /// the request should use (at most) the remaining time as the timeout for these invocations,
+    /// so that the deadline is propagated across service hops.
+    ///
+    /// # Returns
+    ///
+    /// The remaining time or [`Duration::ZERO`] if the deadline has passed already.
+    pub fn deadline_remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+}
+
+// Determines the amount of time left until a request expires, based on the creation time contained
+// in its ID and its TTL. Requests without a TTL are considered to expire after the default TTL.
+pub(crate) fn remaining_time(request_attributes: &UAttributes) -> Duration {
+    let ttl = u64::from(request_attributes.ttl.unwrap_or(DEFAULT_TTL));
+    let Some(creation_time) = request_attributes.message_id().and_then(UUID::get_time) else {
+        return Duration::from_millis(ttl);
+    };
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_millis() as u64);
+    Duration::from_millis(creation_time.saturating_add(ttl).saturating_sub(now))
+}
+
+impl From<&UAttributes> for RequestContext {
+    /// Creates the context for the request that a set of attributes belongs to.
+    ///
+    /// Attributes missing from the given set are replaced by their default values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UAttributes, UPriority, UUID, UUri};
+    /// use up_rust::communication::RequestContext;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let request_id = UUID::build();
+    /// let reply_to_address = UUri::try_from("//my-cloud/BA4C/1/0")?;
+    /// let attributes = UAttributes::request(
+    ///     request_id.clone(),
+    ///     UUri::try_from("//my-vehicle/4210/1/1A50")?,
+    ///     reply_to_address.clone(),
+    ///     None,
+    ///     Some("my-token".to_string()),
+    ///     Some(5_000),
+    /// );
+    /// let context = RequestContext::from(&attributes);
+    /// assert_eq!(context.resource_id(), 0x1A50);
+    /// assert_eq!(context.source(), &reply_to_address);
+    /// assert_eq!(context.request_id(), &request_id);
+    /// assert_eq!(context.priority(), UPriority::UPRIORITY_CS4);
+    /// assert_eq!(context.token(), Some("my-token"));
+    /// assert_eq!(context.permission_level(), None);
+    /// assert!(context.deadline_remaining().as_millis() <= 5_000);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn from(attributes: &UAttributes) -> Self {
+        RequestContext {
+            resource_id: attributes.sink.resource_id as u16,
+            source: attributes.source.get_or_default().to_owned(),
+            request_id: attributes.id.get_or_default().to_owned(),
+            priority: attributes.priority.enum_value_or_default(),
+            token: attributes.token.clone(),
+            permission_level: attributes.permission_level,
+            deadline: Instant::now() + remaining_time(attributes),
+        }
+    }
+}
+
 /// A handler for processing incoming RPC requests.
 ///
 #[async_trait]
@@ -160,6 +365,159 @@ pub trait RequestHandler: Send + Sync {
         resource_id: u16,
         payload: UPayload,
     ) -> Result<Option<UPayload>, ServiceInvocationError>;
+
+    /// Invokes a method with given input parameters and information about the request.
+    ///
+    /// Servers invoke this function for each incoming request. The default implementation
+    /// simply delegates to [`RequestHandler::invoke_method`]. Handlers that need to know
+    /// about the client or the request message, e.g. for performing authorization checks,
+    /// can override this function instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Information about the request, like the client's address and access token.
+    /// * `payload` - The raw payload that contains the input data for the method.
+    ///
+    /// # Returns
+    ///
+    /// the output data generated by the method.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the method request could not be processed successfully.
+    async fn invoke_method_with_context(
+        &self,
+        context: RequestContext,
+        payload: UPayload,
+    ) -> Result<Option<UPayload>, ServiceInvocationError> {
+        self.invoke_method(context.resource_id(), payload).await
+    }
+
+    /// Notifies the handler that the processing of a request has been cancelled by the client.
+    ///
+    /// Servers that support request cancellation invoke this function after they have stopped
+    /// (and dropped) the invocation of [`RequestHandler::invoke_method_with_context`] for the request.
+    /// Handlers can use it for releasing resources or rolling back partially applied changes.
+    /// The default implementation does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Information about the cancelled request.
+    async fn on_cancelled(&self, _context: &RequestContext) {}
+}
+
+/// A handler that returns the request payload unchanged.
+///
+/// This is useful for testing transports and clients, e.g. by means of
+/// [`InMemoryRpcServer::register_echo_endpoint`](super::InMemoryRpcServer::register_echo_endpoint).
+///
+/// # Examples
+///
+/// ```rust
+/// use up_rust::UPayloadFormat;
+/// use up_rust::communication::{EchoHandler, RequestHandler, UPayload};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let payload = UPayload::new("hello".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+/// let response = EchoHandler.invoke_method(0x1000, payload.clone()).await?;
+/// assert_eq!(response, Some(payload));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EchoHandler;
+
+#[async_trait]
+impl RequestHandler for EchoHandler {
+    async fn invoke_method(
+        &self,
+        _resource_id: u16,
+        payload: UPayload,
+    ) -> Result<Option<UPayload>, ServiceInvocationError> {
+        Ok(Some(payload))
+    }
+}
+
+/// A handler for processing incoming RPC requests that produce a sequence of results.
+///
+/// A server sends each item of the stream returned by the handler to the client in a separate
+/// RPC response message. All of these messages refer to the request message by means of their
+/// `reqid` attribute. Because [`UAttributes`] do not provide a dedicated field for a sequence number,
+/// each response message carries its zero-based position within the stream in its message ID
+/// (see [`stream_index`]). This allows clients to restore the order of the items and to detect lost items,
+/// even if the transport does not preserve the order of the response messages. Clients can use
+/// [`InMemoryRpcClient::invoke_streaming_method`](super::InMemoryRpcClient::invoke_streaming_method)
+/// for consuming the stream.
+///
+/// Response messages carrying an item do not contain a `commstatus`. The end of the stream is signaled
+/// by means of a final response message that has its `commstatus` set explicitly:
+///
+/// * [`UCode::OK`] and no payload, if the handler has produced all items successfully, or
+/// * any other code and a [`UStatus`] payload, if the handler has failed to produce an item.
+pub trait StreamingRequestHandler: Send + Sync {
+    /// Invokes a method with given input parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource_id` - The resource identifier of the method to invoke.
+    /// * `payload` - The raw payload that contains the input data for the method.
+    ///
+    /// # Returns
+    ///
+    /// the stream of output data generated by the method. The stream ends after the first error.
+    fn invoke_method(
+        &self,
+        resource_id: u16,
+        payload: UPayload,
+    ) -> BoxStream<'static, Result<UPayload, ServiceInvocationError>>;
+}
+
+// the bits of a message ID that carry the index of a response message within a stream
+const STREAM_INDEX_MASK: u64 = 0xFFFF_FFFF;
+
+/// Gets the sequence index of a response message that has been sent for a streaming request.
+///
+/// Servers encode the (zero-based) position of a response message within the stream in the lowest 32 bits
+/// of the message's ID, which are random bits otherwise. The IDs thereby remain valid uProtocol UUIDs,
+/// i.e. the `token` and all other attributes keep their regular meaning. The final message of a stream
+/// carries the index following the one of the last item.
+///
+/// Note that the index can be determined from any message ID, so it is only meaningful for the response
+/// messages of a streaming request.
+///
+/// # Returns
+///
+/// The index or `None` if the attributes do not contain a message ID.
+///
+/// # Examples
+///
+/// ```rust
+/// use up_rust::{UAttributes, UUID};
+/// use up_rust::communication::stream_index;
+///
+/// let attributes = UAttributes {
+///     id: Some(UUID { msb: 0x0000000000017000, lsb: 0x8010101000000003, ..Default::default() }).into(),
+///     ..Default::default()
+/// };
+/// assert_eq!(stream_index(&attributes), Some(3));
+/// assert_eq!(stream_index(&UAttributes::default()), None);
+/// ```
+pub fn stream_index(attributes: &UAttributes) -> Option<u32> {
+    attributes
+        .id
+        .as_ref()
+        .map(|id| (id.lsb & STREAM_INDEX_MASK) as u32)
+}
+
+// Creates the message ID for a response message that is sent for a streaming request.
+pub(crate) fn stream_message_id(index: u32) -> UUID {
+    let id = UUID::build();
+    UUID {
+        msb: id.msb,
+        lsb: (id.lsb & !STREAM_INDEX_MASK) | u64::from(index),
+        ..Default::default()
+    }
 }
 
 /// A server for exposing RPC endpoints.
@@ -182,8 +540,12 @@ pub trait RpcServer {
     ///
     /// # Errors
     ///
-    /// Returns an error if the listener cannot be registered or if a listener has already been registered
-    /// for the given resource ID.
+    /// Returns an error if the endpoint cannot be registered, e.g.
+    ///
+    /// * [`RegistrationError::DuplicateEndpoint`] if an endpoint has already been registered for the
+    ///   given origin filter and resource ID,
+    /// * [`RegistrationError::CapacityExceeded`] if the service provider does not support any further endpoints,
+    /// * [`RegistrationError::TransportError`] if the transport has failed to register the endpoint's listener.
     async fn register_endpoint(
         &self,
         origin_filter: Option<&UUri>,
@@ -208,4 +570,175 @@ pub trait RpcServer {
         resource_id: u16,
         request_handler: Arc<dyn RequestHandler>,
     ) -> Result<(), RegistrationError>;
+
+    /// Registers an endpoint for RPC requests to a method.
+    ///
+    /// This is equivalent to invoking [`RpcServer::register_endpoint`] with the method's resource ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - A pattern defining origin addresses to accept requests from. If `None`, requests
+    ///                     will be accepted from all sources.
+    /// * `method_id` - The identifier of the (local) method to accept requests for.
+    /// * `request_handler` - The handler to invoke for each incoming request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot be registered or if a listener has already been registered
+    /// for the given method.
+    async fn register_method_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        method_id: MethodId,
+        request_handler: Arc<dyn RequestHandler>,
+    ) -> Result<(), RegistrationError> {
+        self.register_endpoint(origin_filter, method_id.value(), request_handler)
+            .await
+    }
+
+    /// Unregisters a previously [registered endpoint](Self::register_method_endpoint).
+    ///
+    /// This is equivalent to invoking [`RpcServer::unregister_endpoint`] with the method's resource ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_filter` - The origin pattern that the endpoint had been registered for.
+    /// * `method_id` - The identifier of the (local) method that the endpoint had been registered for.
+    /// * `request_handler` - The handler to unregister.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the listener cannot be unregistered.
+    async fn unregister_method_endpoint(
+        &self,
+        origin_filter: Option<&UUri>,
+        method_id: MethodId,
+        request_handler: Arc<dyn RequestHandler>,
+    ) -> Result<(), RegistrationError> {
+        self.unregister_endpoint(origin_filter, method_id.value(), request_handler)
+            .await
+    }
+}
+
+/// Determines the payload format to use for the response to an RPC request.
+///
+/// [`UAttributes`] do not (yet) provide an attribute that clients could use for explicitly listing the payload
+/// formats that they accept in a response. The `token` attribute is reserved for access tokens and must not be
+/// used for this purpose. Consequently, the format of the request's own payload is considered to be the format
+/// preferred by the client.
+///
+/// # Arguments
+///
+/// * `request` - The attributes of the request message.
+/// * `supported` - The payload formats that the server can create the response in, in order of the server's preference.
+///
+/// # Returns
+///
+/// the request's payload format, if it is supported by the server. Otherwise, the format most preferred by the server.
+/// Falls back to the request's payload format if the server has not specified any supported formats.
+///
+/// # Examples
+///
+/// ```rust
+/// use up_rust::{UAttributes, UPayloadFormat};
+/// use up_rust::communication::preferred_response_format;
+///
+/// let request = UAttributes {
+///     payload_format: UPayloadFormat::UPAYLOAD_FORMAT_JSON.into(),
+///     ..Default::default()
+/// };
+/// let supported = [UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF, UPayloadFormat::UPAYLOAD_FORMAT_JSON];
+/// assert_eq!(preferred_response_format(&request, &supported), UPayloadFormat::UPAYLOAD_FORMAT_JSON);
+/// assert_eq!(
+///     preferred_response_format(&request, &supported[..1]),
+///     UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF
+/// );
+/// ```
+pub fn preferred_response_format(
+    request: &UAttributes,
+    supported: &[UPayloadFormat],
+) -> UPayloadFormat {
+    let requested_format = request.payload_format.enum_value_or_default();
+    if supported.contains(&requested_format) {
+        requested_format
+    } else {
+        supported.first().copied().unwrap_or(requested_format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use crate::UMessageBuilder;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_deadline_remaining_decreases_over_time() {
+        let attributes = UAttributes::request(
+            UUID::build(),
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            None,
+            None,
+            Some(5_000),
+        );
+        let context = RequestContext::from(&attributes);
+        assert!(context.deadline_remaining() <= Duration::from_millis(5_000));
+
+        tokio::time::advance(Duration::from_millis(2_000)).await;
+        assert!(context.deadline_remaining() <= Duration::from_millis(3_000));
+
+        tokio::time::advance(Duration::from_millis(4_000)).await;
+        assert_eq!(context.deadline_remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_request_context_contains_token_and_permission_level() {
+        let request = UMessageBuilder::request(
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .with_token("my-token")
+        .with_permission_level(7)
+        .build()
+        .unwrap();
+
+        let context = RequestContext::from(request.attributes.get_or_default());
+        assert_eq!(context.token(), Some("my-token"));
+        assert_eq!(context.permission_level(), Some(7));
+    }
+
+    #[test_case(0x0000, false; "for resource ID 0")]
+    #[test_case(0x0001, true; "for min method ID")]
+    #[test_case(0x7FFF, true; "for max method ID")]
+    #[test_case(0x8000, false; "for event ID")]
+    #[test_case(0xFFFF, false; "for wildcard ID")]
+    fn test_method_id_new(resource_id: u16, expected_valid: bool) {
+        let result = MethodId::new(resource_id);
+        assert_eq!(result.is_ok(), expected_valid);
+        if let Ok(method_id) = result {
+            assert_eq!(u16::from(method_id), resource_id);
+        } else {
+            assert!(matches!(result, Err(RegistrationError::InvalidFilter(_))));
+        }
+    }
+
+    #[test_case(UPayloadFormat::UPAYLOAD_FORMAT_JSON, &[UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF, UPayloadFormat::UPAYLOAD_FORMAT_JSON], UPayloadFormat::UPAYLOAD_FORMAT_JSON; "for supported request format")]
+    #[test_case(UPayloadFormat::UPAYLOAD_FORMAT_TEXT, &[UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF, UPayloadFormat::UPAYLOAD_FORMAT_JSON], UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF; "for unsupported request format")]
+    #[test_case(UPayloadFormat::UPAYLOAD_FORMAT_TEXT, &[], UPayloadFormat::UPAYLOAD_FORMAT_TEXT; "for no supported formats")]
+    #[test_case(UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED, &[UPayloadFormat::UPAYLOAD_FORMAT_RAW], UPayloadFormat::UPAYLOAD_FORMAT_RAW; "for unspecified request format")]
+    fn test_preferred_response_format(
+        request_format: UPayloadFormat,
+        supported: &[UPayloadFormat],
+        expected: UPayloadFormat,
+    ) {
+        let request = UAttributes {
+            payload_format: request_format.into(),
+            ..Default::default()
+        };
+        assert_eq!(preferred_response_format(&request, supported), expected);
+    }
 }
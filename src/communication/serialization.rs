@@ -0,0 +1,195 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use protobuf::Message;
+#[cfg(feature = "json")]
+use protobuf::MessageFull;
+
+use crate::umessage::UMessageError;
+#[cfg(feature = "json")]
+use crate::UPayloadFormat;
+
+use super::UPayload;
+
+/// An encoding for turning values of a given type into [`UPayload`]s.
+///
+/// Implementations allow applications to use any encoding for the data that is exchanged
+/// with other uEntities, e.g. CBOR or FlatBuffers, in addition to the built-in encodings.
+pub trait PayloadSerializer<T: ?Sized> {
+    /// Encodes a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be encoded.
+    fn serialize(value: &T) -> Result<UPayload, UMessageError>;
+}
+
+/// An encoding for turning [`UPayload`]s into values of a given type.
+pub trait PayloadDeserializer<T> {
+    /// Decodes a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload has an unsupported format or if its data cannot be decoded.
+    fn deserialize(payload: &UPayload) -> Result<T, UMessageError>;
+}
+
+/// The binary encoding of protobuf messages.
+///
+/// Serialized payloads have format [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF`](crate::UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF).
+/// Payloads of format [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY`](crate::UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY)
+/// can be deserialized as well.
+pub struct ProtobufSerializer;
+
+impl<M: Message> PayloadSerializer<M> for ProtobufSerializer {
+    fn serialize(value: &M) -> Result<UPayload, UMessageError> {
+        UPayload::try_from_protobuf(value.clone())
+    }
+}
+
+impl<M: Message + Default> PayloadDeserializer<M> for ProtobufSerializer {
+    fn deserialize(payload: &UPayload) -> Result<M, UMessageError> {
+        payload.extract_protobuf()
+    }
+}
+
+/// The canonical JSON encoding of protobuf messages, as defined by the [proto3 JSON mapping](https://protobuf.dev/programming-guides/proto3/#json).
+///
+/// Serialized payloads have format [`UPayloadFormat::UPAYLOAD_FORMAT_JSON`]. The encoding is the same as the one
+/// used by [`UPayload::transcode_to`].
+#[cfg(feature = "json")]
+pub struct JsonSerializer;
+
+#[cfg(feature = "json")]
+impl<M: MessageFull> PayloadSerializer<M> for JsonSerializer {
+    fn serialize(value: &M) -> Result<UPayload, UMessageError> {
+        protobuf_json_mapping::print_to_string(value)
+            .map(|json| UPayload::new(json.into(), UPayloadFormat::UPAYLOAD_FORMAT_JSON))
+            .map_err(|e| UMessageError::PayloadError(e.to_string()))
+    }
+}
+
+#[cfg(feature = "json")]
+impl<M: MessageFull> PayloadDeserializer<M> for JsonSerializer {
+    fn deserialize(payload: &UPayload) -> Result<M, UMessageError> {
+        if payload.payload_format() != UPayloadFormat::UPAYLOAD_FORMAT_JSON {
+            return Err(UMessageError::from("Payload does not contain JSON data"));
+        }
+        let json = std::str::from_utf8(payload.as_bytes())
+            .map_err(|e| UMessageError::PayloadError(e.to_string()))?;
+        protobuf_json_mapping::parse_from_str(json)
+            .map_err(|e| UMessageError::PayloadError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use crate::{UCode, UPayloadFormat, UStatus};
+
+    use super::*;
+
+    /// A trivial encoding of points as comma separated coordinates.
+    struct CsvSerializer;
+
+    #[derive(Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    impl PayloadSerializer<Point> for CsvSerializer {
+        fn serialize(value: &Point) -> Result<UPayload, UMessageError> {
+            Ok(UPayload::new(
+                Bytes::from(format!("{},{}", value.x, value.y)),
+                UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+            ))
+        }
+    }
+
+    impl PayloadDeserializer<Point> for CsvSerializer {
+        fn deserialize(payload: &UPayload) -> Result<Point, UMessageError> {
+            let text = std::str::from_utf8(payload.as_bytes())
+                .map_err(|e| UMessageError::PayloadError(e.to_string()))?;
+            let coordinates = text
+                .split(',')
+                .map(str::parse::<i32>)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| UMessageError::PayloadError(e.to_string()))?;
+            match coordinates[..] {
+                [x, y] => Ok(Point { x, y }),
+                _ => Err(UMessageError::from("expected two coordinates")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_serializer_round_trip() {
+        let point = Point { x: 3, y: -7 };
+        let payload = UPayload::serialize::<CsvSerializer, _>(&point).unwrap();
+        assert_eq!(payload.as_bytes(), b"3,-7");
+        assert_eq!(
+            payload.deserialize::<CsvSerializer, Point>().unwrap(),
+            point
+        );
+    }
+
+    #[test]
+    fn test_custom_deserializer_rejects_invalid_data() {
+        let payload = UPayload::new("3".into(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+        assert!(payload.deserialize::<CsvSerializer, Point>().is_err());
+    }
+
+    #[test]
+    fn test_protobuf_serializer_round_trip() {
+        let status = UStatus::fail_with_code(UCode::NOT_FOUND, "no such entity");
+        let payload = UPayload::serialize::<ProtobufSerializer, _>(&status).unwrap();
+        assert_eq!(
+            payload.payload_format(),
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF
+        );
+        assert_eq!(
+            payload
+                .deserialize::<ProtobufSerializer, UStatus>()
+                .unwrap(),
+            status
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_serializer_round_trip() {
+        let status = UStatus::fail_with_code(UCode::NOT_FOUND, "no such entity");
+        let payload = UPayload::serialize::<JsonSerializer, _>(&status).unwrap();
+        assert_eq!(
+            payload.payload_format(),
+            UPayloadFormat::UPAYLOAD_FORMAT_JSON
+        );
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(payload.as_bytes()).unwrap(),
+            serde_json::json!({"code": "NOT_FOUND", "message": "no such entity"})
+        );
+        assert_eq!(
+            payload.deserialize::<JsonSerializer, UStatus>().unwrap(),
+            status
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_serializer_rejects_binary_payload() {
+        let payload = UPayload::try_from_protobuf(UStatus::ok()).unwrap();
+        assert!(payload.deserialize::<JsonSerializer, UStatus>().is_err());
+    }
+}
@@ -0,0 +1,188 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::future::Future;
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use protobuf::Message;
+use tracing::warn;
+
+use crate::{UAttributes, UListener, UMessage, UPayloadFormat};
+
+/// A [`UListener`] that extracts a protobuf message of a particular type from the payload of each
+/// received message and passes it on to a callback function.
+///
+/// This relieves the callback from deserializing the payload itself, which makes this listener a
+/// good fit for consuming messages received via a [`Subscriber`](super::Subscriber).
+///
+/// A message whose payload cannot be extracted, e.g. because it contains a different type of
+/// protobuf message or uses an unsupported payload format, is discarded and a warning is logged.
+/// A message without any payload data is passed to the callback as the default value of `T`,
+/// as long as its payload format is [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF`] or
+/// [`UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED`]. This is consistent with protobuf's
+/// wire format, which encodes a message that has all fields set to their default values as
+/// an empty byte sequence.
+///
+/// # Examples
+///
+/// ```rust
+/// use protobuf::well_known_types::wrappers::StringValue;
+/// use up_rust::{UAttributes, UListener, UMessageBuilder, UUri};
+/// use up_rust::communication::TypedListener;
+///
+/// # async_std::task::block_on(async {
+/// let listener = TypedListener::new(|value: StringValue, attributes: UAttributes| async move {
+///     println!("received {} from {}", value.value, attributes.source.to_uri(false));
+/// });
+/// // register the listener with a transport ...
+/// # let topic = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+/// # let value = StringValue { value: "hello".to_string(), ..Default::default() };
+/// # listener.on_receive(UMessageBuilder::publish(topic).build_with_protobuf_payload(&value).unwrap()).await;
+/// # });
+/// ```
+pub struct TypedListener<T, F> {
+    callback: F,
+    message_type: PhantomData<fn() -> T>,
+}
+
+impl<T, F, Fut> TypedListener<T, F>
+where
+    T: Message,
+    F: Fn(T, UAttributes) -> Fut + Send + Sync,
+    Fut: Future<Output = ()> + Send,
+{
+    /// Creates a new listener for a callback function.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The function to invoke with the protobuf message extracted from each
+    ///   received message's payload and the received message's attributes.
+    pub fn new(callback: F) -> Self {
+        TypedListener {
+            callback,
+            message_type: PhantomData,
+        }
+    }
+}
+
+fn has_empty_payload(msg: &UMessage) -> bool {
+    msg.payload
+        .as_ref()
+        .map_or(true, |payload| payload.is_empty())
+        && matches!(
+            msg.attributes.payload_format.enum_value_or_default(),
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF | UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED
+        )
+}
+
+#[async_trait]
+impl<T, F, Fut> UListener for TypedListener<T, F>
+where
+    T: Message,
+    F: Fn(T, UAttributes) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    async fn on_receive(&self, msg: UMessage) {
+        let value = if has_empty_payload(&msg) {
+            T::default()
+        } else {
+            match msg.extract_protobuf::<T>() {
+                Ok(value) => value,
+                Err(e) => {
+                    warn!(
+                        "discarding message [{}], failed to extract payload: {}",
+                        msg.summary(),
+                        e
+                    );
+                    return;
+                }
+            }
+        };
+        let attributes = msg.attributes.into_option().unwrap_or_default();
+        (self.callback)(value, attributes).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use protobuf::well_known_types::wrappers::StringValue;
+
+    use crate::{UMessageBuilder, UUri};
+
+    use super::*;
+
+    type Received = Arc<Mutex<Vec<(String, UAttributes)>>>;
+
+    fn recording_listener(received: Received) -> impl UListener {
+        TypedListener::new(move |value: StringValue, attributes: UAttributes| {
+            let received = received.clone();
+            async move {
+                received.lock().unwrap().push((value.value, attributes));
+            }
+        })
+    }
+
+    fn publish_builder() -> UMessageBuilder {
+        UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D").unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_callback_receives_extracted_message() {
+        let received = Received::default();
+        let listener = recording_listener(received.clone());
+        let value = StringValue {
+            value: "hello".to_string(),
+            ..Default::default()
+        };
+        let msg = publish_builder()
+            .build_with_protobuf_payload(&value)
+            .unwrap();
+
+        listener.on_receive(msg.clone()).await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].0, "hello");
+        assert_eq!(received[0].1, *msg.attributes);
+    }
+
+    #[tokio::test]
+    async fn test_callback_receives_default_value_for_empty_payload() {
+        let received = Received::default();
+        let listener = recording_listener(received.clone());
+
+        listener
+            .on_receive(publish_builder().build().unwrap())
+            .await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(received[0].0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_message_with_unsupported_payload_is_discarded() {
+        let received = Received::default();
+        let listener = recording_listener(received.clone());
+        let msg = publish_builder()
+            .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .unwrap();
+
+        listener.on_receive(msg).await;
+
+        assert!(received.lock().unwrap().is_empty());
+    }
+}
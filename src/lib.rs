@@ -25,7 +25,7 @@
 //! * `upayload` module, which defines payload representation for uProtocol messages
 //! * `uri` module, providing convenience wrappers for creation and validation of uProtocol-style resource identifiers
 //! * `ustatus` module, which provices uProtocol types for representing status and status codes
-//! * `utransport` module, as an interface contract between uProtocol and specific transport protocol implementations
+//! * `utransport` module, as an interface contract between uProtocol and specific transport protocol implementations, including an in-memory transport for local communication
 //! * `uuid` module, which generates and validates UUIDs as per the uProtocol specification
 //!
 //! For user convenience, all of these modules export their types on up_rust top-level, except for (future) optional features.
@@ -34,9 +34,16 @@
 //!
 //! Some crate features are made optional, these include:
 //!
+//! * `dynamic` feature, which supports decoding protobuf payloads based on message descriptors that are only known at runtime
+//! * `grpc` feature, which supports converting between `UStatus` and [`google.rpc.Status`](https://github.com/googleapis/googleapis/blob/master/google/rpc/status.proto) for interoperability with gRPC based services
+//! * `gzip` feature, which supports compressing payloads using [gzip](https://www.rfc-editor.org/rfc/rfc1952)
+//! * `json` feature, which supports converting payloads between the binary and the JSON encoding of protobuf messages
+//! * `serde` feature, which provides [serde](https://serde.rs) support for (de-)serializing uProtocol types like `UUri` and `UStatus`
+//! * `trace-messages` feature, which makes the RPC server log a redacted summary of each received request and sent response at trace level, including the request ID for correlating the events
 //! * `udiscovery` feature, which contains the generated protobuf stubs for [uProtocol Core API uDiscovery](https://raw.githubusercontent.com/eclipse-uprotocol/up-spec/main/up-l3/udiscovery/v3/README.adoc)
 //! * `usubscription` feature, which contains the generated protobuf stubs for [uProtocol Core API uSubscription](https://raw.githubusercontent.com/eclipse-uprotocol/up-spec/main/up-l3/usubscription/v3/README.adoc)
 //! * `utwin` feature, which contains the generated protobuf stubs for [uProtocol Core API uTwin](https://raw.githubusercontent.com/eclipse-uprotocol/up-spec/main/up-l3/utwin/v3/README.adoc)
+//! * `zstd` feature, which supports compressing payloads using [Zstandard](https://www.rfc-editor.org/rfc/rfc8878)
 //!
 //! ## References
 //! * [Eclipse-uProtocol Specification](https://github.com/eclipse-uprotocol/up-spec)
@@ -50,19 +57,27 @@ pub use uattributes::{
     NotificationValidator, PublishValidator, RequestValidator, ResponseValidator,
     UAttributesValidator, UAttributesValidators,
 };
-pub use uattributes::{UAttributes, UAttributesError, UMessageType, UPayloadFormat, UPriority};
+pub use uattributes::{
+    UAttributes, UAttributesError, UAttributesPatch, UMessageType, UPayloadFormat, UPriority,
+};
 
 mod umessage;
-pub use umessage::{UMessage, UMessageBuilder, UMessageError};
+pub use umessage::{
+    RequestMetadata, UMessage, UMessageBuilder, UMessageError, REQUEST_CANCELLATION_RESOURCE_ID,
+};
 
 mod uri;
-pub use uri::{UUri, UUriError};
+pub use uri::{ResourceKind, UUri, UUriError};
 
 mod ustatus;
-pub use ustatus::{UCode, UStatus};
+#[cfg(feature = "grpc")]
+pub use ustatus::GoogleRpcStatus;
+pub use ustatus::{ErrorCategory, UCode, UStatus};
 
 mod utransport;
-pub use utransport::{ComparableListener, LocalUriProvider, UListener, UTransport};
+pub use utransport::{
+    ComparableListener, InMemoryTransport, LocalUriProvider, UListener, UTransport,
+};
 mod uuid;
 pub use uuid::UUID;
 
@@ -71,6 +86,12 @@ mod up_core_api {
     include!(concat!(env!("OUT_DIR"), "/uprotocol/mod.rs"));
 }
 
+// protoc-generated stubs for google.rpc types, see build.rs
+#[cfg(feature = "grpc")]
+mod google_rpc {
+    include!(concat!(env!("OUT_DIR"), "/google/mod.rs"));
+}
+
 // Types from up_core_api that we're not re-exporting for now (might change if need arises)
 // pub use up_core_api::file;
 // pub use up_core_api::uprotocol_options;
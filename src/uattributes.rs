@@ -11,7 +11,7 @@
  * SPDX-License-Identifier: Apache-2.0
  ********************************************************************************/
 
-use crate::{UUri, UUID};
+use crate::{UCode, UUri, UUID};
 
 mod uattributesvalidator;
 mod upayloadformat;
@@ -55,7 +55,155 @@ impl std::fmt::Display for UAttributesError {
 
 impl std::error::Error for UAttributesError {}
 
+/// A set of attribute values to replace the values of existing [`UAttributes`] with.
+///
+/// Each field that is set (`Some`) overrides the corresponding attribute, while fields that are
+/// not set (`None`) leave the corresponding attribute unchanged. Consequently, a patch cannot be
+/// used for removing an attribute.
+///
+/// See [`UAttributes::with_overrides`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UAttributesPatch {
+    pub id: Option<UUID>,
+    pub type_: Option<UMessageType>,
+    pub source: Option<UUri>,
+    pub sink: Option<UUri>,
+    pub priority: Option<UPriority>,
+    pub ttl: Option<u32>,
+    pub permission_level: Option<u32>,
+    pub commstatus: Option<UCode>,
+    pub reqid: Option<UUID>,
+    pub token: Option<String>,
+    pub traceparent: Option<String>,
+    pub payload_format: Option<UPayloadFormat>,
+}
+
 impl UAttributes {
+    /// Gets the message's identifier.
+    pub fn message_id(&self) -> Option<&UUID> {
+        self.id.as_ref()
+    }
+
+    /// Gets the address of the entity that has sent the message.
+    pub fn source_uri(&self) -> Option<&UUri> {
+        self.source.as_ref()
+    }
+
+    /// Gets the address of the entity that the message is destined for.
+    pub fn sink_uri(&self) -> Option<&UUri> {
+        self.sink.as_ref()
+    }
+
+    /// Gets the identifier of the request message that the message is correlated with.
+    pub fn request_id(&self) -> Option<&UUID> {
+        self.reqid.as_ref()
+    }
+
+    /// Checks if these are the attributes of a one-way request, i.e. a request message whose
+    /// sender does not expect a response.
+    ///
+    /// See [`UMessageBuilder::request_no_reply`](crate::UMessageBuilder::request_no_reply) for
+    /// the attribute convention used for marking such requests.
+    pub fn is_no_reply_request(&self) -> bool {
+        self.type_.enum_value_or_default() == UMessageType::UMESSAGE_TYPE_REQUEST
+            && self.commstatus == Some(UCode::OK.into())
+    }
+
+    /// Gets the number of milliseconds after which the message expires.
+    ///
+    /// A TTL of 0 is returned as `None`, because it indicates that the message never expires.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UAttributes;
+    ///
+    /// let mut attributes = UAttributes::default();
+    /// assert_eq!(attributes.ttl(), None);
+    /// attributes.ttl = Some(0);
+    /// assert_eq!(attributes.ttl(), None);
+    /// attributes.ttl = Some(5_000);
+    /// assert_eq!(attributes.ttl(), Some(5_000));
+    /// ```
+    pub fn ttl(&self) -> Option<u32> {
+        self.ttl.filter(|ttl| *ttl > 0)
+    }
+
+    /// Creates a copy of these attributes with some of the values replaced.
+    ///
+    /// This is useful for components like gateways which forward messages with some of their
+    /// attributes rewritten.
+    ///
+    /// # Arguments
+    ///
+    /// * `overrides` - The values to replace. Attributes for which the patch contains no value
+    ///   are copied as is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UAttributes, UAttributesPatch, UPriority, UUID, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let attributes = UAttributes::request(
+    ///     UUID::build(),
+    ///     UUri::try_from("//my-vehicle/4210/1/1A50")?,
+    ///     UUri::try_from("//my-cloud/BA4C/1/0")?,
+    ///     None,
+    ///     None,
+    ///     Some(5_000),
+    /// );
+    /// let forwarded = attributes.with_overrides(UAttributesPatch {
+    ///     priority: Some(UPriority::UPRIORITY_CS5),
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(forwarded.priority, UPriority::UPRIORITY_CS5.into());
+    /// assert_eq!(forwarded.id, attributes.id);
+    /// assert_eq!(forwarded.ttl, Some(5_000));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_overrides(&self, overrides: UAttributesPatch) -> UAttributes {
+        let mut attributes = self.clone();
+        if let Some(id) = overrides.id {
+            attributes.id = Some(id).into();
+        }
+        if let Some(message_type) = overrides.type_ {
+            attributes.type_ = message_type.into();
+        }
+        if let Some(source) = overrides.source {
+            attributes.source = Some(source).into();
+        }
+        if let Some(sink) = overrides.sink {
+            attributes.sink = Some(sink).into();
+        }
+        if let Some(priority) = overrides.priority {
+            attributes.priority = priority.into();
+        }
+        if let Some(ttl) = overrides.ttl {
+            attributes.ttl = Some(ttl);
+        }
+        if let Some(permission_level) = overrides.permission_level {
+            attributes.permission_level = Some(permission_level);
+        }
+        if let Some(commstatus) = overrides.commstatus {
+            attributes.commstatus = Some(commstatus.into());
+        }
+        if let Some(reqid) = overrides.reqid {
+            attributes.reqid = Some(reqid).into();
+        }
+        if let Some(token) = overrides.token {
+            attributes.token = Some(token);
+        }
+        if let Some(traceparent) = overrides.traceparent {
+            attributes.traceparent = Some(traceparent);
+        }
+        if let Some(payload_format) = overrides.payload_format {
+            attributes.payload_format = payload_format.into();
+        }
+        attributes
+    }
+
     /// Creates attributes representing an RPC request message.
     ///
     /// The message's priority will be set to [`UPriority::UPRIORITY_CS4`].
@@ -110,3 +258,86 @@ impl UAttributes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accessors_of_request_attributes() {
+        let message_id = UUID::build();
+        let method = UUri::try_from("//my-vehicle/4210/1/1A50").unwrap();
+        let reply_to_address = UUri::try_from("//my-cloud/BA4C/1/0").unwrap();
+        let attributes = UAttributes::request(
+            message_id.clone(),
+            method.clone(),
+            reply_to_address.clone(),
+            None,
+            None,
+            Some(5_000),
+        );
+
+        assert_eq!(attributes.message_id(), Some(&message_id));
+        assert_eq!(attributes.source_uri(), Some(&reply_to_address));
+        assert_eq!(attributes.sink_uri(), Some(&method));
+        assert_eq!(attributes.ttl(), Some(5_000));
+        assert!(attributes.request_id().is_none());
+    }
+
+    #[test]
+    fn test_accessors_of_empty_attributes() {
+        let attributes = UAttributes {
+            ttl: Some(0),
+            ..Default::default()
+        };
+
+        assert!(attributes.message_id().is_none());
+        assert!(attributes.source_uri().is_none());
+        assert!(attributes.sink_uri().is_none());
+        assert!(attributes.request_id().is_none());
+        assert!(attributes.ttl().is_none());
+    }
+
+    #[test]
+    fn test_with_overrides_replaces_given_attributes_only() {
+        let attributes = UAttributes::request(
+            UUID::build(),
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            Some(UPriority::UPRIORITY_CS4),
+            Some("my-token".to_string()),
+            Some(5_000),
+        );
+        let new_sink = UUri::try_from("//other-vehicle/4210/1/1A50").unwrap();
+
+        let patched = attributes.with_overrides(UAttributesPatch {
+            sink: Some(new_sink.clone()),
+            priority: Some(UPriority::UPRIORITY_CS6),
+            ..Default::default()
+        });
+
+        assert_eq!(patched.sink, Some(new_sink).into());
+        assert_eq!(patched.priority, UPriority::UPRIORITY_CS6.into());
+        assert_eq!(patched.id, attributes.id);
+        assert_eq!(patched.source, attributes.source);
+        assert_eq!(patched.type_, attributes.type_);
+        assert_eq!(patched.ttl, attributes.ttl);
+        assert_eq!(patched.token, attributes.token);
+    }
+
+    #[test]
+    fn test_with_empty_overrides_yields_equal_attributes() {
+        let attributes = UAttributes::request(
+            UUID::build(),
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            None,
+            None,
+            Some(5_000),
+        );
+        assert_eq!(
+            attributes.with_overrides(UAttributesPatch::default()),
+            attributes
+        );
+    }
+}
@@ -111,6 +111,145 @@ impl UPayloadFormat {
         let desc_proto = desc.proto();
         crate::up_core_api::uoptions::exts::mime_type.get(desc_proto.options.get_or_default())
     }
+
+    /// Guesses the format of payload data.
+    ///
+    /// This is useful for processing messages that lack the payload format attribute, e.g. in a gateway.
+    /// The detection is best-effort only, based on the following heuristics, which are applied in order:
+    ///
+    /// 1. Empty data results in [`UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED`].
+    /// 2. UTF-8 text that starts with `{` and ends with `}` or that starts with `[` and ends with `]`,
+    ///    ignoring surrounding whitespace, results in [`UPayloadFormat::UPAYLOAD_FORMAT_JSON`].
+    ///    The data is not validated any further.
+    /// 3. Any other UTF-8 text without control characters (except for tabs and line breaks) results in
+    ///    [`UPayloadFormat::UPAYLOAD_FORMAT_TEXT`].
+    /// 4. Data that consists of well-formed protobuf fields results in [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF`].
+    /// 5. All other data results in [`UPayloadFormat::UPAYLOAD_FORMAT_RAW`].
+    ///
+    /// Note that the detected format might differ from the actual format, e.g. because binary data happens to
+    /// look like a protobuf message. Senders should therefore always set the payload format explicitly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UPayloadFormat;
+    ///
+    /// assert_eq!(UPayloadFormat::detect(br#"{"temp": 21}"#), UPayloadFormat::UPAYLOAD_FORMAT_JSON);
+    /// assert_eq!(UPayloadFormat::detect(b"hello"), UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+    /// assert_eq!(UPayloadFormat::detect(&[0x08, 0x96, 0x01]), UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF);
+    /// assert_eq!(UPayloadFormat::detect(&[]), UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED);
+    /// ```
+    pub fn detect(bytes: &[u8]) -> UPayloadFormat {
+        if bytes.is_empty() {
+            return UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED;
+        }
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            let trimmed = text.trim();
+            if (trimmed.starts_with('{') && trimmed.ends_with('}'))
+                || (trimmed.starts_with('[') && trimmed.ends_with(']'))
+            {
+                return UPayloadFormat::UPAYLOAD_FORMAT_JSON;
+            }
+            if !text
+                .chars()
+                .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+            {
+                return UPayloadFormat::UPAYLOAD_FORMAT_TEXT;
+            }
+        }
+        if is_protobuf_wire_format(bytes) {
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF
+        } else {
+            UPayloadFormat::UPAYLOAD_FORMAT_RAW
+        }
+    }
+
+    /// Checks if payload data in this format consists of human readable text.
+    ///
+    /// This is the case for [`UPayloadFormat::UPAYLOAD_FORMAT_JSON`] and [`UPayloadFormat::UPAYLOAD_FORMAT_TEXT`].
+    /// All other formats, including [`UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED`], are considered binary.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UPayloadFormat;
+    ///
+    /// assert!(UPayloadFormat::UPAYLOAD_FORMAT_JSON.is_text());
+    /// assert!(!UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF.is_text());
+    /// ```
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            UPayloadFormat::UPAYLOAD_FORMAT_JSON | UPayloadFormat::UPAYLOAD_FORMAT_TEXT
+        )
+    }
+
+    /// Checks if payload data in this format is binary data, i.e. it is not [text](Self::is_text).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UPayloadFormat;
+    ///
+    /// assert!(UPayloadFormat::UPAYLOAD_FORMAT_RAW.is_binary());
+    /// assert!(!UPayloadFormat::UPAYLOAD_FORMAT_TEXT.is_binary());
+    /// ```
+    pub fn is_binary(&self) -> bool {
+        !self.is_text()
+    }
+}
+
+// reads a base 128 varint, returning the value and the number of bytes consumed
+fn read_varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (index, byte) in bytes.iter().take(10).enumerate() {
+        value |= u64::from(byte & 0x7F) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+    None
+}
+
+// checks if data consists of a sequence of well-formed protobuf fields
+fn is_protobuf_wire_format(bytes: &[u8]) -> bool {
+    let mut remaining = bytes;
+    while !remaining.is_empty() {
+        let Some((tag, tag_len)) = read_varint(remaining) else {
+            return false;
+        };
+        if tag >> 3 == 0 {
+            // field numbers start at 1
+            return false;
+        }
+        remaining = &remaining[tag_len..];
+        let value_len = match tag & 0x07 {
+            // VARINT
+            0 => match read_varint(remaining) {
+                Some((_, len)) => len,
+                None => return false,
+            },
+            // I64
+            1 => 8,
+            // LEN
+            2 => match read_varint(remaining) {
+                Some((len, prefix_len)) => match usize::try_from(len) {
+                    Ok(len) => prefix_len.saturating_add(len),
+                    Err(_) => return false,
+                },
+                None => return false,
+            },
+            // I32
+            5 => 4,
+            // groups are deprecated and not supported
+            _ => return false,
+        };
+        if value_len > remaining.len() {
+            return false;
+        }
+        remaining = &remaining[value_len..];
+    }
+    true
 }
 
 #[cfg(test)]
@@ -160,4 +299,53 @@ mod tests {
     fn test_to_media_type(format: UPayloadFormat, expected_media_type: Option<String>) {
         assert_eq!(format.to_media_type(), expected_media_type);
     }
+
+    #[test_case(br#"{"temperature": 21.5}"#, UPayloadFormat::UPAYLOAD_FORMAT_JSON; "for JSON object")]
+    #[test_case(b" [1, 2, 3]\n", UPayloadFormat::UPAYLOAD_FORMAT_JSON; "for JSON array with whitespace")]
+    #[test_case(b"hello world", UPayloadFormat::UPAYLOAD_FORMAT_TEXT; "for text")]
+    #[test_case("gr\u{fc}\u{df}e\r\n\tand {braces}".as_bytes(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT; "for multi-line UTF-8 text")]
+    #[test_case(&[0x08, 0x96, 0x01, 0x12, 0x02, 0x68, 0x69], UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF; "for protobuf message")]
+    #[test_case(&[0x00, 0xFF, 0x10, 0x80], UPayloadFormat::UPAYLOAD_FORMAT_RAW; "for binary data")]
+    #[test_case(&[0x12, 0x05, 0x68], UPayloadFormat::UPAYLOAD_FORMAT_RAW; "for truncated protobuf message")]
+    #[test_case(&[], UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED; "for empty data")]
+    fn test_detect(bytes: &[u8], expected_format: UPayloadFormat) {
+        assert_eq!(UPayloadFormat::detect(bytes), expected_format);
+    }
+
+    #[test]
+    fn test_detect_serialized_protobuf_message() {
+        use protobuf::Message;
+
+        let status = crate::UStatus::fail_with_code(crate::UCode::NOT_FOUND, "no such entity");
+        let bytes = status.write_to_bytes().unwrap();
+        assert_eq!(
+            UPayloadFormat::detect(&bytes),
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF
+        );
+    }
+
+    #[test]
+    fn test_is_text_and_is_binary() {
+        use protobuf::Enum;
+
+        UPayloadFormat::VALUES.iter().for_each(|format| {
+            let expected_text = matches!(
+                format,
+                UPayloadFormat::UPAYLOAD_FORMAT_JSON | UPayloadFormat::UPAYLOAD_FORMAT_TEXT
+            );
+            assert_eq!(
+                format.is_text(),
+                expected_text,
+                "unexpected result for {:?}",
+                format
+            );
+            assert_eq!(
+                format.is_binary(),
+                !expected_text,
+                "unexpected result for {:?}",
+                format
+            );
+        });
+        assert!(UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED.is_binary());
+    }
 }
@@ -11,7 +11,7 @@
  * SPDX-License-Identifier: Apache-2.0
  ********************************************************************************/
 
-use protobuf::EnumFull;
+use protobuf::{Enum, EnumFull};
 
 use crate::uattributes::UAttributesError;
 pub use crate::up_core_api::uattributes::UPriority;
@@ -79,4 +79,95 @@ impl UPriority {
             })
             .ok_or_else(|| UAttributesError::parsing_error(format!("unknown priority [{}]", prio)))
     }
+
+    /// Gets the numeric QoS class that this priority corresponds to.
+    ///
+    /// The class is the number of the priority's class of service, i.e. `CS0` to `CS6` are mapped
+    /// to `0` to `6`. This allows transport implementations to map priorities to the QoS levels of
+    /// the underlying protocol without matching on the individual variants.
+    ///
+    /// [`UPriority::UPRIORITY_UNSPECIFIED`] is mapped to the class of the default priority `CS1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use up_rust::UPriority;
+    ///
+    /// assert_eq!(UPriority::UPRIORITY_CS0.qos_class(), 0);
+    /// assert_eq!(UPriority::UPRIORITY_CS4.qos_class(), 4);
+    /// assert_eq!(UPriority::UPRIORITY_UNSPECIFIED.qos_class(), 1);
+    /// ```
+    pub fn qos_class(&self) -> u8 {
+        match self {
+            UPriority::UPRIORITY_UNSPECIFIED => UPriority::UPRIORITY_CS1.qos_class(),
+            // CS0 to CS6 have consecutive values starting at 1
+            _ => (self.value() - UPriority::UPRIORITY_CS0.value()) as u8,
+        }
+    }
+
+    /// Checks if this priority is at least as high as another priority.
+    ///
+    /// Priorities are ordered by their [QoS class](Self::qos_class), i.e. `CS0` is the lowest and
+    /// `CS6` is the highest priority. [`UPriority::UPRIORITY_UNSPECIFIED`] is considered to be equal
+    /// to the default priority `CS1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use up_rust::UPriority;
+    ///
+    /// assert!(UPriority::UPRIORITY_CS5.is_at_least(UPriority::UPRIORITY_CS4));
+    /// assert!(UPriority::UPRIORITY_CS4.is_at_least(UPriority::UPRIORITY_CS4));
+    /// assert!(!UPriority::UPRIORITY_CS3.is_at_least(UPriority::UPRIORITY_CS4));
+    /// assert!(UPriority::UPRIORITY_UNSPECIFIED.is_at_least(UPriority::UPRIORITY_CS1));
+    /// ```
+    pub fn is_at_least(&self, other: UPriority) -> bool {
+        self.qos_class() >= other.qos_class()
+    }
+
+    /// Gets the priority for a numeric QoS class.
+    ///
+    /// This is the inverse of [`UPriority::qos_class`].
+    ///
+    /// # Returns
+    ///
+    /// The priority or `None` if the class is greater than `6`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use up_rust::UPriority;
+    ///
+    /// assert_eq!(UPriority::from_qos_class(4), Some(UPriority::UPRIORITY_CS4));
+    /// assert!(UPriority::from_qos_class(7).is_none());
+    /// ```
+    pub fn from_qos_class(qos_class: u8) -> Option<Self> {
+        UPriority::from_i32(UPriority::UPRIORITY_CS0.value() + i32::from(qos_class))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qos_class_round_trip() {
+        for priority in UPriority::VALUES
+            .iter()
+            .filter(|priority| **priority != UPriority::UPRIORITY_UNSPECIFIED)
+        {
+            let qos_class = priority.qos_class();
+            assert_eq!(
+                priority.to_priority_code(),
+                format!("CS{}", qos_class),
+                "unexpected QoS class for {:?}",
+                priority
+            );
+            assert_eq!(UPriority::from_qos_class(qos_class), Some(*priority));
+        }
+        assert_eq!(
+            UPriority::from_qos_class(UPriority::UPRIORITY_UNSPECIFIED.qos_class()),
+            Some(UPriority::UPRIORITY_CS1)
+        );
+    }
 }
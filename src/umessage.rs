@@ -21,7 +21,9 @@ pub use umessagebuilder::*;
 
 pub use crate::up_core_api::umessage::UMessage;
 
-use crate::{UAttributesError, UPayloadFormat};
+use crate::{
+    UAttributes, UAttributesError, UAttributesValidators, UPayloadFormat, UPriority, UUri, UUID,
+};
 
 #[derive(Debug)]
 pub enum UMessageError {
@@ -65,7 +67,387 @@ impl From<&str> for UMessageError {
     }
 }
 
+/// Checks if payload data is plausibly encoded in a given format.
+///
+/// This catches the most common mix-ups, e.g. protobuf encoded data tagged as JSON,
+/// without fully parsing the data.
+pub(crate) fn verify_payload_format(
+    format: UPayloadFormat,
+    data: &[u8],
+) -> Result<(), UMessageError> {
+    match format {
+        UPayloadFormat::UPAYLOAD_FORMAT_TEXT | UPayloadFormat::UPAYLOAD_FORMAT_JSON => {
+            let text = std::str::from_utf8(data).map_err(|_| {
+                UMessageError::PayloadError(format!(
+                    "Payload declared as {:?} is not valid UTF-8",
+                    format
+                ))
+            })?;
+            // JSON allows control characters only as (insignificant) whitespace
+            if format == UPayloadFormat::UPAYLOAD_FORMAT_JSON
+                && text
+                    .chars()
+                    .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+            {
+                return Err(UMessageError::from(
+                    "Payload declared as JSON contains control characters",
+                ));
+            }
+            Ok(())
+        }
+        UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY => {
+            Any::parse_from_bytes(data).map(|_| ()).map_err(|e| {
+                UMessageError::PayloadError(format!(
+                    "Payload declared as protobuf wrapped in Any cannot be parsed: {}",
+                    e
+                ))
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The metadata of a [`UMessage`] that is relevant for processing a request.
+///
+/// The metadata borrows from the message's attributes, so that it can be inspected, e.g. by
+/// interceptors, without copying the attributes or touching the payload.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RequestMetadata<'a> {
+    /// The address of the entity that has sent the message.
+    pub source: Option<&'a UUri>,
+    /// The address of the entity that the message is destined for.
+    pub sink: Option<&'a UUri>,
+    /// The message's identifier.
+    pub id: Option<&'a UUID>,
+    /// The number of milliseconds after which the message expires.
+    pub ttl: Option<u32>,
+    /// The message's priority.
+    pub priority: UPriority,
+    /// The format of the message's payload.
+    pub payload_format: UPayloadFormat,
+}
+
 impl UMessage {
+    /// Gets the [traceparent](https://w3c.github.io/trace-context/#traceparent-header) value
+    /// that correlates this message with a distributed trace.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    /// let message = UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D")?)
+    ///                    .with_traceparent(traceparent)
+    ///                    .build()?;
+    /// assert_eq!(message.traceparent(), Some(traceparent));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn traceparent(&self) -> Option<&str> {
+        self.attributes
+            .as_ref()
+            .and_then(|attribs| attribs.traceparent.as_deref())
+    }
+
+    /// Gets the message's identifier.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let message = UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D")?).build()?;
+    /// assert_eq!(message.message_id(), message.attributes.id.as_ref());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn message_id(&self) -> Option<&UUID> {
+        self.attributes.as_ref().and_then(UAttributes::message_id)
+    }
+
+    /// Gets the address of the entity that has sent this message.
+    pub fn source_uri(&self) -> Option<&UUri> {
+        self.attributes.as_ref().and_then(UAttributes::source_uri)
+    }
+
+    /// Gets the address of the entity that this message is destined for.
+    pub fn sink_uri(&self) -> Option<&UUri> {
+        self.attributes.as_ref().and_then(UAttributes::sink_uri)
+    }
+
+    /// Gets the address that a response to this (request) message needs to be sent to.
+    ///
+    /// The response target is this message's source address with the resource ID set to
+    /// [`UUri::RPC_RESPONSE_ID`].
+    ///
+    /// # Returns
+    ///
+    /// `None` if this message does not have a source address.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let request = UMessageBuilder::request(
+    ///     UUri::try_from("//my-vehicle/4210/1/1A50")?,
+    ///     UUri::try_from("//my-cloud/BA4C/1/0")?,
+    ///     5_000,
+    /// )
+    /// .build()?;
+    /// assert_eq!(request.response_target(), Some(UUri::try_from("//my-cloud/BA4C/1/0")?));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn response_target(&self) -> Option<UUri> {
+        self.source_uri().map(|source| UUri {
+            resource_id: UUri::RPC_RESPONSE_ID,
+            ..source.to_owned()
+        })
+    }
+
+    /// Gets the identifier of the request message that this message is correlated with.
+    pub fn request_id(&self) -> Option<&UUID> {
+        self.attributes.as_ref().and_then(UAttributes::request_id)
+    }
+
+    /// Gets the number of milliseconds after which this message expires.
+    ///
+    /// See [`UAttributes::ttl()`].
+    pub fn ttl(&self) -> Option<u32> {
+        self.attributes.as_ref().and_then(UAttributes::ttl)
+    }
+
+    /// Gets the metadata of this message that is relevant for processing a request.
+    ///
+    /// The metadata refers to this message's attributes. Neither the attributes nor the payload
+    /// are copied.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UPriority, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let method_to_invoke = UUri::try_from("//my-vehicle/4210/1/1A50")?;
+    /// let request = UMessageBuilder::request(
+    ///     method_to_invoke.clone(),
+    ///     UUri::try_from("//my-cloud/BA4C/1/0")?,
+    ///     5_000,
+    /// )
+    /// .build()?;
+    /// let metadata = request.metadata();
+    /// assert_eq!(metadata.sink, Some(&method_to_invoke));
+    /// assert_eq!(metadata.priority, UPriority::UPRIORITY_CS4);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn metadata(&self) -> RequestMetadata<'_> {
+        let attributes = self.attributes.get_or_default();
+        RequestMetadata {
+            source: attributes.source_uri(),
+            sink: attributes.sink_uri(),
+            id: attributes.message_id(),
+            ttl: attributes.ttl(),
+            priority: attributes.priority.enum_value_or_default(),
+            payload_format: attributes.payload_format.enum_value_or_default(),
+        }
+    }
+
+    /// Gets the number of bytes that this message occupies in its protobuf wire format.
+    ///
+    /// The size is computed without serializing the message, so transports with a limited
+    /// maximum transmission unit can use it to reject or fragment oversized messages early.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use protobuf::Message;
+    /// use up_rust::{UMessageBuilder, UPayloadFormat, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let message = UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D")?)
+    ///                    .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)?;
+    /// assert_eq!(message.serialized_size(), message.write_to_bytes()?.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn serialized_size(&self) -> usize {
+        self.compute_size() as usize
+    }
+
+    /// Creates a single line, human readable summary of this message for debugging purposes.
+    ///
+    /// The summary contains the message's type, source and sink address, ID, TTL and the
+    /// length and format of its payload. The payload itself is not included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let message = UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D")?).build()?;
+    /// assert!(message.summary().starts_with("PUBLISH //my-vehicle/4210/1/B24D id="));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn summary(&self) -> String {
+        use protobuf::EnumFull;
+        use std::fmt::Write;
+
+        let attributes = self.attributes.get_or_default();
+        let mut summary = String::with_capacity(128);
+        let message_type = attributes.type_.enum_value_or_default();
+        summary.push_str(
+            message_type
+                .descriptor()
+                .name()
+                .trim_start_matches("UMESSAGE_TYPE_"),
+        );
+        if let Some(source) = attributes.source.as_ref() {
+            let _ = write!(summary, " {}", source.to_uri(false));
+        }
+        if let Some(sink) = attributes.sink.as_ref() {
+            let _ = write!(summary, " -> {}", sink.to_uri(false));
+        }
+        if let Some(id) = attributes.id.as_ref() {
+            let _ = write!(summary, " id={}", id.to_hyphenated_string());
+        }
+        if let Some(ttl) = attributes.ttl {
+            let _ = write!(summary, " ttl={}ms", ttl);
+        }
+        match self.payload.as_ref() {
+            Some(payload) => {
+                let _ = write!(
+                    summary,
+                    " payload={}B/{}",
+                    payload.len(),
+                    attributes
+                        .payload_format
+                        .enum_value_or_default()
+                        .descriptor()
+                        .name()
+                        .trim_start_matches("UPAYLOAD_FORMAT_")
+                );
+            }
+            None => summary.push_str(" payload=none"),
+        }
+        summary
+    }
+
+    /// Creates a copy of this message that does not contain any payload.
+    ///
+    /// All attributes, including the payload format, are retained. This is useful for logging
+    /// messages whose payload may contain sensitive data, e.g. personally identifiable information.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UPayloadFormat, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let message = UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D")?)
+    ///     .build_with_payload("VIN 1234", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)?;
+    /// let redacted = message.redacted();
+    /// assert!(redacted.payload.is_none());
+    /// assert_eq!(redacted.attributes, message.attributes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn redacted(&self) -> UMessage {
+        UMessage {
+            attributes: self.attributes.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a copy of this message that has a newly created message ID.
+    ///
+    /// All other attributes, including the request ID of response messages, as well as the
+    /// payload are retained. This is useful when re-transmitting or forwarding a message
+    /// which needs to be distinguishable from the original.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let message = UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D")?).build()?;
+    /// let copy = message.with_new_id();
+    /// assert_ne!(copy.attributes.id, message.attributes.id);
+    /// assert_eq!(copy.attributes.source, message.attributes.source);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_new_id(&self) -> UMessage {
+        let mut message = self.clone();
+        message.attributes.mut_or_insert_default().id = Some(crate::UUID::build()).into();
+        message
+    }
+
+    /// Checks if this message is consistent.
+    ///
+    /// The message's attributes are checked using the [validator](UAttributesValidators::get_validator_for_attributes)
+    /// for the message's type. Additionally, the payload is checked for consistency with the declared payload format:
+    ///
+    /// * A message that declares a payload format other than [`UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED`]
+    ///   must contain a non-empty payload.
+    /// * A message must not declare an unknown payload format.
+    /// * The payload must plausibly be encoded in the declared format, i.e. text and JSON payloads must be
+    ///   valid UTF-8, JSON payloads must not contain any control characters other than whitespace and
+    ///   payloads declared as [`UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY`] must contain
+    ///   a `google.protobuf.Any`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`UMessageError::AttributesValidationError`] if the message has no attributes or if the
+    /// attributes are invalid. Returns a [`UMessageError::PayloadError`] if the payload is not consistent
+    /// with the declared payload format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UPayloadFormat, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut message = UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D")?)
+    ///                    .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)?;
+    /// assert!(message.validate().is_ok());
+    ///
+    /// message.payload = None;
+    /// assert!(message.validate().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate(&self) -> Result<(), UMessageError> {
+        let Some(attributes) = self.attributes.as_ref() else {
+            return Err(UMessageError::AttributesValidationError(
+                UAttributesError::validation_error("Message has no attributes"),
+            ));
+        };
+        UAttributesValidators::get_validator_for_attributes(attributes).validate(attributes)?;
+
+        let payload = self.payload.as_ref().filter(|payload| !payload.is_empty());
+        match (attributes.payload_format.enum_value(), payload) {
+            (Err(unknown_format), _) => Err(UMessageError::PayloadError(format!(
+                "Message declares unknown payload format [{}]",
+                unknown_format
+            ))),
+            (Ok(UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED), _) => Ok(()),
+            (Ok(format), None) => Err(UMessageError::PayloadError(format!(
+                "Message declares payload format {:?} but has no payload",
+                format
+            ))),
+            (Ok(format), Some(data)) => verify_payload_format(format, data),
+        }
+    }
+
     /// If `UMessage` payload is available, deserialize it as a protobuf `Message`.
     ///
     /// This function is used to extract strongly-typed data from a `UMessage` object,
@@ -122,3 +504,243 @@ pub(crate) fn deserialize_protobuf_bytes<T: Message + Default>(
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use crate::{UMessageBuilder, UUri};
+
+    use super::*;
+
+    #[test]
+    fn test_response_target_has_resource_id_zero() {
+        let source = UUri::try_from("//my-cloud/BA4C/1/B24D").unwrap();
+        let message = UMessageBuilder::publish(source.clone()).build().unwrap();
+
+        let response_target = message.response_target().unwrap();
+
+        assert_eq!(response_target.resource_id, UUri::RPC_RESPONSE_ID);
+        assert_eq!(
+            response_target,
+            UUri {
+                resource_id: 0,
+                ..source
+            }
+        );
+    }
+
+    #[test]
+    fn test_response_target_of_message_without_source() {
+        assert!(UMessage::default().response_target().is_none());
+    }
+
+    #[test]
+    fn test_metadata_of_request_message() {
+        let method_to_invoke = UUri::try_from("//my-vehicle/4210/1/1A50").unwrap();
+        let reply_to_address = UUri::try_from("//my-cloud/BA4C/1/0").unwrap();
+        let request =
+            UMessageBuilder::request(method_to_invoke.clone(), reply_to_address.clone(), 5_000)
+                .with_priority(UPriority::UPRIORITY_CS5)
+                .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+                .unwrap();
+
+        let metadata = request.metadata();
+
+        assert_eq!(metadata.source, Some(&reply_to_address));
+        assert_eq!(metadata.sink, Some(&method_to_invoke));
+        assert_eq!(metadata.id, request.attributes.id.as_ref());
+        assert_eq!(metadata.ttl, Some(5_000));
+        assert_eq!(metadata.priority, UPriority::UPRIORITY_CS5);
+        assert_eq!(
+            metadata.payload_format,
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT
+        );
+    }
+
+    #[test]
+    fn test_metadata_of_message_without_attributes() {
+        let message = UMessage::default();
+
+        let metadata = message.metadata();
+
+        assert!(metadata.source.is_none());
+        assert!(metadata.sink.is_none());
+        assert!(metadata.id.is_none());
+        assert!(metadata.ttl.is_none());
+    }
+
+    #[test]
+    fn test_redacted_message_has_no_payload() {
+        let message = UMessageBuilder::request(
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .with_token("my-token")
+        .build_with_payload("VIN 1234", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        .unwrap();
+
+        let redacted = message.redacted();
+
+        assert!(redacted.payload.is_none());
+        assert_eq!(redacted.attributes, message.attributes);
+        assert_eq!(
+            redacted.attributes.payload_format,
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT.into()
+        );
+    }
+
+    #[test]
+    fn test_accessors_of_response_message() {
+        let request = UMessageBuilder::request(
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .build()
+        .unwrap();
+        let response = UMessageBuilder::response_for_request(&request.attributes)
+            .build()
+            .unwrap();
+
+        assert_eq!(response.message_id(), response.attributes.id.as_ref());
+        assert_eq!(response.source_uri(), request.sink_uri());
+        assert_eq!(response.sink_uri(), request.source_uri());
+        assert_eq!(response.request_id(), request.message_id());
+        assert_eq!(request.ttl(), Some(5_000));
+        assert!(request.request_id().is_none());
+    }
+
+    #[test]
+    fn test_accessors_of_message_without_attributes() {
+        let message = UMessage::default();
+
+        assert!(message.message_id().is_none());
+        assert!(message.source_uri().is_none());
+        assert!(message.sink_uri().is_none());
+        assert!(message.request_id().is_none());
+        assert!(message.ttl().is_none());
+    }
+
+    #[test]
+    fn test_summary_of_request_message() {
+        let message_id = crate::UUID::build();
+        let message = UMessageBuilder::request(
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        )
+        .with_message_id(message_id.clone())
+        .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        .unwrap();
+
+        assert_eq!(
+            message.summary(),
+            format!(
+                "REQUEST //my-cloud/BA4C/1/0 -> //my-vehicle/4210/1/1A50 id={} ttl=5000ms payload=5B/TEXT",
+                message_id.to_hyphenated_string()
+            )
+        );
+    }
+
+    fn publish_message(
+        payload: Option<&'static [u8]>,
+        format: protobuf::EnumOrUnknown<UPayloadFormat>,
+    ) -> UMessage {
+        let mut message =
+            UMessageBuilder::publish(UUri::try_from("//my-vehicle/4210/1/B24D").unwrap())
+                .build()
+                .unwrap();
+        message.payload = payload.map(Bytes::from_static);
+        message.attributes.as_mut().unwrap().payload_format = format;
+        message
+    }
+
+    #[test_case(None, UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED.into(), true; "for no payload and unspecified format")]
+    #[test_case(Some(b"hello"), UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED.into(), true; "for payload and unspecified format")]
+    #[test_case(Some(b"hello"), UPayloadFormat::UPAYLOAD_FORMAT_TEXT.into(), true; "for text payload")]
+    #[test_case(Some(&[0x00, 0x01]), UPayloadFormat::UPAYLOAD_FORMAT_RAW.into(), true; "for raw payload")]
+    #[test_case(None, UPayloadFormat::UPAYLOAD_FORMAT_TEXT.into(), false; "for missing payload")]
+    #[test_case(Some(b""), UPayloadFormat::UPAYLOAD_FORMAT_JSON.into(), false; "for empty payload")]
+    #[test_case(Some(&[0xC3, 0x28]), UPayloadFormat::UPAYLOAD_FORMAT_TEXT.into(), false; "for text payload with invalid UTF-8")]
+    #[test_case(Some(&[0xC3, 0x28]), UPayloadFormat::UPAYLOAD_FORMAT_JSON.into(), false; "for JSON payload with invalid UTF-8")]
+    #[test_case(Some(b"{\"name\": \"abc\"}\n"), UPayloadFormat::UPAYLOAD_FORMAT_JSON.into(), true; "for JSON payload")]
+    #[test_case(Some(b"\x0a\x03abc"), UPayloadFormat::UPAYLOAD_FORMAT_JSON.into(), false; "for protobuf payload tagged as JSON")]
+    #[test_case(Some(&[0xFF, 0xFF]), UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY.into(), false; "for invalid Any payload")]
+    #[test_case(Some(b"hello"), protobuf::EnumOrUnknown::from_i32(4711), false; "for payload with unknown format")]
+    #[test_case(None, protobuf::EnumOrUnknown::from_i32(4711), false; "for no payload and unknown format")]
+    fn test_validate_checks_payload_consistency(
+        payload: Option<&'static [u8]>,
+        format: protobuf::EnumOrUnknown<UPayloadFormat>,
+        expected_valid: bool,
+    ) {
+        let message = publish_message(payload, format);
+        assert_eq!(message.validate().is_ok(), expected_valid);
+        if !expected_valid {
+            assert!(matches!(
+                message.validate(),
+                Err(UMessageError::PayloadError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_checks_attributes() {
+        let mut message = publish_message(None, UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED.into());
+        message.attributes.as_mut().unwrap().id.clear();
+        assert!(matches!(
+            message.validate(),
+            Err(UMessageError::AttributesValidationError(_))
+        ));
+
+        message.attributes.clear();
+        assert!(matches!(
+            message.validate(),
+            Err(UMessageError::AttributesValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_with_new_id_only_replaces_message_id() {
+        let request_id = crate::UUID::build();
+        let message = UMessageBuilder::response(
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            request_id.clone(),
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+        )
+        .build_with_payload("hello", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+        .unwrap();
+
+        let mut copy = message.with_new_id();
+        assert!(copy.attributes.id.is_some());
+        assert_ne!(copy.attributes.id, message.attributes.id);
+        assert_eq!(copy.attributes.reqid, Some(request_id).into());
+
+        copy.attributes.as_mut().unwrap().id = message.attributes.id.clone();
+        assert_eq!(copy, message);
+    }
+
+    #[test_case(None; "for message without payload")]
+    #[test_case(Some(vec![0xAB; 16]); "for message with small payload")]
+    #[test_case(Some(vec![0xAB; 70_000]); "for message with large payload")]
+    fn test_serialized_size_matches_encoded_length(payload: Option<Vec<u8>>) {
+        let mut builder = UMessageBuilder::request(
+            UUri::try_from("//my-vehicle/4210/1/1A50").unwrap(),
+            UUri::try_from("//my-cloud/BA4C/1/0").unwrap(),
+            5_000,
+        );
+        builder.with_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01");
+        let message = match payload {
+            Some(data) => builder
+                .build_with_payload(data, UPayloadFormat::UPAYLOAD_FORMAT_RAW)
+                .unwrap(),
+            None => builder.build().unwrap(),
+        };
+
+        assert_eq!(
+            message.serialized_size(),
+            message.write_to_bytes().unwrap().len()
+        );
+    }
+}
@@ -15,19 +15,28 @@ use bytes::Bytes;
 use protobuf::{well_known_types::any::Any, Enum, EnumOrUnknown, Message, MessageFull};
 
 use crate::uattributes::NotificationValidator;
+use crate::umessage::verify_payload_format;
 use crate::{
     PublishValidator, RequestValidator, ResponseValidator, UAttributes, UAttributesValidator,
-    UCode, UMessage, UMessageError, UMessageType, UPayloadFormat, UPriority, UUri, UUID,
+    UCode, UMessage, UMessageError, UMessageType, UPayloadFormat, UPriority, UStatus, UUri, UUID,
 };
 
 const PRIORITY_DEFAULT: UPriority = UPriority::UPRIORITY_CS1;
 
+/// The resource ID that the source of a [request cancellation](UMessageBuilder::request_cancellation)
+/// message refers to.
+///
+/// The resource ID is a topic ID that is reserved for cancellation messages. uEntities must not use it
+/// for publishing any other events or notifications.
+pub const REQUEST_CANCELLATION_RESOURCE_ID: u16 = 0xFFFE;
+
 /// A builder for creating [`UMessage`]s.
 ///
 /// Messages are being used by a uEntity to inform other entities about the occurrence of events
 /// and/or to invoke service operations provided by other entities.
 pub struct UMessageBuilder {
     comm_status: Option<EnumOrUnknown<UCode>>,
+    deadline: Option<u64>,
     message_id: Option<UUID>,
     message_type: UMessageType,
     payload: Option<Bytes>,
@@ -35,6 +44,7 @@ pub struct UMessageBuilder {
     permission_level: Option<u32>,
     priority: UPriority,
     request_id: Option<UUID>,
+    response_format: Option<UPayloadFormat>,
     sink: Option<UUri>,
     source: Option<UUri>,
     token: Option<String>,
@@ -47,6 +57,7 @@ impl Default for UMessageBuilder {
     fn default() -> Self {
         UMessageBuilder {
             comm_status: None,
+            deadline: None,
             message_id: None,
             message_type: UMessageType::UMESSAGE_TYPE_UNSPECIFIED,
             payload: None,
@@ -54,6 +65,7 @@ impl Default for UMessageBuilder {
             permission_level: None,
             priority: UPriority::UPRIORITY_UNSPECIFIED,
             request_id: None,
+            response_format: None,
             sink: None,
             source: None,
             token: None,
@@ -178,6 +190,52 @@ impl UMessageBuilder {
         }
     }
 
+    /// Gets a builder for creating one-way RPC *request* messages.
+    ///
+    /// A one-way request invokes a service's method just like a regular request, but the sender
+    /// does not expect the service to reply with a response message. This is useful for
+    /// point-to-point interactions with a particular method for which the outcome is of no
+    /// interest to the sender.
+    ///
+    /// A one-way request is an RPC request message that has its `commstatus` attribute set to
+    /// [`UCode::OK`]. Regular request messages never carry a `commstatus`, so service providers
+    /// that are not aware of this convention simply process the request and send a response
+    /// which will then be ignored by the sender. Service providers can use
+    /// [`UAttributes::is_no_reply_request`] to determine if a response is expected.
+    ///
+    /// The builder will be initialized with [`UPriority::UPRIORITY_CS4`].
+    ///
+    /// # Arguments
+    ///
+    /// * `method_to_invoke` - The URI identifying the method to invoke.
+    /// * `source` - The URI of the uEntity sending the request. Its resource ID must be 0, just
+    ///   like a regular request's reply-to address.
+    /// * `ttl` - The number of milliseconds after which the request should no longer be processed
+    ///   by the target service. The value is capped at [`i32::MAX`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UCode, UMessageBuilder, UMessageType, UPayloadFormat, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let method_to_invoke = UUri::try_from("//my-vehicle/4210/5/64AB")?;
+    /// let source = UUri::try_from("//my-cloud/BA4C/1/0")?;
+    /// let message = UMessageBuilder::request_no_reply(method_to_invoke.clone(), source.clone(), 5000)
+    ///                    .build_with_payload("flash lights", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)?;
+    /// assert_eq!(message.attributes.type_, UMessageType::UMESSAGE_TYPE_REQUEST.into());
+    /// assert_eq!(message.attributes.commstatus, Some(UCode::OK.into()));
+    /// assert!(message.attributes.is_no_reply_request());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_no_reply(method_to_invoke: UUri, source: UUri, ttl: u32) -> UMessageBuilder {
+        UMessageBuilder {
+            comm_status: Some(UCode::OK.into()),
+            ..UMessageBuilder::request(method_to_invoke, source, ttl)
+        }
+    }
+
     /// Gets a builder for creating RPC *response* messages.
     ///
     /// A response message is used to send the outcome of processing a request message
@@ -279,6 +337,125 @@ impl UMessageBuilder {
         }
     }
 
+    /// Creates an RPC *response* message that indicates the failure to process a *request*.
+    ///
+    /// The message's `commstatus` is set to the given status' code and the status is included
+    /// as the message's payload. The message's source, sink and request ID are set based on the
+    /// request's attributes, as done by [`UMessageBuilder::response_for_request`]. The request's
+    /// traceparent is retained. The message has the same priority as the request, unless the
+    /// request's priority is not valid for a response message, in which case
+    /// [`UPriority::UPRIORITY_CS4`] is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_attributes` - The attributes from the request message.
+    /// * `status` - The status indicating the reason for the failure.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request attributes lack the information required for creating
+    /// a valid response message, e.g. the reply-to address.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UCode, UMessageBuilder, UStatus, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let request_message = UMessageBuilder::request(
+    ///     UUri::try_from("//my-vehicle/4210/5/64AB")?,
+    ///     UUri::try_from("//my-cloud/BA4C/1/0")?,
+    ///     5000,
+    /// )
+    /// .build()?;
+    ///
+    /// let response_message = UMessageBuilder::error_response(
+    ///     &request_message.attributes,
+    ///     &UStatus::not_found("no such door"),
+    /// )?;
+    /// assert_eq!(response_message.attributes.commstatus, Some(UCode::NOT_FOUND.into()));
+    /// assert_eq!(response_message.attributes.reqid, request_message.attributes.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn error_response(
+        request_attributes: &UAttributes,
+        status: &UStatus,
+    ) -> Result<UMessage, UMessageError> {
+        let mut builder = UMessageBuilder::response_for_request(request_attributes);
+        if !builder.priority.is_at_least(UPriority::UPRIORITY_CS4) {
+            builder.priority = UPriority::UPRIORITY_CS4;
+        }
+        if let Some(traceparent) = request_attributes.traceparent.as_ref() {
+            builder.with_traceparent(traceparent);
+        }
+        builder
+            .with_comm_status(status.get_code())
+            .build_with_protobuf_payload(status)
+    }
+
+    /// Gets a builder for creating a message that cancels a previously sent RPC *request*.
+    ///
+    /// A cancellation message is a *notification* that is sent from the original request's reply-to
+    /// address to the uEntity providing the invoked method. It refers to the request to cancel by means
+    /// of its `reqid` attribute. The source's resource ID is set to [`REQUEST_CANCELLATION_RESOURCE_ID`]
+    /// and the sink's resource ID is set to `0`, so that cancellation messages are never delivered to
+    /// the listeners that a service provider has registered for its methods. Service providers that do
+    /// not support cancellation therefore simply ignore such messages. Service providers that support
+    /// cancellation stop processing the original request and answer it with a response that has its
+    /// `commstatus` set to [`UCode::CANCELLED`]. The cancellation message itself is not answered.
+    ///
+    /// The builder will be initialized with the priority and TTL of the original request.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_attributes` - The attributes of the request message to cancel.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UMessageType, UPayloadFormat, UUri, REQUEST_CANCELLATION_RESOURCE_ID};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let method_to_invoke = UUri::try_from("//my-vehicle/4210/5/64AB")?;
+    /// let reply_to_address = UUri::try_from("//my-cloud/BA4C/1/0")?;
+    /// let request_message = UMessageBuilder::request(method_to_invoke, reply_to_address, 5000)
+    ///                           .build_with_payload("lock", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)?;
+    ///
+    /// let cancellation = UMessageBuilder::request_cancellation(&request_message.attributes).build()?;
+    /// assert_eq!(cancellation.attributes.type_, UMessageType::UMESSAGE_TYPE_NOTIFICATION.into());
+    /// assert_eq!(cancellation.attributes.source, Some(UUri::try_from("//my-cloud/BA4C/1/FFFE")?).into());
+    /// assert_eq!(cancellation.attributes.source.resource_id, REQUEST_CANCELLATION_RESOURCE_ID as u32);
+    /// assert_eq!(cancellation.attributes.sink, Some(UUri::try_from("//my-vehicle/4210/5/0")?).into());
+    /// assert_eq!(cancellation.attributes.reqid, request_message.attributes.id);
+    /// assert_ne!(cancellation.attributes.id, request_message.attributes.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_cancellation(request_attributes: &UAttributes) -> UMessageBuilder {
+        UMessageBuilder {
+            validator: Box::new(NotificationValidator),
+            message_type: UMessageType::UMESSAGE_TYPE_NOTIFICATION,
+            source: request_attributes
+                .source
+                .as_ref()
+                .map(|reply_to_address| UUri {
+                    resource_id: REQUEST_CANCELLATION_RESOURCE_ID as u32,
+                    ..reply_to_address.to_owned()
+                }),
+            sink: request_attributes.sink.as_ref().map(|method| UUri {
+                resource_id: 0,
+                ..method.to_owned()
+            }),
+            request_id: request_attributes.id.as_ref().cloned(),
+            ttl: request_attributes.ttl,
+            priority: request_attributes
+                .priority
+                .enum_value_or(UPriority::UPRIORITY_CS4),
+            ..Default::default()
+        }
+    }
+
     /// Sets the message's identifier.
     ///
     /// Every message must have an identifier. If this function is not used, an identifier will be
@@ -408,8 +585,58 @@ impl UMessageBuilder {
     /// # Ok(())
     /// # }
     /// ```
+    ///
+    /// Replaces any deadline set using [`UMessageBuilder::with_deadline`].
     pub fn with_ttl(&mut self, ttl: u32) -> &mut UMessageBuilder {
         self.ttl = Some(ttl);
+        self.deadline = None;
+        self
+    }
+
+    /// Sets an absolute point in time after which the message should no longer be processed.
+    ///
+    /// uProtocol messages do not carry an absolute deadline. Instead, the message's time-to-live
+    /// is set to the number of milliseconds between the creation time encoded in the message's ID
+    /// and the given deadline, when the message is being built. Receivers can then derive the
+    /// same deadline from the message ID and TTL, regardless of how long the message has been
+    /// queued on its way. Forwarding the deadline of an incoming request to an outgoing request
+    /// thus keeps the end-to-end deadline intact across multiple hops.
+    ///
+    /// If the deadline has already passed when the message is being built, the TTL is set to
+    /// 1 ms so that the message is considered expired by its receivers. The TTL is capped at
+    /// [`i32::MAX`] ms.
+    ///
+    /// Replaces any TTL set using [`UMessageBuilder::with_ttl`].
+    ///
+    /// # Arguments
+    ///
+    /// * `instant_millis` - The deadline as the number of milliseconds since the UNIX epoch.
+    ///
+    /// # Returns
+    ///
+    /// The builder.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UUID, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let message_id = UUID::build();
+    /// let created = message_id.get_time().unwrap();
+    /// let message = UMessageBuilder::request(
+    ///                         UUri::try_from("//my-vehicle/4210/5/64AB")?,
+    ///                         UUri::try_from("//my-cloud/BA4C/1/0")?,
+    ///                         10_000)
+    ///                     .with_message_id(message_id)
+    ///                     .with_deadline(created + 3_000)
+    ///                     .build()?;
+    /// assert_eq!(message.attributes.ttl, Some(3_000));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_deadline(&mut self, instant_millis: u64) -> &mut UMessageBuilder {
+        self.deadline = Some(instant_millis);
         self
     }
 
@@ -523,6 +750,53 @@ impl UMessageBuilder {
         self
     }
 
+    /// Sets the format that the payload of a response message is required to have.
+    ///
+    /// This can be used for making sure that a response is created in the format that the client prefers,
+    /// e.g. as determined by means of [`preferred_response_format`](crate::communication::preferred_response_format).
+    /// Once set, the `build_with_payload` functions verify that the payload is declared using this format and
+    /// that the payload data is plausibly encoded in this format. This prevents, for example, protobuf encoded
+    /// data from accidentally being sent as JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The required payload format.
+    ///
+    /// # Returns
+    ///
+    /// The builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the builder is not used for creating an RPC response message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UMessageBuilder, UPayloadFormat, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let method_to_invoke = UUri::try_from("//my-vehicle/4210/5/64AB")?;
+    /// let reply_to_address = UUri::try_from("//my-cloud/BA4C/1/0")?;
+    /// let request_message = UMessageBuilder::request(method_to_invoke, reply_to_address, 5000)
+    ///                           .build_with_payload(r#"{"lock":true}"#, UPayloadFormat::UPAYLOAD_FORMAT_JSON)?;
+    ///
+    /// let mut builder = UMessageBuilder::response_for_request(&request_message.attributes);
+    /// builder.with_response_format(UPayloadFormat::UPAYLOAD_FORMAT_JSON);
+    /// assert!(builder.build_with_payload(vec![0x0a, 0x01, 0x01], UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF).is_err());
+    /// assert!(builder.build_with_payload(vec![0x0a, 0x01, 0x01], UPayloadFormat::UPAYLOAD_FORMAT_JSON).is_err());
+    ///
+    /// let response_message = builder.build_with_payload(r#"{"locked":true}"#, UPayloadFormat::UPAYLOAD_FORMAT_JSON)?;
+    /// assert_eq!(response_message.attributes.payload_format, UPayloadFormat::UPAYLOAD_FORMAT_JSON.into());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_response_format(&mut self, format: UPayloadFormat) -> &mut UMessageBuilder {
+        assert!(self.message_type == UMessageType::UMESSAGE_TYPE_RESPONSE);
+        self.response_format = Some(format);
+        self
+    }
+
     /// Sets the identifier of the W3C Trace Context to convey in the message.
     ///
     /// # Arguments
@@ -606,10 +880,54 @@ impl UMessageBuilder {
     /// # }
     /// ```
     pub fn build(&self) -> Result<UMessage, UMessageError> {
+        let message = self.build_unchecked();
+        self.validator
+            .validate(&message.attributes)
+            .map_err(UMessageError::from)
+            .map(|_| message)
+    }
+
+    /// Creates the message based on the builder's state without validating its attributes.
+    ///
+    /// This is an escape hatch for advanced use cases only, e.g. for testing how a receiver
+    /// handles malformed messages. In all other cases, [`UMessageBuilder::build`] should be used
+    /// instead, which catches inconsistent attributes before the message is sent.
+    ///
+    /// # Returns
+    ///
+    /// A message which might not be accepted by receivers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UAttributesValidators, UMessageBuilder, UUri};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let method_to_invoke = UUri::try_from("//my-vehicle/4210/5/64AB")?;
+    /// let reply_to_address = UUri::try_from("//my-cloud/BA4C/1/0")?;
+    /// let mut builder = UMessageBuilder::request(method_to_invoke, reply_to_address, 5000);
+    /// builder.with_ttl(0);
+    /// assert!(builder.build().is_err());
+    ///
+    /// let message = builder.build_unchecked();
+    /// assert_eq!(message.attributes.ttl, Some(0));
+    /// assert!(UAttributesValidators::Request.validator().validate(&message.attributes).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build_unchecked(&self) -> UMessage {
         let message_id = self
             .message_id
             .clone()
             .map_or_else(|| Some(UUID::build()), Some);
+        let ttl = match (self.deadline, message_id.as_ref().and_then(UUID::get_time)) {
+            (Some(deadline), Some(creation_time)) => Some(
+                deadline
+                    .saturating_sub(creation_time)
+                    .clamp(1, i32::MAX as u64) as u32,
+            ),
+            _ => self.ttl,
+        };
         let attributes = UAttributes {
             commstatus: self.comm_status,
             id: message_id.into(),
@@ -621,18 +939,15 @@ impl UMessageBuilder {
             source: self.source.clone().into(),
             token: self.token.clone(),
             traceparent: self.traceparent.clone(),
-            ttl: self.ttl,
+            ttl,
             type_: self.message_type.into(),
             ..Default::default()
         };
-        self.validator
-            .validate(&attributes)
-            .map_err(UMessageError::from)
-            .map(|_| UMessage {
-                attributes: Some(attributes).into(),
-                payload: self.payload.to_owned(),
-                ..Default::default()
-            })
+        UMessage {
+            attributes: Some(attributes).into(),
+            payload: self.payload.to_owned(),
+            ..Default::default()
+        }
     }
 
     /// Creates the message based on the builder's state and some payload.
@@ -649,7 +964,8 @@ impl UMessageBuilder {
     /// # Errors
     ///
     /// If the properties set on the builder do not represent a consistent set of [`UAttributes`],
-    /// a [`UMessageError::AttributesValidationError`] is returned.
+    /// a [`UMessageError::AttributesValidationError`] is returned. If a [response format](Self::with_response_format)
+    /// has been set and the payload does not match it, a [`UMessageError::PayloadError`] is returned.
     ///
     /// # Examples
     ///
@@ -669,7 +985,17 @@ impl UMessageBuilder {
         payload: T,
         format: UPayloadFormat,
     ) -> Result<UMessage, UMessageError> {
-        self.payload = Some(payload.into());
+        let payload = payload.into();
+        if let Some(response_format) = self.response_format {
+            if format != response_format {
+                return Err(UMessageError::PayloadError(format!(
+                    "Payload format {:?} does not match required response format {:?}",
+                    format, response_format
+                )));
+            }
+            verify_payload_format(format, &payload)?;
+        }
+        self.payload = Some(payload);
         self.payload_format = format;
 
         self.build()
@@ -856,6 +1182,63 @@ mod tests {
         }
     }
 
+    #[test_case(Some(5000), true; "with TTL")]
+    #[test_case(Some(0), false; "with zero TTL")]
+    #[test_case(None, false; "without TTL")]
+    fn test_build_validates_request_ttl(ttl: Option<u32>, expect_success: bool) {
+        let method_to_invoke = UUri::try_from(METHOD_TO_INVOKE)
+            .expect("should have been able to create destination UUri");
+        let reply_to_address = UUri::try_from(REPLY_TO_ADDRESS)
+            .expect("should have been able to create reply-to UUri");
+        let mut builder = UMessageBuilder::request(method_to_invoke, reply_to_address, 5000);
+        builder.ttl = ttl;
+
+        let result = builder.build();
+        assert_eq!(result.is_ok(), expect_success);
+        if !expect_success {
+            assert!(matches!(
+                result,
+                Err(UMessageError::AttributesValidationError(_))
+            ));
+        }
+        // the unchecked variant creates the message regardless
+        assert_eq!(builder.build_unchecked().attributes.ttl, ttl);
+    }
+
+    #[test_case(3_000, 3_000; "for deadline in the future")]
+    #[test_case(0, 1; "for deadline at creation time")]
+    #[test_case(-500, 1; "for deadline in the past")]
+    #[test_case(i64::from(u32::MAX), i32::MAX as u32; "for deadline exceeding max ttl")]
+    fn test_build_derives_ttl_from_deadline(offset_from_creation: i64, expected_ttl: u32) {
+        let message_id = UUID::build();
+        let creation_time = message_id.get_time().unwrap();
+        let deadline = creation_time.saturating_add_signed(offset_from_creation);
+        let message = UMessageBuilder::request(
+            UUri::try_from(METHOD_TO_INVOKE).unwrap(),
+            UUri::try_from(REPLY_TO_ADDRESS).unwrap(),
+            5000,
+        )
+        .with_message_id(message_id)
+        .with_deadline(deadline)
+        .build()
+        .expect("should have been able to create message");
+        assert_eq!(message.attributes.ttl, Some(expected_ttl));
+    }
+
+    #[test]
+    fn test_with_ttl_replaces_deadline() {
+        let message = UMessageBuilder::request(
+            UUri::try_from(METHOD_TO_INVOKE).unwrap(),
+            UUri::try_from(REPLY_TO_ADDRESS).unwrap(),
+            5000,
+        )
+        .with_deadline(0)
+        .with_ttl(2000)
+        .build()
+        .expect("should have been able to create message");
+        assert_eq!(message.attributes.ttl, Some(2000));
+    }
+
     #[test]
     fn test_build_supports_repeated_invocation() {
         let topic = UUri::try_from(TOPIC).expect("should have been able to create UUri");
@@ -994,4 +1377,123 @@ mod tests {
             UMessageType::UMESSAGE_TYPE_RESPONSE.into()
         );
     }
+
+    #[test_case(UPayloadFormat::UPAYLOAD_FORMAT_JSON, r#"{"locked":true}"#.as_bytes(), UPayloadFormat::UPAYLOAD_FORMAT_JSON, true; "for matching JSON payload")]
+    #[test_case(UPayloadFormat::UPAYLOAD_FORMAT_JSON, &[0x0a, 0x01, 0x01], UPayloadFormat::UPAYLOAD_FORMAT_JSON, false; "for protobuf payload declared as JSON")]
+    #[test_case(UPayloadFormat::UPAYLOAD_FORMAT_JSON, &[0x0a, 0x01, 0x01], UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF, false; "for payload in other format")]
+    #[test_case(UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF, &[0x0a, 0x01, 0x01], UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF, true; "for matching protobuf payload")]
+    fn test_build_response_with_required_format(
+        required_format: UPayloadFormat,
+        payload: &'static [u8],
+        declared_format: UPayloadFormat,
+        expected_valid: bool,
+    ) {
+        let request = UMessageBuilder::request(
+            UUri::try_from(METHOD_TO_INVOKE).unwrap(),
+            UUri::try_from(REPLY_TO_ADDRESS).unwrap(),
+            5000,
+        )
+        .build()
+        .unwrap();
+
+        let result = UMessageBuilder::response_for_request(&request.attributes)
+            .with_response_format(required_format)
+            .build_with_payload(payload, declared_format);
+
+        assert_eq!(result.is_ok(), expected_valid);
+        if let Ok(response) = result {
+            assert_eq!(response.attributes.payload_format, required_format.into());
+            assert_eq!(response.payload, Some(Bytes::from_static(payload)));
+        }
+    }
+
+    #[test_case(UPriority::UPRIORITY_CS5, UPriority::UPRIORITY_CS5; "for request with high priority")]
+    #[test_case(UPriority::UPRIORITY_UNSPECIFIED, UPriority::UPRIORITY_CS4; "for request without priority")]
+    fn test_build_error_response(request_priority: UPriority, expected_priority: UPriority) {
+        let method_to_invoke = UUri::try_from(METHOD_TO_INVOKE).expect("should have succeeded");
+        let reply_to_address = UUri::try_from(REPLY_TO_ADDRESS).expect("should have succeeded");
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let request_attributes = UAttributes {
+            type_: UMessageType::UMESSAGE_TYPE_REQUEST.into(),
+            id: Some(UUID::build()).into(),
+            source: Some(reply_to_address.clone()).into(),
+            sink: Some(method_to_invoke.clone()).into(),
+            priority: request_priority.into(),
+            ttl: Some(5000),
+            traceparent: Some(traceparent.to_string()),
+            ..Default::default()
+        };
+        let status = UStatus::fail_with_code(UCode::NOT_FOUND, "no such door");
+
+        let message = UMessageBuilder::error_response(&request_attributes, &status)
+            .expect("should have been able to create message");
+
+        assert_eq!(
+            message.attributes.type_,
+            UMessageType::UMESSAGE_TYPE_RESPONSE.into()
+        );
+        assert_eq!(message.attributes.source, Some(method_to_invoke).into());
+        assert_eq!(message.attributes.sink, Some(reply_to_address).into());
+        assert_eq!(message.attributes.reqid, request_attributes.id);
+        assert_eq!(message.attributes.priority, expected_priority.into());
+        assert_eq!(message.attributes.commstatus, Some(UCode::NOT_FOUND.into()));
+        assert_eq!(
+            message.attributes.traceparent,
+            Some(traceparent.to_string())
+        );
+        assert_eq!(
+            message.attributes.payload_format,
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF.into()
+        );
+        assert_eq!(message.extract_protobuf::<UStatus>().unwrap(), status);
+    }
+
+    #[test]
+    fn test_build_error_response_fails_for_request_without_reply_to_address() {
+        let request_attributes = UAttributes {
+            type_: UMessageType::UMESSAGE_TYPE_REQUEST.into(),
+            id: Some(UUID::build()).into(),
+            sink: Some(UUri::try_from(METHOD_TO_INVOKE).unwrap()).into(),
+            ..Default::default()
+        };
+
+        assert!(UMessageBuilder::error_response(
+            &request_attributes,
+            &UStatus::fail_with_code(UCode::INVALID_ARGUMENT, "missing reply-to address")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_build_request_no_reply() {
+        let method_to_invoke = UUri::try_from(METHOD_TO_INVOKE).unwrap();
+        let source = UUri::try_from(REPLY_TO_ADDRESS).unwrap();
+        let message =
+            UMessageBuilder::request_no_reply(method_to_invoke.clone(), source.clone(), 5000)
+                .with_priority(UPriority::UPRIORITY_CS5)
+                .build_with_payload("unlock", UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+                .expect("should have been able to create message");
+
+        assert!(RequestValidator.validate(&message.attributes).is_ok());
+        assert!(message.attributes.is_no_reply_request());
+        assert_eq!(message.attributes.commstatus, Some(UCode::OK.into()));
+        assert_eq!(message.attributes.source, Some(source).into());
+        assert_eq!(message.attributes.sink, Some(method_to_invoke).into());
+        assert_eq!(message.attributes.ttl, Some(5000));
+        assert_eq!(message.attributes.priority, UPriority::UPRIORITY_CS5.into());
+        assert!(message.attributes.reqid.is_none());
+    }
+
+    #[test]
+    fn test_regular_request_is_not_no_reply_request() {
+        let message = UMessageBuilder::request(
+            UUri::try_from(METHOD_TO_INVOKE).unwrap(),
+            UUri::try_from(REPLY_TO_ADDRESS).unwrap(),
+            5000,
+        )
+        .build()
+        .expect("should have been able to create message");
+
+        assert!(!message.attributes.is_no_reply_request());
+    }
 }
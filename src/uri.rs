@@ -26,8 +26,22 @@ const WILDCARD_ENTITY_ID: u32 = 0x0000_FFFF;
 const WILDCARD_ENTITY_VERSION: u32 = 0x0000_00FF;
 const WILDCARD_RESOURCE_ID: u32 = 0x0000_FFFF;
 
-const RESOURCE_ID_RESPONSE: u32 = 0;
-const RESOURCE_ID_MIN_EVENT: u32 = 0x8000;
+/// The kind of resource that a [`UUri`] refers to, as determined by its resource ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    /// The URI is an RPC response address or the destination of a Notification
+    /// (resource ID [`UUri::RPC_RESPONSE_ID`]).
+    RpcResponse,
+    /// The URI refers to a service method (resource ID in
+    /// \[[`UUri::RPC_METHOD_ID_MIN`], [`UUri::RPC_METHOD_ID_MAX`]\]).
+    RpcMethod,
+    /// The URI refers to a topic that events are published to (resource ID in
+    /// \[[`UUri::TOPIC_ID_MIN`], [`UUri::TOPIC_ID_MAX`]\]).
+    Topic,
+    /// The resource ID has no meaning defined by the uProtocol specification, e.g. because
+    /// it is the wildcard resource ID or exceeds 16 bits.
+    Reserved,
+}
 
 #[derive(Debug)]
 pub enum UUriError {
@@ -262,6 +276,23 @@ impl TryFrom<&str> for UUri {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for UUri {
+    /// Serializes a UUri to its URI string representation, as created by [`UUri::to_uri`].
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&String::from(self))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for UUri {
+    /// Deserializes a UUri from its URI string representation.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let uri = String::deserialize(deserializer)?;
+        UUri::try_from(uri).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Hash for UUri {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.authority_name.hash(state);
@@ -274,6 +305,38 @@ impl Hash for UUri {
 impl Eq for UUri {}
 
 impl UUri {
+    /// The resource ID of RPC response addresses and Notification destinations.
+    pub const RPC_RESPONSE_ID: u32 = 0x0000;
+    /// The smallest resource ID of a service method.
+    pub const RPC_METHOD_ID_MIN: u32 = 0x0001;
+    /// The largest resource ID of a service method.
+    pub const RPC_METHOD_ID_MAX: u32 = 0x7FFF;
+    /// The smallest resource ID of a topic.
+    pub const TOPIC_ID_MIN: u32 = 0x8000;
+    /// The largest resource ID of a topic.
+    pub const TOPIC_ID_MAX: u32 = 0xFFFE;
+
+    /// Creates a pattern that matches any URI.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UUri;
+    ///
+    /// let pattern = UUri::any();
+    /// assert!(pattern.matches(&UUri::try_from("//VIN/A14F/3/B1D4").unwrap()));
+    /// assert!(pattern.matches(&UUri::try_from("/1A/1/0").unwrap()));
+    /// ```
+    pub fn any() -> Self {
+        UUri {
+            authority_name: WILDCARD_AUTHORITY.to_string(),
+            ue_id: WILDCARD_ENTITY_ID,
+            ue_version_major: WILDCARD_ENTITY_VERSION,
+            resource_id: WILDCARD_RESOURCE_ID,
+            ..Default::default()
+        }
+    }
+
     /// Check if `UUri` is empty by comparing with `UUri::default()` object.
     ///
     /// # Returns
@@ -283,6 +346,47 @@ impl UUri {
         self.eq(&UUri::default())
     }
 
+    /// Normalizes this URI's authority name.
+    ///
+    /// The authority name is a host name, which is case insensitive and may be given in its fully
+    /// qualified form, i.e. with a trailing dot. Normalization converts all characters to lowercase and
+    /// removes surrounding whitespace as well as trailing dots, so that URIs referring to the same
+    /// authority are equal and can be [matched](Self::matches) consistently.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UUri;
+    ///
+    /// let mut uri = UUri::try_from("//My-Vehicle./A14F/3/B1D4").unwrap();
+    /// uri.normalize();
+    /// assert_eq!(uri, UUri::try_from("//my-vehicle/A14F/3/B1D4").unwrap());
+    /// ```
+    pub fn normalize(&mut self) {
+        let normalized = self
+            .authority_name
+            .trim()
+            .trim_end_matches('.')
+            .to_ascii_lowercase();
+        self.authority_name = normalized;
+    }
+
+    /// Creates a copy of this URI with a [normalized](Self::normalize) authority name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UUri;
+    ///
+    /// let uri = UUri::try_from("//VIN.Vehicles/A14F/3/B1D4").unwrap();
+    /// assert_eq!(uri.normalized().authority_name, "vin.vehicles");
+    /// ```
+    pub fn normalized(&self) -> UUri {
+        let mut uri = self.clone();
+        uri.normalize();
+        uri
+    }
+
     /// Serializes this UUri to a URI string.
     ///
     /// # Arguments
@@ -372,9 +476,33 @@ impl UUri {
         }
     }
 
+    /// Determines the kind of resource that this UUri refers to.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{ResourceKind, UUri};
+    ///
+    /// let uri = UUri::try_from("//my-vehicle/4210/1/1A50").unwrap();
+    /// assert_eq!(uri.resource_kind(), ResourceKind::RpcMethod);
+    ///
+    /// let uri = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+    /// assert_eq!(uri.resource_kind(), ResourceKind::Topic);
+    /// ```
+    pub fn resource_kind(&self) -> ResourceKind {
+        match self.resource_id {
+            Self::RPC_RESPONSE_ID => ResourceKind::RpcResponse,
+            Self::RPC_METHOD_ID_MIN..=Self::RPC_METHOD_ID_MAX => ResourceKind::RpcMethod,
+            Self::TOPIC_ID_MIN..=Self::TOPIC_ID_MAX => ResourceKind::Topic,
+            _ => ResourceKind::Reserved,
+        }
+    }
+
     /// Checks if this UUri refers to a service method.
     ///
-    /// Returns `true` if 0 < resource ID < 0x8000.
+    /// Returns `true` if 0 < resource ID < 0x8000, i.e. if the resource ID is in range
+    /// [`Self::RPC_METHOD_ID_MIN`]..=[`Self::RPC_METHOD_ID_MAX`]. Such UUris can be used as the sink
+    /// of RPC request messages and as the source of RPC response messages.
     ///
     /// # Examples
     ///
@@ -388,7 +516,7 @@ impl UUri {
     /// assert!(uri.is_rpc_method());
     /// ```
     pub fn is_rpc_method(&self) -> bool {
-        self.resource_id > RESOURCE_ID_RESPONSE && self.resource_id < RESOURCE_ID_MIN_EVENT
+        self.resource_id > Self::RPC_RESPONSE_ID && self.resource_id < Self::TOPIC_ID_MIN
     }
 
     /// Verifies that this UUri refers to a service method.
@@ -419,7 +547,8 @@ impl UUri {
         if !self.is_rpc_method() {
             Err(UUriError::validation_error(format!(
                 "Resource ID must be a value from ]{:#X}, {:#X}[",
-                RESOURCE_ID_RESPONSE, RESOURCE_ID_MIN_EVENT
+                Self::RPC_RESPONSE_ID,
+                Self::TOPIC_ID_MIN
             )))
         } else {
             self.verify_no_wildcards()
@@ -442,12 +571,13 @@ impl UUri {
     /// assert!(uri.is_notification_destination());
     /// ```
     pub fn is_notification_destination(&self) -> bool {
-        self.resource_id == RESOURCE_ID_RESPONSE
+        self.resource_id == Self::RPC_RESPONSE_ID
     }
 
     /// Checks if this UUri represents an RPC response address.
     ///
-    /// Returns `true` if resource ID is 0.
+    /// Returns `true` if resource ID is 0 ([`Self::RPC_RESPONSE_ID`]). Such UUris can be used as the
+    /// source of RPC request messages (the reply-to address) and as the sink of RPC response messages.
     ///
     /// # Examples
     ///
@@ -461,7 +591,7 @@ impl UUri {
     /// assert!(uri.is_rpc_response());
     /// ```
     pub fn is_rpc_response(&self) -> bool {
-        self.resource_id == RESOURCE_ID_RESPONSE
+        self.resource_id == Self::RPC_RESPONSE_ID
     }
 
     /// Verifies that this UUri represents an RPC response address.
@@ -486,7 +616,7 @@ impl UUri {
         if !self.is_rpc_response() {
             Err(UUriError::validation_error(format!(
                 "Resource ID must be {:#X}",
-                RESOURCE_ID_RESPONSE
+                Self::RPC_RESPONSE_ID
             )))
         } else {
             self.verify_no_wildcards()
@@ -509,7 +639,7 @@ impl UUri {
     /// assert!(uri.is_event());
     /// ```
     pub fn is_event(&self) -> bool {
-        self.resource_id >= RESOURCE_ID_MIN_EVENT
+        self.resource_id >= Self::TOPIC_ID_MIN
     }
 
     /// Verifies that this UUri can be used as the source of an event.
@@ -534,7 +664,7 @@ impl UUri {
         if !self.is_event() {
             Err(UUriError::validation_error(format!(
                 "Resource ID must be >= {:#X}",
-                RESOURCE_ID_MIN_EVENT
+                Self::TOPIC_ID_MIN
             )))
         } else {
             self.verify_no_wildcards()
@@ -570,8 +700,46 @@ impl UUri {
         self.resource_id == WILDCARD_RESOURCE_ID || self.resource_id == candidate.resource_id
     }
 
+    /// Checks if there is any URI that matches both this pattern and another pattern.
+    ///
+    /// This is the case if, for each of the URIs' components, at least one of the patterns contains
+    /// a wildcard or both patterns contain the same value.
+    pub(crate) fn overlaps(&self, other: &UUri) -> bool {
+        let authority_overlaps = self.authority_name == WILDCARD_AUTHORITY
+            || other.authority_name == WILDCARD_AUTHORITY
+            || self.authority_name == other.authority_name;
+        let entity_type_overlaps = self.ue_id & WILDCARD_ENTITY_ID == WILDCARD_ENTITY_ID
+            || other.ue_id & WILDCARD_ENTITY_ID == WILDCARD_ENTITY_ID
+            || self.ue_id & WILDCARD_ENTITY_ID == other.ue_id & WILDCARD_ENTITY_ID;
+        let entity_instance_overlaps = self.ue_id & 0xFFFF_0000 == 0x0000_0000
+            || other.ue_id & 0xFFFF_0000 == 0x0000_0000
+            || self.ue_id & 0xFFFF_0000 == other.ue_id & 0xFFFF_0000;
+        let version_overlaps = self.ue_version_major == WILDCARD_ENTITY_VERSION
+            || other.ue_version_major == WILDCARD_ENTITY_VERSION
+            || self.ue_version_major == other.ue_version_major;
+        let resource_overlaps = self.resource_id == WILDCARD_RESOURCE_ID
+            || other.resource_id == WILDCARD_RESOURCE_ID
+            || self.resource_id == other.resource_id;
+        authority_overlaps
+            && entity_type_overlaps
+            && entity_instance_overlaps
+            && version_overlaps
+            && resource_overlaps
+    }
+
     /// Checks if a given candidate URI matches a pattern.
     ///
+    /// This UUri is the pattern (e.g. a filter that a listener has been registered for) while the
+    /// candidate usually is a concrete URI (e.g. the source or sink of a message). Each of the candidate's
+    /// components is compared to the pattern's corresponding component, which matches any value if it
+    /// contains a wildcard:
+    ///
+    /// * authority name `*`
+    /// * entity type ID `0xFFFF`
+    /// * entity instance ID `0x0000`
+    /// * major version `0xFF`
+    /// * resource ID `0xFFFF`
+    ///
     /// # Returns
     ///
     /// `true` if the candiadate matches the pattern represented by this UUri.
@@ -599,6 +767,56 @@ mod tests {
     use protobuf::Message;
     use test_case::test_case;
 
+    #[test_case(0x0000, ResourceKind::RpcResponse; "for response ID")]
+    #[test_case(0x0001, ResourceKind::RpcMethod; "for min method ID")]
+    #[test_case(0x7FFF, ResourceKind::RpcMethod; "for max method ID")]
+    #[test_case(0x8000, ResourceKind::Topic; "for min topic ID")]
+    #[test_case(0xFFFE, ResourceKind::Topic; "for max topic ID")]
+    #[test_case(0xFFFF, ResourceKind::Reserved; "for wildcard ID")]
+    #[test_case(0x10000, ResourceKind::Reserved; "for ID exceeding 16 bits")]
+    fn test_resource_kind(resource_id: u32, expected_kind: ResourceKind) {
+        let uri = UUri {
+            resource_id,
+            ..Default::default()
+        };
+        assert_eq!(uri.resource_kind(), expected_kind);
+    }
+
+    #[test_case(0x0000, true, false; "for response ID")]
+    #[test_case(0x0001, false, true; "for min method ID")]
+    #[test_case(0x7FFF, false, true; "for max method ID")]
+    #[test_case(0x8000, false, false; "for min topic ID")]
+    fn test_is_rpc_response_and_is_rpc_method(
+        resource_id: u32,
+        expected_response: bool,
+        expected_method: bool,
+    ) {
+        let uri = UUri {
+            resource_id,
+            ..Default::default()
+        };
+        assert_eq!(uri.is_rpc_response(), expected_response);
+        assert_eq!(uri.is_rpc_method(), expected_method);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test_case("//my-vehicle/4210/1/B24D"; "for remote URI")]
+    #[test_case("/4210/1/B24D"; "for local URI")]
+    fn test_serde_json_round_trip(uri: &str) {
+        let uuri = UUri::try_from(uri).unwrap();
+        let json = serde_json::to_string(&uuri).unwrap();
+        assert_eq!(json, format!("\"{}\"", uri));
+        let deserialized: UUri = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, uuri);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_deserialize_fails_for_invalid_uri() {
+        assert!(serde_json::from_str::<UUri>("\"up://my-vehicle:1000/4210/1/B24D\"").is_err());
+        assert!(serde_json::from_str::<UUri>("{\"ue_id\": 5}").is_err());
+    }
+
     // [utest->req~uri-serialization~1]
     // [utest->dsn~uri-scheme~1]
     // [utest->dsn~uri-host-only~1]
@@ -727,6 +945,15 @@ mod tests {
     #[test_case("//authority/A410/3/1003", "//authority/2A410/3/1003"; "for pattern with wildcard entity instance")]
     #[test_case("//authority/A410/FF/1003", "//authority/A410/3/1003"; "for pattern with wildcard entity version")]
     #[test_case("//authority/A410/3/FFFF", "//authority/A410/3/1003"; "for pattern with wildcard resource")]
+    #[test_case("//*/A100/1/0", "//gateway/A100/1/0"; "for reply-to pattern with wildcard authority")]
+    #[test_case("//*/A100/1/0", "//other-gateway/3A100/1/0"; "for reply-to pattern with wildcard authority and entity instance")]
+    #[test_case("//*/FFFF/FF/FFFF", "//authority/A410/3/1003"; "for pattern with wildcards only")]
+    #[test_case("//*/FFFF/FF/FFFF", "/A410/3/1003"; "for pattern with wildcards only and local candidate URI")]
+    #[test_case("//*/FFFF/FF/FFFF", "//authority/2A410/3/0"; "for pattern with wildcards only and candidate with entity instance")]
+    #[test_case("/A410/3/1003", "/A410/3/1003"; "for identical local URIs")]
+    #[test_case("//authority/FFFF/3/1003", "//authority/2A410/3/1003"; "for pattern with wildcard entity ID and instance")]
+    #[test_case("//authority/3FFFF/3/1003", "//authority/3A410/3/1003"; "for pattern with wildcard entity ID and specific instance")]
+    #[test_case("//authority/A410/FF/FFFF", "//authority/A410/3/0"; "for pattern with wildcard version and resource")]
     fn test_matches_succeeds(pattern: &str, candidate: &str) {
         let pattern_uri =
             UUri::try_from(pattern).expect("should have been able to create pattern UUri");
@@ -735,6 +962,20 @@ mod tests {
         assert!(pattern_uri.matches(&candidate_uri));
     }
 
+    #[test_case("//*/A100/1/0", "//gateway/A100/1/0", true; "for wildcard authority")]
+    #[test_case("//*/A100/1/0", "//*/FFFF/1/0", true; "for wildcard authority and wildcard entity")]
+    #[test_case("//*/A100/1/0", "//*/A200/1/0", false; "for different entity IDs")]
+    #[test_case("//*/A100/1/0", "//gateway/A100/2/0", false; "for different versions")]
+    #[test_case("//gateway/A100/1/0", "//other/FFFF/FF/0", false; "for different authorities")]
+    #[test_case("//gateway/1A100/1/0", "//gateway/A100/1/0", true; "for wildcard entity instance")]
+    #[test_case("//gateway/1A100/1/0", "//gateway/2A100/1/0", false; "for different entity instances")]
+    fn test_overlaps(pattern: &str, other_pattern: &str, expected: bool) {
+        let pattern_uri = UUri::try_from(pattern).unwrap();
+        let other_uri = UUri::try_from(other_pattern).unwrap();
+        assert_eq!(pattern_uri.overlaps(&other_uri), expected);
+        assert_eq!(other_uri.overlaps(&pattern_uri), expected);
+    }
+
     // [utest->dsn~pattern-matching~1]
     #[test_case("//Authority/A410/3/1003", "//authority/A410/3/1003"; "for pattern with upper case authority")]
     #[test_case("/A410/3/1003", "//authority/A410/3/1003"; "for local pattern and candidate URI with authority")]
@@ -743,6 +984,14 @@ mod tests {
     #[test_case("//authority/30A410/3/1003", "//authority/2A410/3/1003"; "for pattern with different entity instance")]
     #[test_case("//authority/A410/1/1003", "//authority/A410/3/1003"; "for pattern with different entity version")]
     #[test_case("//authority/A410/3/ABCD", "//authority/A410/3/1003"; "for pattern with different resource")]
+    #[test_case("//*/A100/1/0", "//gateway/A200/1/0"; "for reply-to pattern with wildcard authority and different entity ID")]
+    #[test_case("//*/A100/1/0", "//gateway/A100/2/0"; "for reply-to pattern with wildcard authority and different entity version")]
+    #[test_case("//authority/A410/3/1003", "/A410/3/1003"; "for pattern with authority and local candidate URI")]
+    #[test_case("//authority/3FFFF/3/1003", "//authority/2A410/3/1003"; "for pattern with wildcard entity ID and different instance")]
+    #[test_case("//authority/FFFF/3/1003", "//authority/A410/4/1003"; "for pattern with wildcard entity ID and different version")]
+    #[test_case("//authority/A410/FF/1003", "//authority/A410/3/1004"; "for pattern with wildcard version and different resource")]
+    #[test_case("//*/FFFF/FF/1003", "//authority/A410/3/0"; "for pattern with specific resource only and different resource")]
+    #[test_case("//authority/A410/3/1003", "//authority/FFFF/FF/FFFF"; "for candidate with wildcards")]
     fn test_matches_fails(pattern: &str, candidate: &str) {
         let pattern_uri =
             UUri::try_from(pattern).expect("should have been able to create pattern UUri");
@@ -750,4 +999,28 @@ mod tests {
             UUri::try_from(candidate).expect("should have been able to create candidate UUri");
         assert!(!pattern_uri.matches(&candidate_uri));
     }
+
+    #[test_case("//My-Vehicle/A410/3/1003", "//my-vehicle/A410/3/1003"; "for mixed case authority")]
+    #[test_case("//MY-VEHICLE./A410/3/1003", "//my-vehicle/A410/3/1003"; "for fully qualified authority")]
+    #[test_case("//*/A410/3/1003", "//*/A410/3/1003"; "for wildcard authority")]
+    #[test_case("/A410/3/1003", "/A410/3/1003"; "for local URI")]
+    fn test_normalized_authorities_are_equal(uri: &str, expected: &str) {
+        let uri = UUri::try_from(uri).expect("should have been able to create UUri");
+        let expected = UUri::try_from(expected).expect("should have been able to create UUri");
+        assert_eq!(uri.normalized(), expected);
+        assert!(uri.normalized().matches(&expected));
+    }
+
+    #[test]
+    fn test_normalize_trims_authority() {
+        let mut uri = UUri {
+            authority_name: " VIN.vehicles.. ".to_string(),
+            ue_id: 0xA410,
+            ue_version_major: 0x03,
+            resource_id: 0x1003,
+            ..Default::default()
+        };
+        uri.normalize();
+        assert_eq!(uri.authority_name, "vin.vehicles");
+    }
 }
@@ -13,9 +13,16 @@
 
 use std::error::Error;
 
+use protobuf::Enum;
+
 pub use crate::up_core_api::ucode::UCode;
 pub use crate::up_core_api::ustatus::UStatus;
 
+/// The [`google.rpc.Status`](https://github.com/googleapis/googleapis/blob/master/google/rpc/status.proto)
+/// message type used by gRPC based services.
+#[cfg(feature = "grpc")]
+pub use crate::google_rpc::status::Status as GoogleRpcStatus;
+
 impl UStatus {
     /// Creates a status representing a success.
     ///
@@ -71,6 +78,75 @@ impl UStatus {
         }
     }
 
+    /// Creates a status representing a failure with code [`UCode::NOT_FOUND`].
+    ///
+    /// This is a shorthand for `UStatus::fail_with_code(UCode::NOT_FOUND, msg)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UCode, UStatus};
+    ///
+    /// let status = UStatus::not_found("no such vehicle");
+    /// assert_eq!(status.get_code(), UCode::NOT_FOUND);
+    /// assert_eq!(status.get_message(), "no such vehicle");
+    /// ```
+    pub fn not_found<M: Into<String>>(msg: M) -> Self {
+        Self::fail_with_code(UCode::NOT_FOUND, msg)
+    }
+
+    /// Creates a status representing a failure with code [`UCode::INVALID_ARGUMENT`].
+    ///
+    /// This is a shorthand for `UStatus::fail_with_code(UCode::INVALID_ARGUMENT, msg)`.
+    pub fn invalid_argument<M: Into<String>>(msg: M) -> Self {
+        Self::fail_with_code(UCode::INVALID_ARGUMENT, msg)
+    }
+
+    /// Creates a status representing a failure with code [`UCode::DEADLINE_EXCEEDED`].
+    ///
+    /// This is a shorthand for `UStatus::fail_with_code(UCode::DEADLINE_EXCEEDED, msg)`.
+    pub fn deadline_exceeded<M: Into<String>>(msg: M) -> Self {
+        Self::fail_with_code(UCode::DEADLINE_EXCEEDED, msg)
+    }
+
+    /// Creates a status representing a failure with code [`UCode::UNAVAILABLE`].
+    ///
+    /// This is a shorthand for `UStatus::fail_with_code(UCode::UNAVAILABLE, msg)`.
+    pub fn unavailable<M: Into<String>>(msg: M) -> Self {
+        Self::fail_with_code(UCode::UNAVAILABLE, msg)
+    }
+
+    /// Creates a status representing a failure with code [`UCode::INTERNAL`].
+    ///
+    /// This is a shorthand for `UStatus::fail_with_code(UCode::INTERNAL, msg)`.
+    pub fn internal<M: Into<String>>(msg: M) -> Self {
+        Self::fail_with_code(UCode::INTERNAL, msg)
+    }
+
+    /// Creates a status representing a failure with code [`UCode::PERMISSION_DENIED`].
+    ///
+    /// This is a shorthand for `UStatus::fail_with_code(UCode::PERMISSION_DENIED, msg)`.
+    pub fn permission_denied<M: Into<String>>(msg: M) -> Self {
+        Self::fail_with_code(UCode::PERMISSION_DENIED, msg)
+    }
+
+    /// Creates a status representing a failure with code [`UCode::ABORTED`].
+    ///
+    /// This is a shorthand for `UStatus::fail_with_code(UCode::ABORTED, msg)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UCode, UStatus};
+    ///
+    /// let status = UStatus::aborted("concurrent modification");
+    /// assert_eq!(status.get_code(), UCode::ABORTED);
+    /// assert!(status.get_code().is_retryable());
+    /// ```
+    pub fn aborted<M: Into<String>>(msg: M) -> Self {
+        Self::fail_with_code(UCode::ABORTED, msg)
+    }
+
     /// Checks if this status represents a failure.
     ///
     /// # Examples
@@ -150,15 +226,598 @@ impl UStatus {
     pub fn get_code(&self) -> UCode {
         self.code.enum_value_or_default()
     }
+
+    /// Combines multiple statuses into a single status, e.g. for reporting the outcome of a batch operation.
+    ///
+    /// The code of the resulting status is the code of the most [severe](UCode::severity) of the given statuses.
+    /// Codes of the same severity are ranked by their numeric value.
+    ///
+    /// The message of the resulting status consists of the (non-empty) messages of all failed statuses,
+    /// separated by semicolons.
+    ///
+    /// # Returns
+    ///
+    /// A status with [`UCode::OK`] if all given statuses represent a success or if no statuses are given.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{UCode, UStatus};
+    ///
+    /// let status = UStatus::merge(&[
+    ///     UStatus::ok(),
+    ///     UStatus::not_found("no such topic"),
+    ///     UStatus::internal("out of memory"),
+    /// ]);
+    /// assert_eq!(status.get_code(), UCode::INTERNAL);
+    /// assert_eq!(status.get_message(), "no such topic; out of memory");
+    /// ```
+    pub fn merge(statuses: &[UStatus]) -> UStatus {
+        let failed_statuses: Vec<&UStatus> = statuses
+            .iter()
+            .filter(|status| status.is_failed())
+            .collect();
+        let Some(code) = failed_statuses
+            .iter()
+            .map(|status| status.get_code())
+            .max_by_key(|code| (code.severity(), code.value()))
+        else {
+            return UStatus::ok();
+        };
+        let message = failed_statuses
+            .iter()
+            .filter_map(|status| status.message.as_deref())
+            .filter(|message| !message.is_empty())
+            .collect::<Vec<&str>>()
+            .join("; ");
+        UStatus::fail_with_code(code, message)
+    }
 }
 
 impl Error for UStatus {}
 
+/// A coarse classification of [`UCode`]s, e.g. for grouping errors in dashboards.
+///
+/// The classification follows the common categorization of the corresponding
+/// [gRPC status codes](https://grpc.github.io/grpc/core/md_doc_statuscodes.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The operation has completed successfully.
+    Success,
+    /// The operation has failed due to a problem with the request or the caller, e.g. an invalid argument
+    /// or missing permissions. Repeating the (unchanged) operation will fail again.
+    ClientError,
+    /// The operation has failed due to a problem of the service, e.g. an internal error or missing functionality.
+    ServerError,
+    /// The operation has failed due to a temporary condition. Repeating the operation later on might succeed.
+    Transient,
+}
+
+impl UCode {
+    /// Gets the category that this code belongs to.
+    ///
+    /// Codes that are [retryable](UCode::is_retryable) belong to [`ErrorCategory::Transient`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{ErrorCategory, UCode};
+    ///
+    /// assert_eq!(UCode::OK.category(), ErrorCategory::Success);
+    /// assert_eq!(UCode::NOT_FOUND.category(), ErrorCategory::ClientError);
+    /// assert_eq!(UCode::INTERNAL.category(), ErrorCategory::ServerError);
+    /// assert_eq!(UCode::UNAVAILABLE.category(), ErrorCategory::Transient);
+    /// ```
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            UCode::OK => ErrorCategory::Success,
+            UCode::CANCELLED
+            | UCode::INVALID_ARGUMENT
+            | UCode::NOT_FOUND
+            | UCode::ALREADY_EXISTS
+            | UCode::PERMISSION_DENIED
+            | UCode::FAILED_PRECONDITION
+            | UCode::OUT_OF_RANGE
+            | UCode::UNAUTHENTICATED => ErrorCategory::ClientError,
+            UCode::UNKNOWN | UCode::UNIMPLEMENTED | UCode::INTERNAL | UCode::DATA_LOSS => {
+                ErrorCategory::ServerError
+            }
+            UCode::UNAVAILABLE
+            | UCode::DEADLINE_EXCEEDED
+            | UCode::ABORTED
+            | UCode::RESOURCE_EXHAUSTED => ErrorCategory::Transient,
+        }
+    }
+
+    /// Gets the severity of this code, e.g. for determining the most severe of multiple errors.
+    ///
+    /// The ranking follows the common practice of considering problems of the service more severe than
+    /// problems that are caused by the client or that are of temporary nature:
+    ///
+    /// | Severity | Codes |
+    /// |----------|-------|
+    /// | 0 | `OK` |
+    /// | 1 | `CANCELLED` |
+    /// | 2 | `INVALID_ARGUMENT`, `NOT_FOUND`, `ALREADY_EXISTS`, `FAILED_PRECONDITION`, `OUT_OF_RANGE` |
+    /// | 3 | `PERMISSION_DENIED`, `UNAUTHENTICATED` |
+    /// | 4 | `DEADLINE_EXCEEDED`, `RESOURCE_EXHAUSTED`, `ABORTED`, `UNAVAILABLE` |
+    /// | 5 | `UNKNOWN`, `UNIMPLEMENTED` |
+    /// | 6 | `INTERNAL`, `DATA_LOSS` |
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UCode;
+    ///
+    /// assert!(UCode::OK.severity() < UCode::NOT_FOUND.severity());
+    /// assert!(UCode::UNAVAILABLE.severity() < UCode::INTERNAL.severity());
+    /// assert_eq!(UCode::INTERNAL.severity(), UCode::DATA_LOSS.severity());
+    /// ```
+    pub fn severity(&self) -> u8 {
+        match self {
+            UCode::OK => 0,
+            UCode::CANCELLED => 1,
+            UCode::INVALID_ARGUMENT
+            | UCode::NOT_FOUND
+            | UCode::ALREADY_EXISTS
+            | UCode::FAILED_PRECONDITION
+            | UCode::OUT_OF_RANGE => 2,
+            UCode::PERMISSION_DENIED | UCode::UNAUTHENTICATED => 3,
+            UCode::DEADLINE_EXCEEDED
+            | UCode::RESOURCE_EXHAUSTED
+            | UCode::ABORTED
+            | UCode::UNAVAILABLE => 4,
+            UCode::UNKNOWN | UCode::UNIMPLEMENTED => 5,
+            UCode::INTERNAL | UCode::DATA_LOSS => 6,
+        }
+    }
+
+    /// Gets the code corresponding to a [gRPC status code](https://grpc.github.io/grpc/core/md_doc_statuscodes.html).
+    ///
+    /// `UCode`s use the same numeric values as gRPC status codes. Values that do not correspond
+    /// to any known code are mapped to [`UCode::UNKNOWN`], as recommended for gRPC clients.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UCode;
+    ///
+    /// assert_eq!(UCode::from_grpc_code(5), UCode::NOT_FOUND);
+    /// assert_eq!(UCode::from_grpc_code(42), UCode::UNKNOWN);
+    /// ```
+    pub fn from_grpc_code(code: i32) -> UCode {
+        UCode::from_i32(code).unwrap_or(UCode::UNKNOWN)
+    }
+
+    /// Gets the HTTP status code corresponding to this code, e.g. for exposing services via a REST gateway.
+    ///
+    /// The mapping follows the [conventional mapping of gRPC status codes](https://github.com/googleapis/googleapis/blob/master/google/rpc/code.proto).
+    /// `CANCELLED` is mapped to the (non-standard) status code 499 _Client Closed Request_.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UCode;
+    ///
+    /// assert_eq!(UCode::OK.to_http_status(), 200);
+    /// assert_eq!(UCode::NOT_FOUND.to_http_status(), 404);
+    /// assert_eq!(UCode::UNAVAILABLE.to_http_status(), 503);
+    /// ```
+    pub fn to_http_status(&self) -> u16 {
+        match self {
+            UCode::OK => 200,
+            UCode::INVALID_ARGUMENT | UCode::FAILED_PRECONDITION | UCode::OUT_OF_RANGE => 400,
+            UCode::UNAUTHENTICATED => 401,
+            UCode::PERMISSION_DENIED => 403,
+            UCode::NOT_FOUND => 404,
+            UCode::ALREADY_EXISTS | UCode::ABORTED => 409,
+            UCode::RESOURCE_EXHAUSTED => 429,
+            UCode::CANCELLED => 499,
+            UCode::UNKNOWN | UCode::INTERNAL | UCode::DATA_LOSS => 500,
+            UCode::UNIMPLEMENTED => 501,
+            UCode::UNAVAILABLE => 503,
+            UCode::DEADLINE_EXCEEDED => 504,
+        }
+    }
+
+    /// Gets the code that best matches an HTTP status code, e.g. for mapping the responses of a REST service.
+    ///
+    /// This is the inverse of [`UCode::to_http_status`], as far as possible. HTTP status codes that
+    /// correspond to multiple codes are mapped to the most generic one, e.g. 400 to `INVALID_ARGUMENT`
+    /// and 500 to `INTERNAL`. Some status codes that are not produced by [`UCode::to_http_status`] are
+    /// mapped to their closest match, e.g. 408 _Request Timeout_ to `DEADLINE_EXCEEDED`. All
+    /// 2xx status codes are mapped to `OK`, and any other status code is mapped to `UNKNOWN`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UCode;
+    ///
+    /// assert_eq!(UCode::from_http_status(204), UCode::OK);
+    /// assert_eq!(UCode::from_http_status(403), UCode::PERMISSION_DENIED);
+    /// assert_eq!(UCode::from_http_status(418), UCode::UNKNOWN);
+    /// ```
+    pub fn from_http_status(status: u16) -> UCode {
+        match status {
+            200..=299 => UCode::OK,
+            400 => UCode::INVALID_ARGUMENT,
+            401 => UCode::UNAUTHENTICATED,
+            403 => UCode::PERMISSION_DENIED,
+            404 => UCode::NOT_FOUND,
+            408 | 504 => UCode::DEADLINE_EXCEEDED,
+            409 => UCode::ALREADY_EXISTS,
+            412 => UCode::FAILED_PRECONDITION,
+            416 => UCode::OUT_OF_RANGE,
+            429 => UCode::RESOURCE_EXHAUSTED,
+            499 => UCode::CANCELLED,
+            500 => UCode::INTERNAL,
+            501 => UCode::UNIMPLEMENTED,
+            502 | 503 => UCode::UNAVAILABLE,
+            _ => UCode::UNKNOWN,
+        }
+    }
+
+    /// Checks if an operation that has failed with this code is worth being retried.
+    ///
+    /// The following codes indicate a transient condition, i.e. retrying the (unchanged) operation
+    /// later on has a reasonable chance to succeed:
+    ///
+    /// * [`UCode::UNAVAILABLE`] - the service or the transport is (temporarily) not reachable,
+    /// * [`UCode::DEADLINE_EXCEEDED`] - the operation did not complete in time, e.g. due to a congested network,
+    /// * [`UCode::ABORTED`] - the operation has been aborted due to a concurrency conflict,
+    /// * [`UCode::RESOURCE_EXHAUSTED`] - a quota or rate limit has been hit which is expected to recover over time.
+    ///
+    /// All other codes indicate either success or a failure that will occur again
+    /// unless the request or the system state is changed, e.g. [`UCode::INVALID_ARGUMENT`],
+    /// [`UCode::NOT_FOUND`] or [`UCode::PERMISSION_DENIED`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UCode;
+    ///
+    /// assert!(UCode::UNAVAILABLE.is_retryable());
+    /// assert!(!UCode::INVALID_ARGUMENT.is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        self.category() == ErrorCategory::Transient
+    }
+
+    /// Gets a human readable description of this code's meaning.
+    ///
+    /// The descriptions follow the wording used for the corresponding
+    /// [gRPC status codes](https://grpc.github.io/grpc/core/md_doc_statuscodes.html).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::UCode;
+    ///
+    /// assert_eq!(UCode::NOT_FOUND.description(), "The requested entity was not found");
+    /// ```
+    pub fn description(&self) -> &'static str {
+        match self {
+            UCode::OK => "The operation completed successfully",
+            UCode::CANCELLED => "The operation was cancelled, typically by the caller",
+            UCode::UNKNOWN => "An unknown error occurred",
+            UCode::INVALID_ARGUMENT => "The client specified an invalid argument",
+            UCode::DEADLINE_EXCEEDED => "The deadline expired before the operation could complete",
+            UCode::NOT_FOUND => "The requested entity was not found",
+            UCode::ALREADY_EXISTS => "The entity that the client attempted to create already exists",
+            UCode::PERMISSION_DENIED => {
+                "The caller does not have permission to execute the specified operation"
+            }
+            UCode::RESOURCE_EXHAUSTED => {
+                "Some resource has been exhausted, e.g. a per-user quota or the available memory"
+            }
+            UCode::FAILED_PRECONDITION => {
+                "The operation was rejected because the system is not in a state required for its execution"
+            }
+            UCode::ABORTED => "The operation was aborted, typically due to a concurrency issue",
+            UCode::OUT_OF_RANGE => "The operation was attempted past the valid range",
+            UCode::UNIMPLEMENTED => "The operation is not implemented or not supported",
+            UCode::INTERNAL => "An internal error occurred",
+            UCode::UNAVAILABLE => "The service is currently unavailable",
+            UCode::DATA_LOSS => "Unrecoverable data loss or corruption occurred",
+            UCode::UNAUTHENTICATED => {
+                "The request does not have valid authentication credentials for the operation"
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for UStatus {
+    /// Maps an I/O error to a status with a corresponding code and the error's message.
+    ///
+    /// Error kinds that do not have an obvious counterpart are mapped to [`UCode::INTERNAL`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{Error, ErrorKind};
+    /// use up_rust::{UCode, UStatus};
+    ///
+    /// let status = UStatus::from(Error::new(ErrorKind::TimedOut, "no reply from peer"));
+    /// assert_eq!(status.get_code(), UCode::DEADLINE_EXCEEDED);
+    /// assert_eq!(status.get_message(), "no reply from peer");
+    /// ```
+    fn from(value: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        let code = match value.kind() {
+            ErrorKind::NotFound => UCode::NOT_FOUND,
+            ErrorKind::PermissionDenied => UCode::PERMISSION_DENIED,
+            ErrorKind::TimedOut => UCode::DEADLINE_EXCEEDED,
+            ErrorKind::AlreadyExists | ErrorKind::AddrInUse => UCode::ALREADY_EXISTS,
+            ErrorKind::InvalidInput | ErrorKind::InvalidData => UCode::INVALID_ARGUMENT,
+            ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::AddrNotAvailable
+            | ErrorKind::BrokenPipe
+            | ErrorKind::WouldBlock => UCode::UNAVAILABLE,
+            ErrorKind::Interrupted => UCode::ABORTED,
+            ErrorKind::UnexpectedEof => UCode::DATA_LOSS,
+            ErrorKind::Unsupported => UCode::UNIMPLEMENTED,
+            ErrorKind::OutOfMemory => UCode::RESOURCE_EXHAUSTED,
+            _ => UCode::INTERNAL,
+        };
+        UStatus::fail_with_code(code, value.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for UCode {
+    /// Serializes a code to its canonical name, e.g. `NOT_FOUND`.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use protobuf::EnumFull;
+        serializer.serialize_str(self.descriptor().name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for UCode {
+    /// Deserializes a code from its canonical name, e.g. `NOT_FOUND`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use protobuf::EnumFull;
+        let name = String::deserialize(deserializer)?;
+        UCode::enum_descriptor()
+            .value_by_name(&name)
+            .and_then(|value| value.cast::<UCode>())
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown code: {}", name)))
+    }
+}
+
+// the representation of a UStatus used with serde, omitting the status' details
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerdeStatus {
+    code: UCode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for UStatus {
+    /// Serializes a status to a structure containing its code and (optional) message.
+    ///
+    /// The status' details are not included.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerdeStatus {
+            code: self.get_code(),
+            message: self.message.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for UStatus {
+    /// Deserializes a status from a structure containing its code and (optional) message.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let status = SerdeStatus::deserialize(deserializer)?;
+        Ok(UStatus {
+            code: status.code.into(),
+            message: status.message,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl UStatus {
+    /// Creates a status from a `google.rpc.Status`.
+    ///
+    /// The status code is mapped using [`UCode::from_grpc_code`], while the message and the details
+    /// are copied as is. An empty message is mapped to a status without a message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use up_rust::{GoogleRpcStatus, UCode, UStatus};
+    ///
+    /// let grpc_status = GoogleRpcStatus {
+    ///     code: 5,
+    ///     message: "no such vehicle".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let status = UStatus::from_google_rpc_status(&grpc_status);
+    /// assert_eq!(status.get_code(), UCode::NOT_FOUND);
+    /// assert_eq!(status.get_message(), "no such vehicle");
+    /// ```
+    pub fn from_google_rpc_status(status: &GoogleRpcStatus) -> UStatus {
+        UStatus {
+            code: UCode::from_grpc_code(status.code).into(),
+            message: Some(status.message.clone()).filter(|msg| !msg.is_empty()),
+            details: status.details.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a `google.rpc.Status` from this status.
+    ///
+    /// This is the inverse of [`UStatus::from_google_rpc_status`].
+    pub fn to_google_rpc_status(&self) -> GoogleRpcStatus {
+        GoogleRpcStatus {
+            code: self.get_code().value(),
+            message: self.message.clone().unwrap_or_default(),
+            details: self.details.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl From<&GoogleRpcStatus> for UStatus {
+    fn from(value: &GoogleRpcStatus) -> Self {
+        UStatus::from_google_rpc_status(value)
+    }
+}
+
+impl From<tokio::time::error::Elapsed> for UStatus {
+    /// Maps the expiration of a [`tokio::time::timeout`] to a status with code [`UCode::DEADLINE_EXCEEDED`].
+    fn from(value: tokio::time::error::Elapsed) -> Self {
+        UStatus::fail_with_code(UCode::DEADLINE_EXCEEDED, value.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use protobuf::{Enum, EnumOrUnknown};
+    use std::io::ErrorKind;
+    use test_case::test_case;
+
+    #[test]
+    fn test_severity_ordering() {
+        let codes = [
+            UCode::OK,
+            UCode::CANCELLED,
+            UCode::NOT_FOUND,
+            UCode::PERMISSION_DENIED,
+            UCode::UNAVAILABLE,
+            UCode::UNIMPLEMENTED,
+            UCode::INTERNAL,
+        ];
+        assert!(codes
+            .windows(2)
+            .all(|pair| pair[0].severity() < pair[1].severity()));
+        assert_eq!(UCode::INTERNAL.severity(), UCode::DATA_LOSS.severity());
+    }
+
+    #[test]
+    fn test_merge_picks_most_severe_code() {
+        let status = UStatus::merge(&[
+            UStatus::internal("out of memory"),
+            UStatus::permission_denied("not allowed"),
+            UStatus::unavailable("transport down"),
+        ]);
+        assert_eq!(status.get_code(), UCode::INTERNAL);
+    }
+
+    #[test]
+    fn test_merge_mixed_statuses() {
+        let status = UStatus::merge(&[
+            UStatus::ok(),
+            UStatus::unavailable("transport down"),
+            UStatus::ok(),
+            UStatus::invalid_argument("bad filter"),
+            UStatus::fail_with_code(UCode::NOT_FOUND, ""),
+        ]);
+        assert_eq!(status.get_code(), UCode::UNAVAILABLE);
+        assert_eq!(status.get_message(), "transport down; bad filter");
+    }
+
+    #[test_case(&[]; "for no statuses")]
+    #[test_case(&[UStatus::ok(), UStatus::ok()]; "for successful statuses")]
+    fn test_merge_succeeds(statuses: &[UStatus]) {
+        assert!(UStatus::merge(statuses).is_success());
+    }
+
+    #[test_case(0, UCode::OK; "for OK")]
+    #[test_case(14, UCode::UNAVAILABLE; "for UNAVAILABLE")]
+    #[test_case(16, UCode::UNAUTHENTICATED; "for UNAUTHENTICATED")]
+    #[test_case(17, UCode::UNKNOWN; "for unknown code")]
+    #[test_case(-1, UCode::UNKNOWN; "for negative code")]
+    fn test_from_grpc_code(code: i32, expected_code: UCode) {
+        assert_eq!(UCode::from_grpc_code(code), expected_code);
+    }
+
+    #[test_case(UCode::OK, 200; "for OK")]
+    #[test_case(UCode::CANCELLED, 499; "for CANCELLED")]
+    #[test_case(UCode::UNKNOWN, 500; "for UNKNOWN")]
+    #[test_case(UCode::INVALID_ARGUMENT, 400; "for INVALID_ARGUMENT")]
+    #[test_case(UCode::DEADLINE_EXCEEDED, 504; "for DEADLINE_EXCEEDED")]
+    #[test_case(UCode::NOT_FOUND, 404; "for NOT_FOUND")]
+    #[test_case(UCode::ALREADY_EXISTS, 409; "for ALREADY_EXISTS")]
+    #[test_case(UCode::PERMISSION_DENIED, 403; "for PERMISSION_DENIED")]
+    #[test_case(UCode::RESOURCE_EXHAUSTED, 429; "for RESOURCE_EXHAUSTED")]
+    #[test_case(UCode::FAILED_PRECONDITION, 400; "for FAILED_PRECONDITION")]
+    #[test_case(UCode::ABORTED, 409; "for ABORTED")]
+    #[test_case(UCode::OUT_OF_RANGE, 400; "for OUT_OF_RANGE")]
+    #[test_case(UCode::UNIMPLEMENTED, 501; "for UNIMPLEMENTED")]
+    #[test_case(UCode::INTERNAL, 500; "for INTERNAL")]
+    #[test_case(UCode::UNAVAILABLE, 503; "for UNAVAILABLE")]
+    #[test_case(UCode::DATA_LOSS, 500; "for DATA_LOSS")]
+    #[test_case(UCode::UNAUTHENTICATED, 401; "for UNAUTHENTICATED")]
+    fn test_to_http_status(code: UCode, expected_status: u16) {
+        assert_eq!(code.to_http_status(), expected_status);
+    }
+
+    #[test_case(200, UCode::OK; "for 200")]
+    #[test_case(201, UCode::OK; "for 201")]
+    #[test_case(400, UCode::INVALID_ARGUMENT; "for 400")]
+    #[test_case(401, UCode::UNAUTHENTICATED; "for 401")]
+    #[test_case(403, UCode::PERMISSION_DENIED; "for 403")]
+    #[test_case(404, UCode::NOT_FOUND; "for 404")]
+    #[test_case(408, UCode::DEADLINE_EXCEEDED; "for 408")]
+    #[test_case(409, UCode::ALREADY_EXISTS; "for 409")]
+    #[test_case(429, UCode::RESOURCE_EXHAUSTED; "for 429")]
+    #[test_case(499, UCode::CANCELLED; "for 499")]
+    #[test_case(500, UCode::INTERNAL; "for 500")]
+    #[test_case(501, UCode::UNIMPLEMENTED; "for 501")]
+    #[test_case(502, UCode::UNAVAILABLE; "for 502")]
+    #[test_case(503, UCode::UNAVAILABLE; "for 503")]
+    #[test_case(504, UCode::DEADLINE_EXCEEDED; "for 504")]
+    #[test_case(302, UCode::UNKNOWN; "for redirect")]
+    #[test_case(418, UCode::UNKNOWN; "for unmapped client error")]
+    fn test_from_http_status(status: u16, expected_code: UCode) {
+        assert_eq!(UCode::from_http_status(status), expected_code);
+    }
+
+    #[test]
+    fn test_http_status_round_trip_preserves_status() {
+        for code in UCode::VALUES {
+            let status = code.to_http_status();
+            assert_eq!(UCode::from_http_status(status).to_http_status(), status);
+        }
+    }
+
+    #[cfg(feature = "grpc")]
+    #[test]
+    fn test_google_rpc_status_round_trip() {
+        use protobuf::well_known_types::any::Any;
+
+        let detail = Any {
+            type_url: "type.googleapis.com/google.rpc.ErrorInfo".to_string(),
+            value: vec![0x0a, 0x03, 0x66, 0x6f, 0x6f],
+            ..Default::default()
+        };
+        let grpc_status = GoogleRpcStatus {
+            code: UCode::PERMISSION_DENIED.value(),
+            message: "not allowed".to_string(),
+            details: vec![detail.clone()],
+            ..Default::default()
+        };
+
+        let status = UStatus::from(&grpc_status);
+        assert_eq!(status.get_code(), UCode::PERMISSION_DENIED);
+        assert_eq!(status.get_message(), "not allowed");
+        assert_eq!(status.details, vec![detail]);
+        assert_eq!(status.to_google_rpc_status(), grpc_status);
+    }
 
     #[test]
     fn test_is_failed() {
@@ -189,4 +848,103 @@ mod tests {
             assert_eq!(ustatus.is_success(), *code == UCode::OK);
         });
     }
+
+    #[test]
+    fn test_category_is_consistent_for_all_codes() {
+        UCode::VALUES.iter().for_each(|code| {
+            let category = code.category();
+            assert_eq!(
+                category == ErrorCategory::Success,
+                *code == UCode::OK,
+                "unexpected category {:?} for {:?}",
+                category,
+                code
+            );
+            assert_eq!(category == ErrorCategory::Transient, code.is_retryable());
+        });
+        let categories: std::collections::HashSet<ErrorCategory> =
+            UCode::VALUES.iter().map(UCode::category).collect();
+        assert_eq!(categories.len(), 4, "every category should be in use");
+    }
+
+    #[test_case(ErrorKind::NotFound, UCode::NOT_FOUND; "for NotFound")]
+    #[test_case(ErrorKind::PermissionDenied, UCode::PERMISSION_DENIED; "for PermissionDenied")]
+    #[test_case(ErrorKind::TimedOut, UCode::DEADLINE_EXCEEDED; "for TimedOut")]
+    #[test_case(ErrorKind::AlreadyExists, UCode::ALREADY_EXISTS; "for AlreadyExists")]
+    #[test_case(ErrorKind::InvalidInput, UCode::INVALID_ARGUMENT; "for InvalidInput")]
+    #[test_case(ErrorKind::ConnectionRefused, UCode::UNAVAILABLE; "for ConnectionRefused")]
+    #[test_case(ErrorKind::BrokenPipe, UCode::UNAVAILABLE; "for BrokenPipe")]
+    #[test_case(ErrorKind::Interrupted, UCode::ABORTED; "for Interrupted")]
+    #[test_case(ErrorKind::UnexpectedEof, UCode::DATA_LOSS; "for UnexpectedEof")]
+    #[test_case(ErrorKind::Unsupported, UCode::UNIMPLEMENTED; "for Unsupported")]
+    #[test_case(ErrorKind::OutOfMemory, UCode::RESOURCE_EXHAUSTED; "for OutOfMemory")]
+    #[test_case(ErrorKind::WriteZero, UCode::INTERNAL; "for WriteZero")]
+    #[test_case(ErrorKind::Other, UCode::INTERNAL; "for Other")]
+    fn test_from_io_error(kind: ErrorKind, expected_code: UCode) {
+        let status = UStatus::from(std::io::Error::new(kind, "I/O failed"));
+        assert_eq!(status.get_code(), expected_code);
+        assert_eq!(status.get_message(), "I/O failed");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test_case(UStatus::ok(), r#"{"code":"OK"}"#; "for OK status")]
+    #[test_case(
+        UStatus::fail_with_code(UCode::NOT_FOUND, "no such method"),
+        r#"{"code":"NOT_FOUND","message":"no such method"}"#;
+        "for failed status with message")]
+    fn test_serde_json_round_trip(status: UStatus, expected_json: &str) {
+        let json = serde_json::to_string(&status).unwrap();
+        assert_eq!(json, expected_json);
+        let deserialized: UStatus = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, status);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_deserialize_code_fails_for_unknown_name() {
+        assert_eq!(
+            serde_json::from_str::<UCode>(r#""UNAVAILABLE""#).unwrap(),
+            UCode::UNAVAILABLE
+        );
+        assert!(serde_json::from_str::<UCode>(r#""NOT_A_CODE""#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_from_elapsed() {
+        let elapsed = tokio::time::timeout(std::time::Duration::ZERO, std::future::pending::<()>())
+            .await
+            .unwrap_err();
+        let status = UStatus::from(elapsed);
+        assert_eq!(status.get_code(), UCode::DEADLINE_EXCEEDED);
+    }
+
+    #[test]
+    fn test_description_is_available_for_all_codes() {
+        UCode::VALUES.iter().for_each(|code| {
+            assert!(
+                !code.description().is_empty(),
+                "no description for {:?}",
+                code
+            );
+        });
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        UCode::VALUES.iter().for_each(|code| {
+            let expected = matches!(
+                code,
+                UCode::UNAVAILABLE
+                    | UCode::DEADLINE_EXCEEDED
+                    | UCode::ABORTED
+                    | UCode::RESOURCE_EXHAUSTED
+            );
+            assert_eq!(
+                code.is_retryable(),
+                expected,
+                "unexpected result for {:?}",
+                code
+            );
+        });
+    }
 }
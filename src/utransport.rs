@@ -11,6 +11,10 @@
  * SPDX-License-Identifier: Apache-2.0
  ********************************************************************************/
 
+mod in_memory_transport;
+
+pub use in_memory_transport::InMemoryTransport;
+
 use async_trait::async_trait;
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
@@ -118,6 +122,8 @@ pub trait UListener: Send + Sync {
 
     /// Performs some action on receipt of an error.
     ///
+    /// The default implementation does nothing.
+    ///
     /// # Parameters
     ///
     /// * `err` - The error as `UStatus`
@@ -130,9 +136,15 @@ pub trait UListener: Send + Sync {
     ///
     /// # Note for `UTransport` implementers
     ///
+    /// Transports are expected to invoke this function if a message that matches the listener's
+    /// filter criteria could not be delivered to the listener, e.g. because it could not be
+    /// decoded or because the connection to the underlying messaging infrastructure has been lost
+    /// while receiving it. The status should indicate the reason, e.g. using
+    /// [`UCode::DATA_LOSS`](crate::UCode::DATA_LOSS) or [`UCode::UNAVAILABLE`](crate::UCode::UNAVAILABLE).
+    ///
     /// Because `on_error()` is async you may choose to either `.await` it in the current context
     /// or spawn it onto a new task and await there to allow current context to immediately continue.
-    async fn on_error(&self, err: UStatus);
+    async fn on_error(&self, _err: UStatus) {}
 }
 
 /// [`UTransport`] is the uP-L1 interface that provides a common API for uE developers to send and receive messages.
@@ -161,6 +173,39 @@ pub trait UTransport: Send + Sync {
     /// Returns an error if the message could not be sent.
     async fn send(&self, message: UMessage) -> Result<(), UStatus>;
 
+    /// Sends a message without waiting for the transport to become ready for sending it.
+    ///
+    /// In contrast to [`UTransport::send`], this function fails immediately if the message cannot be
+    /// handed over to the underlying transport right away, e.g. because the transport's outbound
+    /// queue is full or the connection is congested. Latency sensitive callers can use this
+    /// function to shed load instead of waiting for the transport.
+    ///
+    /// This default implementation simply invokes [`UTransport::send`], i.e. it may wait for the
+    /// transport. Implementations that are able to detect congestion should override this function.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to send. Please refer to [`UTransport::send`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`UCode::RESOURCE_EXHAUSTED`] if the message cannot be sent without waiting.
+    /// Otherwise, returns the same errors as [`UTransport::send`].
+    async fn try_send(&self, message: UMessage) -> Result<(), UStatus> {
+        self.send(message).await
+    }
+
+    /// Checks if this transport is currently connected to the underlying messaging infrastructure.
+    ///
+    /// Applications can use this function during startup to wait for the transport to become
+    /// available, or to fail fast instead of trying to send messages that cannot be delivered.
+    ///
+    /// This default implementation always returns `true`. Transports that maintain a connection,
+    /// e.g. to a broker, should override this function.
+    async fn is_connected(&self) -> bool {
+        true
+    }
+
     /// Receives a message from the transport.
     ///
     /// This default implementation returns an error with [`UCode::UNIMPLEMENTED`].
@@ -491,6 +536,19 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
+    struct SendOnlyTransport {
+        sent_messages: Mutex<Vec<UMessage>>,
+    }
+
+    #[async_trait]
+    impl UTransport for SendOnlyTransport {
+        async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+            self.sent_messages.lock().unwrap().push(message);
+            Ok(())
+        }
+    }
+
     #[derive(Clone, Debug)]
     struct ListenerBaz;
     #[async_trait]
@@ -527,6 +585,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_try_send_defaults_to_send() {
+        let transport = SendOnlyTransport::default();
+        let send_res = task::block_on(transport.try_send(UMessage::default()));
+        assert!(send_res.is_ok());
+        assert_eq!(transport.sent_messages.lock().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_register_and_receive() {
         let mut up_client_foo = UPClientFoo::default();
@@ -0,0 +1,667 @@
+/********************************************************************************
+ * Copyright (c) 2024 Contributors to the Eclipse Foundation
+ *
+ * See the NOTICE file(s) distributed with this work for additional
+ * information regarding copyright ownership.
+ *
+ * This program and the accompanying materials are made available under the
+ * terms of the Apache License Version 2.0 which is available at
+ * https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ ********************************************************************************/
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::{
+    ComparableListener, UAttributes, UAttributesValidators, UCode, UListener, UMessage, UStatus,
+    UTransport, UUri,
+};
+
+#[derive(Clone, PartialEq, Eq)]
+struct RegisteredListener {
+    source_filter: UUri,
+    sink_filter: Option<UUri>,
+    listener: ComparableListener,
+}
+
+impl RegisteredListener {
+    fn matches(&self, attributes: &UAttributes) -> bool {
+        let source_matches = attributes
+            .source
+            .as_ref()
+            .is_some_and(|source| self.source_filter.matches(source));
+        let sink_matches = match (self.sink_filter.as_ref(), attributes.sink.as_ref()) {
+            (Some(sink_filter), Some(sink)) => sink_filter.matches(sink),
+            (None, None) => true,
+            _ => false,
+        };
+        source_matches && sink_matches
+    }
+}
+
+/// A [`UTransport`] that delivers messages to listeners that have been registered with
+/// the same transport instance, i.e. within the same process.
+///
+/// A message is dispatched to all listeners whose source and sink filters [match](UUri::matches)
+/// the message's source and sink address. Listeners are invoked sequentially, before
+/// [`UTransport::send`] returns. Listeners with a higher [priority](Self::register_listener_with_priority)
+/// are invoked before listeners with a lower priority. Listeners with the same priority are invoked
+/// in the order in which they have been registered.
+///
+/// This transport can be used for testing (uEntities without needing to connect to a real
+/// transport) and for exchanging messages among multiple components running in the same process.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use up_rust::{InMemoryTransport, UMessageBuilder, UTransport, UUri};
+/// use up_rust::communication::{ChannelListener, OverflowPolicy};
+///
+/// # async_std::task::block_on(async {
+/// let transport = InMemoryTransport::new();
+/// let topic = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+/// let (listener, mut receiver) = ChannelListener::new(10, OverflowPolicy::DropOldest);
+/// transport.register_listener(&topic, None, Arc::new(listener)).await.unwrap();
+///
+/// transport.send(UMessageBuilder::publish(topic).build().unwrap()).await.unwrap();
+/// assert!(receiver.try_recv().is_some());
+/// # });
+/// ```
+#[derive(Default)]
+pub struct InMemoryTransport {
+    // ordered by descending priority, listeners with the same priority are ordered by registration time
+    listeners: Mutex<Vec<(RegisteredListener, i32)>>,
+}
+
+impl InMemoryTransport {
+    /// Creates a new transport without any listeners.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a listener for messages matching the given filters, using an explicit priority.
+    ///
+    /// The priority determines the order in which listeners are invoked for a message that matches
+    /// the filters of multiple listeners. This allows gateways to set up deterministic chains of
+    /// listeners, e.g. for having a listener that records messages always process a message before
+    /// the listener implementing the business logic. Listeners with a higher priority are invoked first.
+    /// Listeners with the same priority are invoked in the order in which they have been registered.
+    /// Listeners registered using [`UTransport::register_listener`] have priority 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `source_filter` - The source address pattern that messages need to match.
+    /// * `sink_filter` - The sink address pattern that messages need to match.
+    /// * `listener` - The listener to invoke.
+    /// * `priority` - The priority of the listener.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`UCode::ALREADY_EXISTS`] if the listener has already been registered
+    /// for the given filters.
+    pub fn register_listener_with_priority(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+        priority: i32,
+    ) -> Result<(), UStatus> {
+        let registered_listener = RegisteredListener {
+            source_filter: source_filter.to_owned(),
+            sink_filter: sink_filter.cloned(),
+            listener: ComparableListener::new(listener),
+        };
+        let mut listeners = self.listeners.lock().unwrap();
+        if listeners
+            .iter()
+            .any(|(registered, _)| *registered == registered_listener)
+        {
+            return Err(UStatus::fail_with_code(
+                UCode::ALREADY_EXISTS,
+                "listener has already been registered for filters",
+            ));
+        }
+        // insert after all listeners that have the same or a higher priority
+        let index =
+            listeners.partition_point(|(_, existing_priority)| *existing_priority >= priority);
+        listeners.insert(index, (registered_listener, priority));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UTransport for InMemoryTransport {
+    /// Dispatches a message to all matching listeners.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`UCode::INVALID_ARGUMENT`] if the message does not contain valid attributes.
+    async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+        let Some(attributes) = message.attributes.as_ref() else {
+            return Err(UStatus::fail_with_code(
+                UCode::INVALID_ARGUMENT,
+                "message has no attributes",
+            ));
+        };
+        UAttributesValidators::get_validator_for_attributes(attributes)
+            .validate(attributes)
+            .map_err(|e| UStatus::fail_with_code(UCode::INVALID_ARGUMENT, e.to_string()))?;
+
+        // listeners may send messages themselves, so we must not hold the lock while invoking them
+        let matching_listeners: Vec<ComparableListener> = self
+            .listeners
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(registered, _)| registered.matches(attributes))
+            .map(|(registered, _)| registered.listener.clone())
+            .collect();
+        for listener in matching_listeners {
+            listener.on_receive(message.clone()).await;
+        }
+        Ok(())
+    }
+
+    /// Registers a listener for messages matching the given filters.
+    ///
+    /// The same listener can be registered for multiple filters, and multiple listeners can be
+    /// registered for the same filters. The listener is registered with priority 0, see
+    /// [`InMemoryTransport::register_listener_with_priority`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`UCode::ALREADY_EXISTS`] if the listener has already been registered
+    /// for the given filters.
+    async fn register_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        self.register_listener_with_priority(source_filter, sink_filter, listener, 0)
+    }
+
+    /// Unregisters a listener.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`UCode::NOT_FOUND`] if the listener has not been registered for the given filters.
+    async fn unregister_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        let registered_listener = RegisteredListener {
+            source_filter: source_filter.to_owned(),
+            sink_filter: sink_filter.cloned(),
+            listener: ComparableListener::new(listener),
+        };
+        let mut listeners = self.listeners.lock().unwrap();
+        if let Some(index) = listeners
+            .iter()
+            .position(|(registered, _)| *registered == registered_listener)
+        {
+            listeners.remove(index);
+            Ok(())
+        } else {
+            Err(UStatus::fail_with_code(
+                UCode::NOT_FOUND,
+                "no such listener registered for filters",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use futures::stream::{self, BoxStream};
+    use futures::StreamExt;
+    use test_case::test_case;
+    use tokio::sync::Notify;
+    use tokio_util::sync::CancellationToken;
+
+    use crate::communication::{
+        CallOptions, ChannelListener, InMemoryRpcClient, InMemoryRpcServer, MessageReceiver,
+        OverflowPolicy, RequestContext, RequestHandler, RpcClient, RpcServer,
+        ServiceInvocationError, StreamingRequestHandler, UPayload,
+    };
+    use crate::{LocalUriProvider, UMessageBuilder, UPayloadFormat};
+
+    use super::*;
+
+    struct TestUriProvider {
+        ue_id: u32,
+    }
+
+    impl LocalUriProvider for TestUriProvider {
+        fn get_authority(&self) -> String {
+            "my-vehicle".to_string()
+        }
+        fn get_resource_uri(&self, resource_id: u16) -> UUri {
+            UUri {
+                authority_name: self.get_authority(),
+                ue_id: self.ue_id,
+                ue_version_major: 0x01,
+                resource_id: resource_id as u32,
+                ..Default::default()
+            }
+        }
+        fn get_source_uri(&self) -> UUri {
+            self.get_resource_uri(0x0000)
+        }
+    }
+
+    struct UppercaseHandler;
+
+    #[async_trait]
+    impl RequestHandler for UppercaseHandler {
+        async fn invoke_method(
+            &self,
+            _resource_id: u16,
+            payload: UPayload,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            let text = String::from_utf8(payload.payload().to_vec())
+                .map_err(|e| ServiceInvocationError::InvalidArgument(e.to_string()))?;
+            Ok(Some(UPayload::new(
+                Bytes::from(text.to_uppercase()),
+                UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+            )))
+        }
+    }
+
+    fn channel() -> (Arc<dyn UListener>, MessageReceiver) {
+        let (listener, receiver) = ChannelListener::new(10, OverflowPolicy::DropNewest);
+        (Arc::new(listener), receiver)
+    }
+
+    fn publish_message(topic: &str) -> UMessage {
+        UMessageBuilder::publish(UUri::try_from(topic).unwrap())
+            .build()
+            .unwrap()
+    }
+
+    #[test_case("//my-vehicle/4210/1/B24D", true; "for identical topic")]
+    #[test_case("//*/4210/1/FFFF", true; "for wildcard filter")]
+    #[test_case("//my-vehicle/4210/1/B24E", false; "for different topic")]
+    #[tokio::test]
+    async fn test_send_dispatches_to_matching_listeners(source_filter: &str, expected: bool) {
+        let transport = InMemoryTransport::new();
+        let (listener, mut receiver) = channel();
+        transport
+            .register_listener(&UUri::try_from(source_filter).unwrap(), None, listener)
+            .await
+            .unwrap();
+
+        assert!(transport
+            .send(publish_message("//my-vehicle/4210/1/B24D"))
+            .await
+            .is_ok());
+        assert_eq!(receiver.try_recv().is_some(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_send_considers_sink_filter() {
+        let transport = InMemoryTransport::new();
+        let origin = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+        let destination = UUri::try_from("//other-vehicle/A1/1/0").unwrap();
+        let (notification_listener, mut notifications) = channel();
+        let (publish_listener, mut published_messages) = channel();
+        transport
+            .register_listener(&origin, Some(&destination), notification_listener)
+            .await
+            .unwrap();
+        transport
+            .register_listener(&origin, None, publish_listener)
+            .await
+            .unwrap();
+
+        let notification = UMessageBuilder::notification(origin.clone(), destination)
+            .build()
+            .unwrap();
+        assert!(transport.send(notification).await.is_ok());
+        assert!(notifications.try_recv().is_some());
+        assert!(published_messages.try_recv().is_none());
+
+        assert!(transport
+            .send(publish_message("//my-vehicle/4210/1/B24D"))
+            .await
+            .is_ok());
+        assert!(notifications.try_recv().is_none());
+        assert!(published_messages.try_recv().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_send_dispatches_to_multiple_listeners_for_same_filter() {
+        let transport = InMemoryTransport::new();
+        let topic = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+        let (first_listener, mut first_receiver) = channel();
+        let (second_listener, mut second_receiver) = channel();
+        transport
+            .register_listener(&topic, None, first_listener.clone())
+            .await
+            .unwrap();
+        transport
+            .register_listener(&topic, None, second_listener)
+            .await
+            .unwrap();
+
+        assert!(transport
+            .send(publish_message("//my-vehicle/4210/1/B24D"))
+            .await
+            .is_ok());
+        assert!(first_receiver.try_recv().is_some());
+        assert!(second_receiver.try_recv().is_some());
+
+        transport
+            .unregister_listener(&topic, None, first_listener)
+            .await
+            .unwrap();
+        assert!(transport
+            .send(publish_message("//my-vehicle/4210/1/B24D"))
+            .await
+            .is_ok());
+        assert!(first_receiver.try_recv().is_none());
+        assert!(second_receiver.try_recv().is_some());
+    }
+
+    struct RecordingListener {
+        id: usize,
+        invocations: Arc<Mutex<Vec<usize>>>,
+    }
+
+    #[async_trait]
+    impl UListener for RecordingListener {
+        async fn on_receive(&self, _msg: UMessage) {
+            self.invocations.lock().unwrap().push(self.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_invokes_listeners_in_order_of_priority() {
+        let transport = InMemoryTransport::new();
+        let invocations = Arc::new(Mutex::new(Vec::new()));
+        let topic = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+        let wildcard_filter = UUri::try_from("//*/4210/1/FFFF").unwrap();
+        for (id, source_filter, priority) in [
+            (0, &topic, 0),
+            (1, &wildcard_filter, 10),
+            (2, &topic, -5),
+            (3, &wildcard_filter, 0),
+            (4, &topic, 10),
+        ] {
+            let listener = Arc::new(RecordingListener {
+                id,
+                invocations: invocations.clone(),
+            });
+            transport
+                .register_listener_with_priority(source_filter, None, listener, priority)
+                .unwrap();
+        }
+
+        assert!(transport
+            .send(publish_message("//my-vehicle/4210/1/B24D"))
+            .await
+            .is_ok());
+        // listeners with the same priority are invoked in the order of registration
+        assert_eq!(*invocations.lock().unwrap(), vec![1, 4, 0, 3, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_for_invalid_message() {
+        let transport = InMemoryTransport::new();
+        let mut message = publish_message("//my-vehicle/4210/1/B24D");
+        message.attributes.as_mut().unwrap().id.clear();
+
+        let result = transport.send(message).await;
+        assert!(result.is_err_and(|e| e.get_code() == UCode::INVALID_ARGUMENT));
+    }
+
+    #[tokio::test]
+    async fn test_register_listener_fails_for_duplicate_registration() {
+        let transport = InMemoryTransport::new();
+        let topic = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+        let (listener, _receiver) = channel();
+        assert!(transport
+            .register_listener(&topic, None, listener.clone())
+            .await
+            .is_ok());
+
+        let result = transport
+            .register_listener(&topic, None, listener.clone())
+            .await;
+        assert!(result.is_err_and(|e| e.get_code() == UCode::ALREADY_EXISTS));
+    }
+
+    #[tokio::test]
+    async fn test_unregister_listener_fails_for_unknown_listener() {
+        let transport = InMemoryTransport::new();
+        let topic = UUri::try_from("//my-vehicle/4210/1/B24D").unwrap();
+        let (listener, _receiver) = channel();
+
+        let result = transport.unregister_listener(&topic, None, listener).await;
+        assert!(result.is_err_and(|e| e.get_code() == UCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_rpc_client_invokes_rpc_server_method() {
+        let transport = Arc::new(InMemoryTransport::new());
+        let server = InMemoryRpcServer::new(
+            transport.clone(),
+            Arc::new(TestUriProvider { ue_id: 0x4210 }),
+        );
+        let client = InMemoryRpcClient::new(
+            transport.clone(),
+            Arc::new(TestUriProvider { ue_id: 0xA100 }),
+        );
+        server
+            .register_endpoint(None, 0x1000, Arc::new(UppercaseHandler))
+            .await
+            .unwrap();
+        let method = TestUriProvider { ue_id: 0x4210 }.get_resource_uri(0x1000);
+
+        let response = client
+            .invoke_method(
+                method.clone(),
+                CallOptions::new(5_000, None, None, None),
+                Some(UPayload::new(
+                    Bytes::from("hello"),
+                    UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+                )),
+            )
+            .await;
+        assert!(response
+            .is_ok_and(|payload| payload.is_some_and(|payload| payload.payload() == "HELLO")));
+
+        let response = client
+            .invoke_method(
+                method,
+                CallOptions::new(5_000, None, None, None),
+                Some(UPayload::new(
+                    Bytes::from(vec![0xFF, 0xFE]),
+                    UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+                )),
+            )
+            .await;
+        assert!(response.is_err_and(|e| matches!(e, ServiceInvocationError::InvalidArgument(_))));
+    }
+
+    struct CountdownHandler;
+
+    impl StreamingRequestHandler for CountdownHandler {
+        fn invoke_method(
+            &self,
+            _resource_id: u16,
+            payload: UPayload,
+        ) -> BoxStream<'static, Result<UPayload, ServiceInvocationError>> {
+            let items: Vec<Result<UPayload, ServiceInvocationError>> =
+                match payload.payload().first() {
+                    Some(start) => (0..=*start)
+                        .rev()
+                        .map(|n| {
+                            Ok(UPayload::new(
+                                Bytes::from(n.to_string()),
+                                UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+                            ))
+                        })
+                        .collect(),
+                    None => vec![Err(ServiceInvocationError::InvalidArgument(
+                        "no start value".to_string(),
+                    ))],
+                };
+            Box::pin(stream::iter(items))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rpc_client_consumes_stream_of_rpc_server() {
+        let transport = Arc::new(InMemoryTransport::new());
+        let server = InMemoryRpcServer::new(
+            transport.clone(),
+            Arc::new(TestUriProvider { ue_id: 0x4210 }),
+        );
+        let client = InMemoryRpcClient::new(
+            transport.clone(),
+            Arc::new(TestUriProvider { ue_id: 0xA100 }),
+        );
+        server
+            .register_streaming_endpoint(None, 0x1000, Arc::new(CountdownHandler))
+            .await
+            .unwrap();
+        let method = TestUriProvider { ue_id: 0x4210 }.get_resource_uri(0x1000);
+
+        let items: Vec<String> = client
+            .invoke_streaming_method(
+                method.clone(),
+                CallOptions::new(5_000, None, None, None),
+                Some(UPayload::new(
+                    Bytes::from(vec![3]),
+                    UPayloadFormat::UPAYLOAD_FORMAT_RAW,
+                )),
+            )
+            .await
+            .unwrap()
+            .map(|item| String::from_utf8(item.unwrap().payload().to_vec()).unwrap())
+            .collect()
+            .await;
+        // the stream ends with the final response message sent by the server
+        assert_eq!(items, vec!["3", "2", "1", "0"]);
+
+        let items: Vec<Result<UPayload, ServiceInvocationError>> = client
+            .invoke_streaming_method(method, CallOptions::new(5_000, None, None, None), None)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(items.len(), 1);
+        assert!(matches!(
+            items[0],
+            Err(ServiceInvocationError::InvalidArgument(_))
+        ));
+    }
+
+    /// A transport that delivers messages asynchronously, like transports for real messaging
+    /// infrastructures do, instead of waiting for the listeners to process them.
+    struct DeferringTransport {
+        delegate: Arc<InMemoryTransport>,
+    }
+
+    #[async_trait]
+    impl UTransport for DeferringTransport {
+        async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+            let delegate = self.delegate.clone();
+            tokio::spawn(async move { delegate.send(message).await });
+            Ok(())
+        }
+
+        async fn register_listener(
+            &self,
+            source_filter: &UUri,
+            sink_filter: Option<&UUri>,
+            listener: Arc<dyn UListener>,
+        ) -> Result<(), UStatus> {
+            self.delegate
+                .register_listener(source_filter, sink_filter, listener)
+                .await
+        }
+
+        async fn unregister_listener(
+            &self,
+            source_filter: &UUri,
+            sink_filter: Option<&UUri>,
+            listener: Arc<dyn UListener>,
+        ) -> Result<(), UStatus> {
+            self.delegate
+                .unregister_listener(source_filter, sink_filter, listener)
+                .await
+        }
+    }
+
+    /// A handler that never completes on its own.
+    #[derive(Default)]
+    struct StalledHandler {
+        invoked: Notify,
+        cancelled: Notify,
+    }
+
+    #[async_trait]
+    impl RequestHandler for StalledHandler {
+        async fn invoke_method(
+            &self,
+            _resource_id: u16,
+            _payload: UPayload,
+        ) -> Result<Option<UPayload>, ServiceInvocationError> {
+            self.invoked.notify_one();
+            std::future::pending().await
+        }
+
+        async fn on_cancelled(&self, _context: &RequestContext) {
+            self.cancelled.notify_one();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rpc_client_cancels_request_processed_by_rpc_server() {
+        let transport = Arc::new(DeferringTransport {
+            delegate: Arc::new(InMemoryTransport::new()),
+        });
+        let server = InMemoryRpcServer::new(
+            transport.clone(),
+            Arc::new(TestUriProvider { ue_id: 0x4210 }),
+        );
+        let client = InMemoryRpcClient::new(
+            transport.clone(),
+            Arc::new(TestUriProvider { ue_id: 0xA100 }),
+        );
+        let request_handler = Arc::new(StalledHandler::default());
+        server
+            .register_endpoint(None, 0x1000, request_handler.clone())
+            .await
+            .unwrap();
+        server.enable_request_cancellation().await.unwrap();
+        let method = TestUriProvider { ue_id: 0x4210 }.get_resource_uri(0x1000);
+        let token = CancellationToken::new();
+        let mut call_options = CallOptions::new(5_000, None, None, None);
+        call_options.with_cancellation_token(token.clone());
+
+        let (result, _) = tokio::join!(client.invoke_method(method, call_options, None), async {
+            request_handler.invoked.notified().await;
+            token.cancel();
+        });
+
+        assert!(result.is_err_and(|e| matches!(e, ServiceInvocationError::Cancelled)));
+        // the server aborts the handler once it has received the cancellation message
+        assert!(
+            tokio::time::timeout(Duration::from_secs(1), request_handler.cancelled.notified())
+                .await
+                .is_ok()
+        );
+    }
+}